@@ -34,11 +34,11 @@ async fn delete_bucket(cxt: ContextData, path: web::Path<String>) -> HttpRespons
 #[get("/bucket/{bucket_name}")]
 async fn get_bucket(cxt: ContextData, path: web::Path<String>) -> WebResponse<web::Json<Bucket>> {
     let client = cxt.get_ref();
-    let bucket_name = format!("/{}/_stats", path);
+    let bucket_name = path.to_string();
     client.get_bucket(bucket_name.as_str()).await
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "elastic-search"))]
 mod buckets_endpoints {
     use crate::errors::{ErrorResponse, SuccessfulResponse};
     use crate::searcher::elastic::build_elastic_client;
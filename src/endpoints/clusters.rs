@@ -31,7 +31,7 @@ async fn get_cluster(cxt: ContextData, path: web::Path<String>) -> WebResponse<w
     client.get_cluster(cluster_name.as_str()).await
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "elastic-search"))]
 mod cluster_endpoints {
     use crate::errors::SuccessfulResponse;
     use crate::searcher::elastic::build_elastic_client;
@@ -76,7 +76,7 @@ mod cluster_endpoints {
             .await;
 
         let get_all_clusters: Vec<Cluster> = test::read_body_json(get_all_clusters_resp).await;
-        assert_eq!(get_all_clusters.len() > 0, true);
+        assert!(!get_all_clusters.is_empty());
 
         // Get cluster request by index
         // let get_cluster_resp = TestRequest::get()
@@ -39,7 +39,7 @@ async fn get_document(
         .await
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "elastic-search"))]
 mod documents_endpoints {
     use crate::errors::{ErrorResponse, SuccessfulResponse};
     use crate::searcher::elastic::build_elastic_client;
@@ -3,6 +3,7 @@ pub mod clusters;
 pub mod documents;
 pub mod hello;
 pub mod loader;
+pub mod saved_searches;
 pub mod searcher;
 pub mod similarities;
 
@@ -0,0 +1,67 @@
+use crate::endpoints::ContextData;
+use crate::errors::WebResponse;
+use crate::wrappers::saved_search::{SavedSearch, SavedSearchForm};
+use crate::wrappers::search_response::SearchResponse;
+
+use actix_web::{delete, get, post, put, web, HttpResponse};
+
+#[get("/saved-searches")]
+async fn all_saved_searches(cxt: ContextData) -> WebResponse<web::Json<Vec<SavedSearch>>> {
+    let client = cxt.get_ref();
+    client.get_all_saved_searches().await
+}
+
+#[post("/saved-search/new")]
+async fn new_saved_search(
+    cxt: ContextData,
+    form: web::Json<SavedSearchForm>,
+) -> WebResponse<web::Json<SavedSearch>> {
+    let client = cxt.get_ref();
+    form.validate()?;
+    client.create_saved_search(&form.0).await
+}
+
+#[get("/saved-search/{search_id}")]
+async fn get_saved_search(
+    cxt: ContextData,
+    path: web::Path<String>,
+) -> WebResponse<web::Json<SavedSearch>> {
+    let client = cxt.get_ref();
+    client.get_saved_search(path.as_str()).await
+}
+
+#[put("/saved-search/{search_id}")]
+async fn update_saved_search(
+    cxt: ContextData,
+    path: web::Path<String>,
+    form: web::Json<SavedSearchForm>,
+) -> WebResponse<web::Json<SavedSearch>> {
+    let client = cxt.get_ref();
+    form.validate()?;
+    client.update_saved_search(path.as_str(), &form.0).await
+}
+
+#[delete("/saved-search/{search_id}")]
+async fn delete_saved_search(cxt: ContextData, path: web::Path<String>) -> HttpResponse {
+    let client = cxt.get_ref();
+    client.delete_saved_search(path.as_str()).await
+}
+
+/// Runs saved search found by id or name over its buckets and records the
+/// run time if search has succeeded.
+#[post("/saved-search/{search_id}/run")]
+async fn run_saved_search(
+    cxt: ContextData,
+    path: web::Path<String>,
+) -> WebResponse<web::Json<SearchResponse>> {
+    let client = cxt.get_ref();
+    let search = client.get_saved_search(path.as_str()).await?;
+    search.params.validate_matching()?;
+    let search_response = match search.buckets.as_deref() {
+        Some(buckets) => client.search_bucket(buckets, &search.params).await?,
+        None => client.search_all(&search.params).await?,
+    };
+
+    client.mark_saved_search_run(&search.id).await?;
+    Ok(search_response)
+}
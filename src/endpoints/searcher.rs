@@ -1,17 +1,22 @@
 use crate::endpoints::ContextData;
 use crate::errors::WebResponse;
-use crate::wrappers::document::Document;
+use crate::wrappers::export::ExportForm;
 use crate::wrappers::search_params::*;
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::{SuggestForm, Suggestion};
 
-use actix_web::{post, web};
+use actix_web::web::Bytes;
+use actix_web::{delete, get, post, web, HttpResponse};
+use futures::stream::{self, StreamExt, TryStreamExt};
 
 #[post("/search")]
 async fn search_all(
     cxt: ContextData,
     form: web::Json<SearchParams>,
-) -> WebResponse<web::Json<Vec<Document>>> {
+) -> WebResponse<web::Json<SearchResponse>> {
     let client = cxt.get_ref();
     let search_form = form.0;
+    search_form.validate_matching()?;
     client.search_all(&search_form).await
 }
 
@@ -20,20 +25,61 @@ async fn search_target(
     cxt: ContextData,
     path: web::Path<String>,
     form: web::Json<SearchParams>,
-) -> WebResponse<web::Json<Vec<Document>>> {
+) -> WebResponse<web::Json<SearchResponse>> {
     let client = cxt.get_ref();
     let search_form = form.0;
+    search_form.validate_matching()?;
     let buckets = path.as_ref();
     client.search_bucket(buckets.as_str(), &search_form).await
 }
 
-#[cfg(test)]
+/// Streams all documents found by passed parameters. Documents are loaded
+/// and written by batches, so export is not buffered in memory.
+#[post("/search/export")]
+async fn export_documents(
+    cxt: ContextData,
+    export_form: web::Query<ExportForm>,
+    form: web::Json<SearchParams>,
+) -> WebResponse<HttpResponse> {
+    let client = cxt.get_ref();
+    let fields = export_form.fields()?;
+    let format = export_form.format;
+    let buckets = export_form.buckets.as_deref();
+    form.validate_matching()?;
+    let documents = client.export_documents(buckets, &form.0).await?;
+
+    let header = stream::iter(format.header(&fields)).map(|header| Ok(Bytes::from(header)));
+    let batches = documents.map_ok(move |batch| Bytes::from(format.write_batch(&batch, &fields)));
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .streaming(header.chain(batches)))
+}
+
+/// Completes prefix typed by user by documents names and frequent terms
+/// of documents text.
+#[get("/suggest")]
+async fn suggest(
+    cxt: ContextData,
+    form: web::Query<SuggestForm>,
+) -> WebResponse<web::Json<Vec<Suggestion>>> {
+    let client = cxt.get_ref();
+    form.validate()?;
+    client.suggest(form.buckets.as_deref(), &form).await
+}
+
+#[delete("/search/cursor")]
+async fn close_cursor(cxt: ContextData, form: web::Json<CursorForm>) -> HttpResponse {
+    let client = cxt.get_ref();
+    client.close_cursor(form.cursor.as_str()).await
+}
+
+#[cfg(all(test, feature = "elastic-search"))]
 mod searcher_endpoints {
     use crate::searcher::elastic::build_elastic_client;
     use crate::searcher::elastic::context::ElasticContext;
     use crate::service::{build_service, init_service_parameters};
-    use crate::wrappers::document::Document;
     use crate::wrappers::search_params::*;
+    use crate::wrappers::search_response::SearchResponse;
 
     use actix_web::test::TestRequest;
     use actix_web::{test, web, App};
@@ -89,8 +135,8 @@ mod searcher_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() > 0, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(!founded.hits.is_empty());
 
         // Found documents request by document name with filter
         let mut search_params = SearchParams::default();
@@ -102,8 +148,8 @@ mod searcher_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() >= 1, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(!founded.hits.is_empty());
 
         // Found documents request by document name and bucket name
         let mut search_params = SearchParams::default();
@@ -114,8 +160,8 @@ mod searcher_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() >= 4, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(founded.hits.len() >= 4);
 
         // Found documents request by document name and bucket name
         let mut search_params = SearchParams::default();
@@ -126,8 +172,8 @@ mod searcher_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() >= 4, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(founded.hits.len() >= 4);
     }
 
     #[test]
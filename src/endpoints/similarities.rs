@@ -1,7 +1,7 @@
 use crate::endpoints::ContextData;
 use crate::errors::WebResponse;
-use crate::wrappers::document::Document;
 use crate::wrappers::search_params::*;
+use crate::wrappers::search_response::SearchResponse;
 
 use actix_web::{post, web};
 
@@ -9,7 +9,7 @@ use actix_web::{post, web};
 async fn search_similar_docs(
     cxt: ContextData,
     form: web::Json<SearchParams>,
-) -> WebResponse<web::Json<Vec<Document>>> {
+) -> WebResponse<web::Json<SearchResponse>> {
     let client = cxt.get_ref();
     let search_form = form.0;
     client.similar_all(&search_form).await
@@ -20,20 +20,20 @@ async fn search_similar_docs_target(
     cxt: ContextData,
     path: web::Path<String>,
     form: web::Json<SearchParams>,
-) -> WebResponse<web::Json<Vec<Document>>> {
+) -> WebResponse<web::Json<SearchResponse>> {
     let client = cxt.get_ref();
     let search_form = form.0;
     let buckets = path.as_ref();
     client.similar_bucket(buckets.as_str(), &search_form).await
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "elastic-search"))]
 mod similarities_endpoints {
     use crate::searcher::elastic::build_elastic_client;
     use crate::searcher::elastic::context::ElasticContext;
     use crate::service::{build_service, init_service_parameters};
-    use crate::wrappers::document::Document;
     use crate::wrappers::search_params::*;
+    use crate::wrappers::search_response::SearchResponse;
 
     use actix_web::test::TestRequest;
     use actix_web::{test, web, App};
@@ -88,8 +88,8 @@ mod similarities_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() > 0, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(!founded.hits.is_empty());
 
         // Found documents request by document name with filter
         let mut search_params = SearchParams::default();
@@ -101,8 +101,8 @@ mod similarities_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() >= 1, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(!founded.hits.is_empty());
 
         // Found documents request by document name and bucket name
         let mut search_params = SearchParams::default();
@@ -113,8 +113,8 @@ mod similarities_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() >= 4, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(founded.hits.len() >= 4);
 
         // Found documents request by document name and bucket name
         let mut search_params = SearchParams::default();
@@ -125,7 +125,7 @@ mod similarities_endpoints {
             .send_request(&test_app)
             .await;
 
-        let founded_documents: Vec<Document> = test::read_body_json(search_resp).await;
-        assert_eq!(founded_documents.len() >= 4, true);
+        let founded: SearchResponse = test::read_body_json(search_resp).await;
+        assert!(founded.hits.len() >= 4);
     }
 }
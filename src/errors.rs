@@ -8,10 +8,13 @@ pub type WebResponse<T> = Result<T, WebError>;
 
 #[derive(Error, Debug)]
 pub enum WebError {
+    #[cfg(feature = "elastic-search")]
     #[error("Error while getting cluster: {0}")]
     GetCluster(String),
+    #[cfg(feature = "elastic-search")]
     #[error("Error while creating cluster: {0}")]
     CreateCluster(String),
+    #[cfg(feature = "elastic-search")]
     #[error("Error while deleting cluster: {0}")]
     DeletingCluster(String),
     #[error("Error while getting bucket: {0}")]
@@ -24,10 +27,16 @@ pub enum WebError {
     GetDocument(String),
     #[error("Failed while creating document: {0}")]
     CreateDocument(String),
+    #[cfg(feature = "elastic-search")]
     #[error("Failed while updating document: {0}")]
     UpdateDocument(String),
     #[error("Failed while deleting document: {0}")]
     DeleteDocument(String),
+    #[cfg(any(
+        feature = "elastic-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
     #[error("Failed while serializing document: {0}")]
     DocumentSerializing(String),
     #[error("Failed while searching: {0}")]
@@ -36,6 +45,15 @@ pub enum WebError {
     LoadFileFailed(String),
     #[error("Response error: {0}")]
     ResponseError(String),
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[error("Failed while accessing storage: {0}")]
+    StorageFailed(String),
+    #[error("Failed while processing saved search: {0}")]
+    SavedSearch(String),
 }
 
 impl WebError {
@@ -44,12 +62,26 @@ impl WebError {
             WebError::SearchFailed(_) => "SearchError",
             WebError::ResponseError(_) => "ResponseError",
             WebError::GetBucket(_) => "GetBucketError",
+            #[cfg(feature = "elastic-search")]
             WebError::GetCluster(_) => "GetClusterError",
             WebError::GetDocument(_) => "GetDocumentError",
             WebError::CreateDocument(_) => "CreateDocumentError",
+            #[cfg(feature = "elastic-search")]
             WebError::UpdateDocument(_) => "UpdateDocumentError",
             WebError::DeleteDocument(_) => "DeleteDocumentError",
+            #[cfg(any(
+                feature = "elastic-search",
+                feature = "tantivy-search",
+                feature = "sqlite-search"
+            ))]
             WebError::DocumentSerializing(_) => "DocumentSerializingError",
+            #[cfg(any(
+                feature = "default-search",
+                feature = "tantivy-search",
+                feature = "sqlite-search"
+            ))]
+            WebError::StorageFailed(_) => "StorageError",
+            WebError::SavedSearch(_) => "SavedSearchError",
             _ => "RuntimeError",
         }
         .to_string()
@@ -75,12 +107,26 @@ impl ResponseError for WebError {
             WebError::SearchFailed(_) => StatusCode::BAD_REQUEST,
             WebError::ResponseError(_) => StatusCode::BAD_REQUEST,
             WebError::GetBucket(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "elastic-search")]
             WebError::GetCluster(_) => StatusCode::BAD_REQUEST,
             WebError::GetDocument(_) => StatusCode::BAD_REQUEST,
             WebError::CreateDocument(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "elastic-search")]
             WebError::UpdateDocument(_) => StatusCode::BAD_REQUEST,
             WebError::DeleteDocument(_) => StatusCode::BAD_REQUEST,
+            #[cfg(any(
+                feature = "elastic-search",
+                feature = "tantivy-search",
+                feature = "sqlite-search"
+            ))]
             WebError::DocumentSerializing(_) => StatusCode::BAD_REQUEST,
+            #[cfg(any(
+                feature = "default-search",
+                feature = "tantivy-search",
+                feature = "sqlite-search"
+            ))]
+            WebError::StorageFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            WebError::SavedSearch(_) => StatusCode::BAD_REQUEST,
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -1,4 +1,4 @@
-use chrono::{DateTime, ParseResult, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, ParseResult, Utc};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 pub fn serialize_dt<S>(dt: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
@@ -19,19 +19,70 @@ where
     D: Deserializer<'de>,
 {
     String::deserialize(deserializer)
-        .and_then(|value| Ok(format_datetime(value.as_str())))
-        .and_then(|value| Ok(value.ok()))
+        .map(|value| format_datetime(value.as_str()))
+        .map(|value| value.ok())
+}
+
+/// Parses datetime passed as search parameter, e.g. "2023-09-15",
+/// "2023-09-15 12:00:00" or "2023-09-15T12:00:00Z".
+pub fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(dt) = format_datetime(value) {
+        return Some(dt);
+    }
+
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(dt.and_utc());
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+/// Parses upper bound of datetime range passed as search parameter. Date
+/// without time means the whole day, so it is parsed as the end of the day.
+pub fn parse_datetime_end(value: &str) -> Option<DateTime<Utc>> {
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => date
+            .and_hms_nano_opt(23, 59, 59, 999_999_999)
+            .map(|dt| dt.and_utc()),
+        Err(_) => parse_datetime(value),
+    }
 }
 
 fn format_datetime(value: &str) -> ParseResult<DateTime<Utc>> {
-    Utc.datetime_from_str(value, "%Y-%m-%dT%H:%M:%SZ")
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%SZ").map(|dt| dt.and_utc())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         // let result = add(2, 2);
         // assert_eq!(result, 4);
     }
+
+    #[test]
+    fn parse_datetime_formats() {
+        let expected = "2023-09-15T00:00:00Z";
+        for value in ["2023-09-15", "2023-09-15 00:00:00", expected] {
+            let dt = parse_datetime(value).unwrap();
+            assert_eq!(dt.format("%Y-%m-%dT%H:%M:%SZ").to_string(), expected);
+        }
+
+        assert!(parse_datetime("15.09.2023").is_none());
+    }
+
+    #[test]
+    fn parse_datetime_end_of_day() {
+        let dt = parse_datetime_end("2023-09-15").unwrap();
+        assert_eq!(dt.format("%Y-%m-%dT%H:%M:%SZ").to_string(), "2023-09-15T23:59:59Z");
+
+        let dt = parse_datetime_end("2023-09-15 12:00:00").unwrap();
+        assert_eq!(dt, parse_datetime("2023-09-15T12:00:00Z").unwrap());
+        assert!(parse_datetime_end("15.09.2023").is_none());
+    }
 }
@@ -0,0 +1,442 @@
+//! Behaviour which every embedded backend must share. Tests are run over
+//! `ServiceClient` of each backend enabled by cargo features, so backends
+//! are checked by the same fixture and expectations.
+
+use crate::searcher::service_client::ServiceClient;
+use crate::wrappers::aggregation::{Facet, FacetBucket};
+use crate::wrappers::document::{Document, DocumentBuilder};
+use crate::wrappers::pattern::{PatternField, PatternMode, PatternQuery};
+use crate::wrappers::proximity::ProximityQuery;
+use crate::wrappers::search_params::{SearchParams, SortField, SortKey, SortOrder};
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::{SuggestField, SuggestForm, Suggestion};
+
+use datetime::parse_datetime;
+
+pub fn build_document(bucket: &str, id: &str, size: i32, entity_data: &str) -> Document {
+    DocumentBuilder::default()
+        .bucket_uuid(bucket.to_string())
+        .bucket_path("/tmp/test_document".to_string())
+        .document_name(format!("{}.txt", id))
+        .document_path(format!("/tmp/dir/{}.txt", id))
+        .document_size(size)
+        .document_type("document".to_string())
+        .document_extension("txt".to_string())
+        .document_permissions(777)
+        .document_md5_hash(id.to_string())
+        .document_ssdeep_hash(format!("3a:{}", id))
+        .entity_data(entity_data.to_string())
+        .entity_keywords(Vec::default())
+        .highlight(None)
+        .document_created(parse_datetime("2023-09-15"))
+        .document_modified(parse_datetime("2023-09-15"))
+        .build()
+        .unwrap()
+}
+
+pub fn build_documents() -> Vec<Document> {
+    let entities = [
+        (
+            "first",
+            1024,
+            "The lease fee shall be paid by the Tenant in a lump sum.",
+        ),
+        (
+            "second",
+            2048,
+            "Security deposit shall be refunded to the Tenant.",
+        ),
+        (
+            "third",
+            4096,
+            "Rostov, together with his commander Vasily Denisov, comes home.",
+        ),
+    ];
+
+    entities
+        .into_iter()
+        .map(|(id, size, data)| build_document("test_bucket", id, size, data))
+        .collect()
+}
+
+pub fn search_params(query: &str) -> SearchParams {
+    SearchParams {
+        query: query.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Returns in-memory clients of enabled embedded backends with indexed
+/// documents of fixture.
+async fn build_clients() -> Vec<(&'static str, Box<dyn ServiceClient>)> {
+    let mut clients: Vec<(&'static str, Box<dyn ServiceClient>)> = Vec::new();
+
+    #[cfg(feature = "default-search")]
+    {
+        use crate::searcher::own_engine::context::OtherContext;
+        use crate::searcher::own_engine::engine::SearchEngine;
+        clients.push(("own", Box::new(OtherContext::_new(SearchEngine::new()))));
+    }
+
+    #[cfg(feature = "tantivy-search")]
+    {
+        use crate::searcher::tantivy_engine::context::TantivyContext;
+        use crate::searcher::tantivy_engine::engine::TantivyEngine;
+        let context = TantivyContext::_new(TantivyEngine::new());
+        clients.push(("tantivy", Box::new(context)));
+    }
+
+    #[cfg(feature = "sqlite-search")]
+    {
+        use crate::searcher::sqlite_engine::context::SqliteContext;
+        use crate::searcher::sqlite_engine::engine::SqliteEngine;
+        let context = SqliteContext::_new(SqliteEngine::open_in_memory().unwrap());
+        clients.push(("sqlite", Box::new(context)));
+    }
+
+    for (backend, client) in clients.iter() {
+        for document in build_documents() {
+            let response = client.create_document(&document).await;
+            assert!(response.status().is_success(), "{}", backend);
+        }
+    }
+
+    clients
+}
+
+async fn search(client: &dyn ServiceClient, params: &SearchParams) -> SearchResponse {
+    client.search_all(params).await.unwrap().0
+}
+
+fn hits_ids(search_response: &SearchResponse) -> Vec<&str> {
+    search_response
+        .hits
+        .iter()
+        .map(|hit| hit.document_md5_hash.as_str())
+        .collect()
+}
+
+#[actix_web::test]
+async fn search_by_terms() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let founded = search(client, &search_params("tenant")).await.hits;
+        assert_eq!(founded.len(), 2, "{}", backend);
+        assert!(
+            founded.iter().all(|doc| doc.highlight.is_some()),
+            "{}",
+            backend
+        );
+
+        let params = search_params("lump denisov");
+        let founded = client.search_bucket("test_bucket", &params).await.unwrap();
+        assert_eq!(founded.hits.len(), 2, "{}", backend);
+
+        let founded = search(client, &search_params("*")).await.hits;
+        assert_eq!(founded.len(), 3, "{}", backend);
+
+        let mut params = search_params("tenant");
+        params.result_size = 1;
+        let search_response = search(client, &params).await;
+        assert_eq!(search_response.total, 2, "{}", backend);
+        assert_eq!(search_response.hits.len(), 1, "{}", backend);
+
+        let hit = &search_response.hits[0];
+        assert_eq!(hit.id, hit.document_md5_hash, "{}", backend);
+        assert_eq!(hit.index, "test_bucket", "{}", backend);
+        assert_eq!(hit.score, search_response.max_score, "{}", backend);
+
+        let founded = search(client, &search_params("commander")).await.hits;
+        let highlight = founded[0].highlight.as_ref().unwrap();
+        assert!(
+            highlight.entity_data[0].contains("commander"),
+            "{}",
+            backend
+        );
+    }
+}
+
+#[actix_web::test]
+async fn search_by_proximity() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("tenant");
+        params.result_size = 1;
+        params.proximity = vec![ProximityQuery {
+            terms: vec!["tenant".to_string(), "lease fee".to_string()],
+            within: 5,
+            ordered: false,
+        }];
+
+        let search_response = search(client, &params).await;
+        assert_eq!(search_response.total, 1, "{}", backend);
+        assert_eq!(hits_ids(&search_response), ["first"], "{}", backend);
+
+        params.proximity[0].ordered = true;
+        assert_eq!(search(client, &params).await.total, 0, "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn search_by_pattern() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("*");
+        params.pattern = Some(PatternQuery {
+            mode: PatternMode::Regex,
+            value: "Vasily [A-Z][a-z]+,".to_string(),
+            fields: Vec::default(),
+            timeout_ms: None,
+        });
+
+        let search_response = search(client, &params).await;
+        assert_eq!(search_response.total, 1, "{}", backend);
+        assert_eq!(hits_ids(&search_response), ["third"], "{}", backend);
+        assert!(!search_response.timed_out, "{}", backend);
+
+        params.query = "tenant".to_string();
+        params.pattern = Some(PatternQuery {
+            mode: PatternMode::Wildcard,
+            value: "/TMP/*/second.txt".to_string(),
+            fields: vec![PatternField::DocumentPath],
+            timeout_ms: None,
+        });
+        let search_response = search(client, &params).await;
+        assert_eq!(hits_ids(&search_response), ["second"], "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn search_with_filters() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("tenant");
+        params.document_size_from = 2000;
+        let search_response = search(client, &params).await;
+        assert_eq!(hits_ids(&search_response), ["second"], "{}", backend);
+
+        let mut params = search_params("*");
+        params.created_date_from = "2023-10-01".to_string();
+        assert!(search(client, &params).await.hits.is_empty(), "{}", backend);
+
+        let mut params = search_params("*");
+        params.document_extension = ".docx".to_string();
+        assert!(search(client, &params).await.hits.is_empty(), "{}", backend);
+
+        params.document_extension = ".TXT".to_string();
+        params.document_type = "Document".to_string();
+        assert_eq!(search(client, &params).await.hits.len(), 3, "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn search_by_date_ranges() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("*");
+        params.modified_date_from = "2023-09-01".to_string();
+        params.modified_date_to = "2023-09-30".to_string();
+        assert_eq!(search(client, &params).await.hits.len(), 3, "{}", backend);
+
+        let mut params = search_params("*");
+        params.modified_date_to = "2023-09-01".to_string();
+        assert!(search(client, &params).await.hits.is_empty(), "{}", backend);
+
+        let mut params = search_params("*");
+        params.created_date_to = "2023-10-01".to_string();
+        assert_eq!(search(client, &params).await.hits.len(), 3, "{}", backend);
+
+        let mut document = build_document("test_bucket", "fourth", 512, "Midday report");
+        document.document_modified = parse_datetime("2023-09-20 12:00:00");
+        client.create_document(&document).await;
+        let mut params = search_params("*");
+        params.modified_date_from = "2023-09-20".to_string();
+        params.modified_date_to = "2023-09-20".to_string();
+        let search_response = search(client, &params).await;
+        assert_eq!(hits_ids(&search_response), ["fourth"], "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn search_by_path() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("*");
+        params.path_prefixes = vec!["/tmp/dir/sec".to_string(), "/tmp/dir/th".to_string()];
+        assert_eq!(search(client, &params).await.hits.len(), 2, "{}", backend);
+
+        let mut params = search_params("*");
+        params.document_path_glob = "/tmp/*/{first,third}.txt".to_string();
+        params.bucket_path_glob = "/tmp/test_?ocument".to_string();
+        assert_eq!(search(client, &params).await.hits.len(), 2, "{}", backend);
+
+        params.document_path_glob = "/tmp/*.txt".to_string();
+        assert!(search(client, &params).await.hits.is_empty(), "{}", backend);
+
+        params.document_path_glob = "/tmp/**.txt".to_string();
+        assert_eq!(search(client, &params).await.hits.len(), 3, "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn search_with_sort() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("*");
+        params.sort = vec![SortKey {
+            field: SortField::Size,
+            order: Some(SortOrder::Desc),
+        }];
+        params.result_offset = 1;
+        params.result_size = 2;
+        let search_response = search(client, &params).await;
+        assert_eq!(
+            hits_ids(&search_response),
+            ["second", "first"],
+            "{}",
+            backend
+        );
+
+        let mut params = search_params("tenant");
+        params.sort = vec![SortKey {
+            field: SortField::Name,
+            order: None,
+        }];
+        let search_response = search(client, &params).await;
+        assert_eq!(hits_ids(&search_response)[0], "first", "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn search_with_exclusions() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("*");
+        params.exclude_paths = vec!["/tmp/dir/fir".to_string()];
+        params.exclude_types = vec!["archive".to_string()];
+        assert_eq!(search(client, &params).await.hits.len(), 2, "{}", backend);
+
+        let mut params = search_params("*");
+        params.any_extensions = vec![".docx".to_string(), ".txt".to_string()];
+        assert_eq!(search(client, &params).await.hits.len(), 3, "{}", backend);
+
+        params.exclude_extensions = vec![".txt".to_string()];
+        assert!(search(client, &params).await.hits.is_empty(), "{}", backend);
+
+        let mut params = search_params("*");
+        params.any_extensions = vec!["TXT".to_string()];
+        params.exclude_types = vec!["DOCUMENT".to_string()];
+        assert!(search(client, &params).await.hits.is_empty(), "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn aggregate_facets() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let mut params = search_params("tenant");
+        let search_response = search(client, &params).await;
+        assert!(search_response.aggregations.is_empty(), "{}", backend);
+
+        params.aggregations = vec![Facet::Extension, Facet::Created, Facet::Size];
+        let aggregations = search(client, &params).await.aggregations;
+        assert_eq!(
+            aggregations[&Facet::Extension],
+            [FacetBucket::new("txt", 2)],
+            "{}",
+            backend
+        );
+        assert_eq!(
+            aggregations[&Facet::Created],
+            [FacetBucket::new("2023-09-01T00:00:00Z", 2)],
+            "{}",
+            backend
+        );
+        assert_eq!(aggregations[&Facet::Size][0].count, 2, "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn update_and_delete_document() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let updated = build_document("test_bucket", "first", 1024, "Updated agreement text");
+        assert!(client.update_document(&updated).await.status().is_success());
+
+        let document = client.get_document("test_bucket", "first").await.unwrap();
+        assert_eq!(
+            document.entity_data, "Updated agreement text",
+            "{}",
+            backend
+        );
+        let bucket = client.get_bucket("test_bucket").await.unwrap();
+        assert_eq!(bucket.docs_count, "3", "{}", backend);
+        assert!(search(client, &search_params("lump")).await.hits.is_empty());
+        let founded = search(client, &search_params("agreement")).await.hits;
+        assert_eq!(founded.len(), 1, "{}", backend);
+
+        let duplicate = client.create_document(&updated).await;
+        assert!(!duplicate.status().is_success(), "{}", backend);
+
+        let response = client.delete_document("test_bucket", "first").await;
+        assert!(response.status().is_success(), "{}", backend);
+        assert!(client.get_document("test_bucket", "first").await.is_err());
+        let response = client.delete_document("test_bucket", "first").await;
+        assert!(!response.status().is_success(), "{}", backend);
+        let founded = search(client, &search_params("agreement")).await.hits;
+        assert!(founded.is_empty(), "{}", backend);
+    }
+}
+
+#[actix_web::test]
+async fn suggest_completions() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let form = SuggestForm {
+            prefix: "T".to_string(),
+            size: 2,
+            buckets: None,
+        };
+
+        assert_eq!(
+            client.suggest(None, &form).await.unwrap().0,
+            vec![
+                Suggestion::new("third.txt", SuggestField::DocumentName, 1),
+                Suggestion::new("tenant", SuggestField::EntityData, 2),
+                Suggestion::new("the", SuggestField::EntityData, 2),
+            ],
+            "{}",
+            backend
+        );
+
+        client.delete_document("test_bucket", "second").await;
+        let suggestions = client.suggest(Some("test_bucket"), &form).await.unwrap();
+        assert_eq!(
+            suggestions[1],
+            Suggestion::new("tenant", SuggestField::EntityData, 1),
+            "{}",
+            backend
+        );
+    }
+}
+
+#[actix_web::test]
+async fn suggest_corrected_query() {
+    for (backend, client) in build_clients().await {
+        let client = client.as_ref();
+        let search_response = search(client, &search_params("Tenamt, commandr")).await;
+        assert!(search_response.hits.is_empty(), "{}", backend);
+
+        let did_you_mean = &search_response.did_you_mean;
+        assert_eq!(did_you_mean.len(), 1, "{}", backend);
+        assert_eq!(did_you_mean[0].text, "tenant, commander", "{}", backend);
+        assert_eq!(
+            did_you_mean[0].highlighted, "<em>tenant</em>, <em>commander</em>",
+            "{}",
+            backend
+        );
+
+        let search_response = search(client, &search_params("tenant")).await;
+        assert!(search_response.did_you_mean.is_empty(), "{}", backend);
+    }
+}
@@ -1,20 +1,26 @@
-use crate::errors::{SuccessfulResponse, WebError};
+use crate::errors::{SuccessfulResponse, WebError, WebResponse};
 use crate::searcher::elastic::context::ElasticContext;
+use crate::searcher::elastic::cursor::{close_cursor, export_documents, search_with_cursor};
 use crate::searcher::elastic::helper::*;
-use crate::searcher::service_client::{JsonResponse, ServiceClient};
+use crate::searcher::elastic::retry::retry_idempotent;
+use crate::searcher::elastic::saved_search::*;
+use crate::searcher::service_client::{DocumentStream, JsonResponse, ServiceClient};
 use crate::wrappers::bucket::{Bucket, BucketForm};
 use crate::wrappers::cluster::Cluster;
 use crate::wrappers::document::Document;
-use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::saved_search::{SavedSearch, SavedSearchForm};
+use crate::wrappers::search_params::{MatchOptions, SearchParams};
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::{is_low_result, SuggestForm, Suggestion};
 
 use actix_web::{web, HttpResponse, ResponseError};
 use elasticsearch::http::headers::HeaderMap;
 use elasticsearch::http::request::JsonBody;
 use elasticsearch::http::Method;
-use elasticsearch::{BulkParts, CountParts, IndexParts};
+use elasticsearch::indices::IndicesCreateParts;
+use elasticsearch::{BulkParts, CountParts};
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
-use hasher::{gen_hash, HashType};
 use serde::Deserialize;
 use serde_json::{json, Value};
 
@@ -22,8 +28,8 @@ use serde_json::{json, Value};
 impl ServiceClient for ElasticContext {
     async fn get_all_clusters(&self) -> JsonResponse<Vec<Cluster>> {
         let elastic = self.get_cxt().read().await;
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Get,
                 "/_cat/nodes",
                 HeaderMap::new(),
@@ -31,7 +37,8 @@ impl ServiceClient for ElasticContext {
                 Some(b"".as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         if response_result.is_err() {
             let err = response_result.err().unwrap();
@@ -49,8 +56,8 @@ impl ServiceClient for ElasticContext {
         let elastic = self.get_cxt().read().await;
         let cluster_name = format!("/_nodes/{}", cluster_id);
         let body = b"";
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Get,
                 cluster_name.as_str(),
                 HeaderMap::new(),
@@ -58,7 +65,8 @@ impl ServiceClient for ElasticContext {
                 Some(body.as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         if response_result.is_err() {
             let err = response_result.err().unwrap();
@@ -92,8 +100,8 @@ impl ServiceClient for ElasticContext {
         }
 
         let body = body.unwrap().as_bytes();
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Put,
                 "/_cluster/settings",
                 HeaderMap::new(),
@@ -101,7 +109,8 @@ impl ServiceClient for ElasticContext {
                 Some(body),
                 None,
             )
-            .await;
+        })
+        .await;
 
         match response_result {
             Ok(_) => SuccessfulResponse::ok_response("Ok"),
@@ -111,8 +120,8 @@ impl ServiceClient for ElasticContext {
 
     async fn get_all_buckets(&self) -> JsonResponse<Vec<Bucket>> {
         let elastic = self.get_cxt().read().await;
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Get,
                 "/_cat/indices?format=json",
                 HeaderMap::new(),
@@ -120,7 +129,8 @@ impl ServiceClient for ElasticContext {
                 Some(b"".as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         if response_result.is_err() {
             let err = response_result.err().unwrap();
@@ -130,15 +140,18 @@ impl ServiceClient for ElasticContext {
         let response = response_result.unwrap();
         match response.json::<Vec<Bucket>>().await {
             Err(err) => Err(WebError::from(err)),
-            Ok(buckets) => Ok(web::Json(buckets)),
+            Ok(mut buckets) => {
+                buckets.retain(|bucket| !is_saved_search_index(&bucket.index));
+                Ok(web::Json(buckets))
+            }
         }
     }
 
     async fn get_bucket(&self, bucket_id: &str) -> JsonResponse<Bucket> {
         let elastic = self.get_cxt().read().await;
         let bucket_name = format!("/{}/_stats", bucket_id);
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Get,
                 bucket_name.as_str(),
                 HeaderMap::new(),
@@ -146,7 +159,8 @@ impl ServiceClient for ElasticContext {
                 Some(b"".as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         if response_result.is_err() {
             let err = response_result.err().unwrap();
@@ -163,8 +177,8 @@ impl ServiceClient for ElasticContext {
 
     async fn delete_bucket(&self, bucket_id: &str) -> HttpResponse {
         let elastic = self.get_cxt().read().await;
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Delete,
                 bucket_id,
                 HeaderMap::new(),
@@ -172,7 +186,8 @@ impl ServiceClient for ElasticContext {
                 Some(b"".as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         match response_result {
             Ok(_) => SuccessfulResponse::ok_response("Ok"),
@@ -183,17 +198,17 @@ impl ServiceClient for ElasticContext {
     async fn create_bucket(&self, bucket_form: &BucketForm) -> HttpResponse {
         let elastic = self.get_cxt().read().await;
         let bucket_name = bucket_form.get_name();
-        let hasher_res = gen_hash(HashType::MD5, bucket_name.as_bytes());
-        let binding = hasher_res.unwrap();
-        let id_str = binding.get_hash_data();
         let bucket_schema: Value = serde_json::from_str(create_bucket_scheme().as_str()).unwrap();
-        let response_result = elastic
-            .index(IndexParts::IndexId(bucket_name, id_str))
-            .body(json!({
-                bucket_name: bucket_schema,
-            }))
-            .send()
-            .await;
+        let indices = elastic.indices();
+        let response_result = retry_idempotent(|| {
+            indices
+                .create(IndicesCreateParts::Index(bucket_name))
+                .body(json!({
+                    "mappings": bucket_schema,
+                }))
+                .send()
+        })
+        .await;
 
         match response_result {
             Ok(_) => SuccessfulResponse::ok_response("Ok"),
@@ -203,17 +218,20 @@ impl ServiceClient for ElasticContext {
 
     async fn check_duplication(&self, bucket_id: &str, document_id: &str) -> bool {
         let elastic = self.get_cxt().read().await;
-        let response = elastic
-            .count(CountParts::Index(&[bucket_id]))
-            .body(json!({
-                "query" : {
-                    "term" : {
-                        "document_md5_hash" : document_id
+        let indexes = [bucket_id];
+        let response = retry_idempotent(|| {
+            elastic
+                .count(CountParts::Index(&indexes))
+                .body(json!({
+                    "query" : {
+                        "term" : {
+                            "document_md5_hash" : document_id
+                        }
                     }
-                }
-            }))
-            .send()
-            .await;
+                }))
+                .send()
+        })
+        .await;
 
         let value = response.unwrap().json::<Value>().await.unwrap();
         let count = value["count"].as_i64().unwrap_or(0);
@@ -223,8 +241,8 @@ impl ServiceClient for ElasticContext {
     async fn get_document(&self, bucket_id: &str, doc_id: &str) -> JsonResponse<Document> {
         let elastic = self.get_cxt().read().await;
         let s_path = format!("/{}/_doc/{}", bucket_id, doc_id);
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Get,
                 s_path.as_str(),
                 HeaderMap::new(),
@@ -232,7 +250,8 @@ impl ServiceClient for ElasticContext {
                 Some(b"".as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         if response_result.is_err() {
             let err = response_result.err().unwrap();
@@ -302,18 +321,19 @@ impl ServiceClient for ElasticContext {
             return web_err.error_response();
         }
 
-        let document_json = document_json.unwrap();
+        let document_json = document_json.unwrap().to_string();
         let s_path = format!("/{}/_doc/{}", bucket_name, document_id);
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Put,
                 s_path.as_str(),
                 HeaderMap::new(),
                 Option::<&Value>::None,
-                Some(document_json.to_string().as_bytes()),
+                Some(document_json.as_bytes()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         match response_result {
             Ok(_) => SuccessfulResponse::ok_response("Ok"),
@@ -324,8 +344,8 @@ impl ServiceClient for ElasticContext {
     async fn delete_document(&self, bucket_id: &str, doc_id: &str) -> HttpResponse {
         let elastic = self.get_cxt().read().await;
         let s_path = format!("/{}/_doc/{}", bucket_id, doc_id);
-        let response_result = elastic
-            .send(
+        let response_result = retry_idempotent(|| {
+            elastic.send(
                 Method::Delete,
                 s_path.as_str(),
                 HeaderMap::new(),
@@ -333,7 +353,8 @@ impl ServiceClient for ElasticContext {
                 Some(b"".as_ref()),
                 None,
             )
-            .await;
+        })
+        .await;
 
         match response_result {
             Ok(_) => SuccessfulResponse::ok_response("Ok"),
@@ -381,24 +402,85 @@ impl ServiceClient for ElasticContext {
     //     SuccessfulResponse::ok_response("Ok")
     // }
 
-    async fn search_all(&self, s_params: &SearchParams) -> JsonResponse<Vec<Document>> {
+    async fn search_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse> {
         let elastic = self.get_cxt().read().await;
-        let body_value = build_search_query(s_params);
-        search_documents(&elastic, &["*"], &body_value, s_params).await
+        let body_value = build_search_query(s_params, &MatchOptions::default())?;
+        let mut search_response = match s_params.is_cursor_pagination() {
+            true => search_with_cursor(&elastic, &["*"], &body_value, s_params).await,
+            false => search_documents(&elastic, &["*"], &body_value, s_params).await,
+        }?;
+
+        if is_low_result(search_response.total, &s_params.query) {
+            let query = s_params.query.as_str();
+            search_response.did_you_mean = suggest_corrections(&elastic, &["*"], query).await;
+        }
+
+        Ok(search_response)
     }
 
     async fn search_bucket(
         &self,
         buckets_ids: &str,
         s_params: &SearchParams,
-    ) -> JsonResponse<Vec<Document>> {
+    ) -> JsonResponse<SearchResponse> {
         let elastic = self.get_cxt().read().await;
         let indexes: Vec<&str> = buckets_ids.split(',').collect();
-        let body_value = build_search_query(s_params);
-        search_documents(&elastic, indexes.as_slice(), &body_value, s_params).await
+        let match_defaults = self.match_defaults(indexes.as_slice());
+        let body_value = build_search_query(s_params, &match_defaults)?;
+        let mut search_response = match s_params.is_cursor_pagination() {
+            true => search_with_cursor(&elastic, indexes.as_slice(), &body_value, s_params).await,
+            false => search_documents(&elastic, indexes.as_slice(), &body_value, s_params).await,
+        }?;
+
+        if is_low_result(search_response.total, &s_params.query) {
+            let query = s_params.query.as_str();
+            search_response.did_you_mean = suggest_corrections(&elastic, &indexes, query).await;
+        }
+
+        Ok(search_response)
     }
 
-    async fn similar_all(&self, s_params: &SearchParams) -> JsonResponse<Vec<Document>> {
+    async fn close_cursor(&self, cursor: &str) -> HttpResponse {
+        let elastic = self.get_cxt().read().await;
+        match close_cursor(&elastic, cursor).await {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
+    }
+
+    async fn export_documents(
+        &self,
+        buckets_ids: Option<&str>,
+        s_params: &SearchParams,
+    ) -> WebResponse<DocumentStream> {
+        let elastic = self.get_cxt().read().await.clone();
+        let indexes: Vec<&str> = buckets_ids.unwrap_or("*").split(',').collect();
+        let match_defaults = match buckets_ids {
+            Some(_) => self.match_defaults(indexes.as_slice()),
+            None => MatchOptions::default(),
+        };
+
+        let mut body_value = build_search_query(s_params, &match_defaults)?;
+        if let Some(body) = body_value.as_object_mut() {
+            body.remove("highlight");
+            body.remove("aggs");
+            body.remove("track_total_hits");
+        }
+
+        export_documents(elastic, indexes.as_slice(), body_value).await
+    }
+
+    async fn suggest(
+        &self,
+        buckets_ids: Option<&str>,
+        form: &SuggestForm,
+    ) -> JsonResponse<Vec<Suggestion>> {
+        let elastic = self.get_cxt().read().await;
+        let indexes: Vec<&str> = buckets_ids.unwrap_or("*").split(',').collect();
+        suggest_documents(&elastic, indexes.as_slice(), form).await
+    }
+
+    async fn similar_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse> {
         let elastic = self.get_cxt().read().await;
         let body_value = build_search_similar_query(s_params);
         search_documents(&elastic, &["*"], &body_value, s_params).await
@@ -408,10 +490,55 @@ impl ServiceClient for ElasticContext {
         &self,
         buckets_id: &str,
         s_params: &SearchParams,
-    ) -> JsonResponse<Vec<Document>> {
+    ) -> JsonResponse<SearchResponse> {
         let elastic = self.get_cxt().read().await;
         let indexes: Vec<&str> = buckets_id.split(',').collect();
         let body_value = build_search_similar_query(s_params);
         search_documents(&elastic, indexes.as_slice(), &body_value, s_params).await
     }
+
+    async fn get_all_saved_searches(&self) -> JsonResponse<Vec<SavedSearch>> {
+        let elastic = self.get_cxt().read().await;
+        let searches = load_saved_searches(&elastic).await?;
+        Ok(web::Json(searches))
+    }
+
+    async fn get_saved_search(&self, search_id: &str) -> JsonResponse<SavedSearch> {
+        let elastic = self.get_cxt().read().await;
+        let search = get_saved_search(&elastic, search_id).await?;
+        Ok(web::Json(search))
+    }
+
+    async fn create_saved_search(&self, form: &SavedSearchForm) -> JsonResponse<SavedSearch> {
+        let elastic = self.get_cxt().read().await;
+        let search = SavedSearch::new(form);
+        store_saved_search(&elastic, &search, None).await?;
+        Ok(web::Json(search))
+    }
+
+    async fn update_saved_search(
+        &self,
+        search_id: &str,
+        form: &SavedSearchForm,
+    ) -> JsonResponse<SavedSearch> {
+        let elastic = self.get_cxt().read().await;
+        let current = get_saved_search(&elastic, search_id).await?;
+        let search = current.updated(form);
+        store_saved_search(&elastic, &search, Some(&current.name)).await?;
+        Ok(web::Json(search))
+    }
+
+    async fn delete_saved_search(&self, search_id: &str) -> HttpResponse {
+        let elastic = self.get_cxt().read().await;
+        match delete_saved_search(&elastic, search_id).await {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
+    }
+
+    async fn mark_saved_search_run(&self, search_id: &str) -> JsonResponse<SavedSearch> {
+        let elastic = self.get_cxt().read().await;
+        let search = mark_saved_search_run(&elastic, search_id).await?;
+        Ok(web::Json(search))
+    }
 }
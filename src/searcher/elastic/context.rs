@@ -1,19 +1,44 @@
+use crate::wrappers::search_params::MatchOptions;
+
 use elasticsearch::Elasticsearch;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Match options of buckets which are used if search request does not set
+/// them, it is loaded from json file passed by `BUCKET_MATCH_CONFIG`.
+pub type MatchDefaults = HashMap<String, MatchOptions>;
+
 #[derive(Default, Clone)]
 pub struct ElasticContext {
     context: Arc<RwLock<Elasticsearch>>,
+    match_defaults: Arc<MatchDefaults>,
 }
 
 impl ElasticContext {
     pub fn _new(elastic: Elasticsearch) -> Self {
         let context = Arc::new(RwLock::new(elastic));
-        ElasticContext { context }
+        ElasticContext {
+            context,
+            match_defaults: Arc::default(),
+        }
+    }
+
+    pub fn with_match_defaults(mut self, match_defaults: MatchDefaults) -> Self {
+        self.match_defaults = Arc::new(match_defaults);
+        self
     }
 
     pub fn get_cxt(&self) -> &Arc<RwLock<Elasticsearch>> {
         &self.context
     }
+
+    /// Returns defaults of the first searched bucket which has them.
+    pub fn match_defaults(&self, buckets_ids: &[&str]) -> MatchOptions {
+        buckets_ids
+            .iter()
+            .find_map(|bucket_id| self.match_defaults.get(*bucket_id))
+            .cloned()
+            .unwrap_or_default()
+    }
 }
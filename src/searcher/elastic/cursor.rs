@@ -0,0 +1,268 @@
+use crate::errors::{WebError, WebResponse};
+use crate::searcher::elastic::helper::{build_sort, extract_search_response, read_response};
+use crate::searcher::elastic::retry::retry_idempotent;
+use crate::searcher::service_client::DocumentStream;
+use crate::wrappers::aggregation::Facet;
+use crate::wrappers::search_params::{SearchParams, SortField, SortKey};
+use crate::wrappers::search_response::SearchResponse;
+
+use actix_web::web;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use elasticsearch::{Elasticsearch, OpenPointInTimeParts, SearchParts};
+use futures::stream;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Keep-alive and size of batches to export all found documents.
+const EXPORT_KEEP_ALIVE: &str = "1m";
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+/// Keep-alive of point-in-time is limited because every opened one keeps
+/// segments of searched indices from merging.
+const MAX_KEEP_ALIVE_SECS: u64 = 60 * 60;
+
+/// State of cursor pagination which is passed to client as opaque token.
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct Cursor {
+    pit_id: String,
+    keep_alive: String,
+    search_after: Vec<Value>,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let cursor = serde_json::to_vec(self).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(cursor)
+    }
+
+    pub fn decode(token: &str) -> Result<Self, WebError> {
+        URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .ok()
+            .and_then(|cursor| serde_json::from_slice(&cursor).ok())
+            .ok_or_else(|| WebError::SearchFailed("Invalid cursor".to_string()))
+    }
+}
+
+/// Checks passed keep-alive like `30s`, `5m` or `1h` is within limit.
+pub fn validate_keep_alive(keep_alive: &str) -> Result<(), WebError> {
+    let unit_len = keep_alive.chars().last().map_or(0, char::len_utf8);
+    let (value, unit) = keep_alive.split_at(keep_alive.len() - unit_len);
+    let seconds = match (value.parse::<u64>(), unit) {
+        (Ok(value), "s") => Some(value),
+        (Ok(value), "m") => value.checked_mul(60),
+        (Ok(value), "h") => value.checked_mul(60 * 60),
+        _ => None,
+    };
+
+    match seconds {
+        Some(seconds) if seconds > 0 && seconds <= MAX_KEEP_ALIVE_SECS => Ok(()),
+        _ => {
+            let msg = format!(
+                "Invalid keep-alive {}: expected duration like 1m up to {}s",
+                keep_alive, MAX_KEEP_ALIVE_SECS
+            );
+            Err(WebError::SearchFailed(msg))
+        }
+    }
+}
+
+/// Searches page of documents within point-in-time. The point-in-time is
+/// opened by the first page and closed after the last one, so cursor is
+/// returned only while there may be more documents.
+pub async fn search_with_cursor(
+    elastic: &Elasticsearch,
+    indexes: &[&str],
+    body_value: &Value,
+    es_params: &SearchParams,
+) -> WebResponse<web::Json<SearchResponse>> {
+    let mut cursor = match es_params.cursor.as_deref() {
+        Some(token) => Cursor::decode(token)?,
+        None => Cursor {
+            pit_id: String::default(),
+            keep_alive: es_params.keep_alive.clone().unwrap_or_default(),
+            search_after: Vec::default(),
+        },
+    };
+
+    if let Some(keep_alive) = es_params.keep_alive.as_ref() {
+        cursor.keep_alive = keep_alive.to_owned();
+    }
+
+    validate_keep_alive(&cursor.keep_alive)?;
+    if cursor.pit_id.is_empty() {
+        cursor.pit_id = open_point_in_time(elastic, indexes, &cursor.keep_alive).await?;
+    }
+
+    let result_size = es_params.result_size;
+    let facets = es_params.aggregations.as_slice();
+    let (mut search_response, has_more) =
+        search_page(elastic, body_value, &mut cursor, result_size, facets).await?;
+    search_response.cursor = has_more.then(|| cursor.encode());
+    Ok(web::Json(search_response))
+}
+
+/// Returns stream of all documents matched by passed query which are loaded
+/// by batches within point-in-time. The point-in-time is closed after the
+/// last batch or expires if stream is dropped before.
+pub async fn export_documents(
+    elastic: Elasticsearch,
+    indexes: &[&str],
+    body_value: Value,
+) -> Result<DocumentStream, WebError> {
+    let pit_id = open_point_in_time(&elastic, indexes, EXPORT_KEEP_ALIVE).await?;
+    let cursor = Cursor {
+        pit_id,
+        keep_alive: EXPORT_KEEP_ALIVE.to_string(),
+        search_after: Vec::default(),
+    };
+
+    let documents = stream::try_unfold(Some(cursor), move |cursor| {
+        let elastic = elastic.clone();
+        let body_value = body_value.clone();
+        async move {
+            let Some(mut cursor) = cursor else {
+                return Ok(None);
+            };
+
+            let batch_size = EXPORT_BATCH_SIZE;
+            let (search_response, has_more) =
+                search_page(&elastic, &body_value, &mut cursor, batch_size, &[]).await?;
+            let documents = search_response.hits.into_iter().map(|hit| hit.document);
+            Ok(Some((documents.collect(), has_more.then_some(cursor))))
+        }
+    });
+
+    Ok(Box::pin(documents))
+}
+
+/// Searches page of documents after passed cursor and moves cursor to the
+/// last found document. Returns false with the last page and closes its
+/// point-in-time.
+async fn search_page(
+    elastic: &Elasticsearch,
+    body_value: &Value,
+    cursor: &mut Cursor,
+    result_size: i64,
+    facets: &[Facet],
+) -> Result<(SearchResponse, bool), WebError> {
+    let mut body_value = body_value.to_owned();
+    body_value["pit"] = json!({ "id": cursor.pit_id, "keep_alive": cursor.keep_alive });
+    if body_value["sort"].is_null() {
+        let score_key = SortKey {
+            field: SortField::Score,
+            order: None,
+        };
+        body_value["sort"] = build_sort(&[score_key]);
+    }
+
+    if !cursor.search_after.is_empty() {
+        body_value["search_after"] = Value::Array(cursor.search_after.to_owned());
+    }
+
+    let response = retry_idempotent(|| {
+        elastic
+            .search(SearchParts::None)
+            .size(result_size)
+            .body(&body_value)
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SearchFailed(err.to_string()))?;
+
+    let common_object = read_response(response).await?;
+    if let Some(pit_id) = common_object[&"pit_id"].as_str() {
+        cursor.pit_id = pit_id.to_string();
+    }
+
+    let hits = common_object[&"hits"][&"hits"].as_array();
+    let is_last_page = hits.map_or(0, Vec::len) < result_size.max(0) as usize;
+    let last_sort = hits
+        .and_then(|hits| hits.last())
+        .and_then(|hit| hit[&"sort"].as_array());
+
+    let search_response = extract_search_response(&common_object, facets);
+    match (is_last_page, last_sort) {
+        (false, Some(last_sort)) => {
+            cursor.search_after = last_sort.to_owned();
+            Ok((search_response, true))
+        }
+        _ => {
+            if let Err(err) = close_point_in_time(elastic, &cursor.pit_id).await {
+                log::warn!("Failed to close point-in-time: {}", err);
+            }
+            Ok((search_response, false))
+        }
+    }
+}
+
+/// Closes point-in-time of passed cursor before its keep-alive expires.
+pub async fn close_cursor(elastic: &Elasticsearch, token: &str) -> Result<(), WebError> {
+    let cursor = Cursor::decode(token)?;
+    close_point_in_time(elastic, &cursor.pit_id).await
+}
+
+async fn open_point_in_time(
+    elastic: &Elasticsearch,
+    indexes: &[&str],
+    keep_alive: &str,
+) -> Result<String, WebError> {
+    let response = retry_idempotent(|| {
+        elastic
+            .open_point_in_time(OpenPointInTimeParts::Index(indexes))
+            .keep_alive(keep_alive)
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SearchFailed(err.to_string()))?;
+
+    let common_object = read_response(response).await?;
+    match common_object[&"id"].as_str() {
+        Some(pit_id) => Ok(pit_id.to_string()),
+        None => Err(WebError::SearchFailed(
+            "Point-in-time is not opened".to_string(),
+        )),
+    }
+}
+
+async fn close_point_in_time(elastic: &Elasticsearch, pit_id: &str) -> Result<(), WebError> {
+    let response = elastic
+        .close_point_in_time()
+        .body(json!({ "id": pit_id }))
+        .send()
+        .await
+        .map_err(|err| WebError::SearchFailed(err.to_string()))?;
+
+    read_response(response).await.map(|_| ())
+}
+
+#[cfg(test)]
+mod cursor_tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_cursor() {
+        let cursor = Cursor {
+            pit_id: "46ToAwMDaWR5BXV1".to_string(),
+            keep_alive: "1m".to_string(),
+            search_after: vec![json!(1.5), json!("first")],
+        };
+
+        let token = cursor.encode();
+        assert!(!token.contains('='));
+        assert_eq!(Cursor::decode(&token).unwrap(), cursor);
+        assert!(Cursor::decode("not a cursor").is_err());
+    }
+
+    #[test]
+    fn validate_keep_alive_test() {
+        assert!(validate_keep_alive("30s").is_ok());
+        assert!(validate_keep_alive("1h").is_ok());
+        assert!(validate_keep_alive("2h").is_err());
+        assert!(validate_keep_alive("0m").is_err());
+        assert!(validate_keep_alive("1d").is_err());
+        assert!(validate_keep_alive("").is_err());
+        assert!(validate_keep_alive("1мин").is_err());
+    }
+}
@@ -1,14 +1,24 @@
 use crate::errors::{WebError, WebResponse};
 use crate::searcher::elastic::query_builder::filter_query::{
-    CommonFilter, CreateDateQuery, FilterRange, FilterTerm,
+    CommonFilter, CreateDateQuery, FilterPrefix, FilterRange, FilterRegexp, FilterTermIgnoreCase,
+    ModifyDateQuery,
 };
-use crate::searcher::elastic::query_builder::search_query::MultiMatchQuery;
+use crate::searcher::elastic::query_builder::search_query::{
+    build_pattern_query, build_proximity_query, validate_match_options,
+};
+use crate::searcher::elastic::query_language::compile_query;
+use crate::searcher::elastic::retry::retry_idempotent;
 use crate::searcher::elastic::send_status::SendDocumentStatus;
+use crate::wrappers::aggregation::*;
 use crate::wrappers::bucket::{Bucket, BucketBuilder};
 use crate::wrappers::document::{Document, HighlightEntity};
-use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::highlight::HighlightOptions;
+use crate::wrappers::search_params::{MatchOptions, SearchParams, SortField, SortKey};
+use crate::wrappers::search_response::{SearchHit, SearchResponse};
+use crate::wrappers::suggest::*;
 
 use actix_web::web;
+use chrono::{TimeZone, Utc};
 use elasticsearch::http::request::JsonBody;
 use elasticsearch::http::response::Response;
 use elasticsearch::{BulkParts, Elasticsearch, SearchParts};
@@ -19,29 +29,49 @@ use tokio::sync::RwLockReadGuard;
 use std::path::Path;
 use std::string::ToString;
 
+/// Count of documents of each shard which terms are suggested from.
+const SUGGEST_SAMPLE_SIZE: usize = 200;
+
+/// Mapping of bucket index. Path fields are keywords so prefix, wildcard
+/// and regexp filters match whole paths, `document_path` also keeps text
+/// mapping for full-text search. Names are completed by `suggest` field.
+/// Text is matched by regex and wildcard patterns by `pattern` field of
+/// `wildcard` type, buckets created before it was added match nothing.
 pub fn create_bucket_scheme() -> String {
     String::from(
         "
     {
-        \"_source\": { \"enabled\": false },
         \"properties\": {
-            \"_timestamp\": { \"enabled\": \"true\" },
-            \"bucket_uuid\": { \"type\": \"string\" },
-            \"bucket_path\": { \"type\": \"string\" },
-            \"document_name\": { \"type\": \"string\" },
-            \"document_path\": { \"type\": \"string\", \"index\": \"not_analyzed\" },
-            \"document_size\": { \"type\": \"integer\" },
-            \"document_type\": { \"type\": \"string\" },
-            \"document_extension\": { \"type\": \"string\" },
+            \"bucket_uuid\": { \"type\": \"keyword\" },
+            \"bucket_path\": { \"type\": \"keyword\" },
+            \"document_name\": {
+                \"type\": \"text\",
+                \"fields\": {
+                    \"keyword\": { \"type\": \"keyword\", \"ignore_above\": 1024 },
+                    \"suggest\": { \"type\": \"completion\" }
+                }
+            },
+            \"document_path\": {
+                \"type\": \"text\",
+                \"fields\": {
+                    \"keyword\": { \"type\": \"keyword\", \"ignore_above\": 4096 }
+                }
+            },
+            \"document_size\": { \"type\": \"long\" },
+            \"document_type\": { \"type\": \"keyword\" },
+            \"document_extension\": { \"type\": \"keyword\" },
             \"document_permissions\": { \"type\": \"integer\" },
             \"document_created\": { \"type\": \"date\" },
             \"document_modified\": { \"type\": \"date\" },
-            \"document_md5_hash\": { \"type\": \"string\" },
-            \"document_ssdeep_hash\": { \"type\": \"string\" },
-            \"entity_data\": { \"type\": \"string\" },
-            \"entity_keywords\": [],
-            \"document_created\": { \"type\": \"date\" },
-            \"document_modified\": { \"type\": \"date\" }
+            \"document_md5_hash\": { \"type\": \"keyword\" },
+            \"document_ssdeep_hash\": { \"type\": \"keyword\" },
+            \"entity_data\": {
+                \"type\": \"text\",
+                \"fields\": {
+                    \"pattern\": { \"type\": \"wildcard\" }
+                }
+            },
+            \"entity_keywords\": { \"type\": \"keyword\" }
         }
     }
     ",
@@ -53,18 +83,20 @@ pub async fn search_documents(
     indexes: &[&str],
     body_value: &Value,
     es_params: &SearchParams,
-) -> WebResponse<web::Json<Vec<Document>>> {
+) -> WebResponse<web::Json<SearchResponse>> {
     let result_size = es_params.result_size;
     let result_offset = es_params.result_offset;
-    let response_result = elastic
-        .search(SearchParts::Index(indexes))
-        .from(result_offset)
-        .size(result_size)
-        .body(body_value)
-        .pretty(true)
-        .allow_no_indices(true)
-        .send()
-        .await;
+    let response_result = retry_idempotent(|| {
+        elastic
+            .search(SearchParts::Index(indexes))
+            .from(result_offset)
+            .size(result_size)
+            .body(body_value)
+            .pretty(true)
+            .allow_no_indices(true)
+            .send()
+    })
+    .await;
 
     match response_result {
         Err(err) => {
@@ -72,14 +104,108 @@ pub async fn search_documents(
             Err(web_err)
         }
         Ok(response) => {
-            let documents = parse_search_result(response).await;
-            Ok(web::Json(documents))
+            let search_response = parse_search_result(response, &es_params.aggregations).await?;
+            Ok(web::Json(search_response))
+        }
+    }
+}
+
+/// Completes prefix by documents names and frequent terms of documents text.
+/// Buckets created before `suggest` field was added to mapping fail request.
+pub async fn suggest_documents(
+    elastic: &Elasticsearch,
+    indexes: &[&str],
+    form: &SuggestForm,
+) -> WebResponse<web::Json<Vec<Suggestion>>> {
+    let body_value = build_suggest_query(form);
+    let response = retry_idempotent(|| {
+        elastic
+            .search(SearchParts::Index(indexes))
+            .body(&body_value)
+            .allow_no_indices(true)
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SearchFailed(err.to_string()))?;
+
+    let common_object = read_response(response).await?;
+    let suggestions = extract_suggestions(&common_object);
+    Ok(web::Json(merge_suggestions(suggestions, form.size)))
+}
+
+/// Suggests corrected queries for search which matched few documents.
+/// Search is not failed by suggester, so its errors are only logged.
+pub async fn suggest_corrections(
+    elastic: &Elasticsearch,
+    indexes: &[&str],
+    query: &str,
+) -> Vec<QuerySuggestion> {
+    let body_value = build_did_you_mean_query(query);
+    let response_result = retry_idempotent(|| {
+        elastic
+            .search(SearchParts::Index(indexes))
+            .body(&body_value)
+            .allow_no_indices(true)
+            .send()
+    })
+    .await;
+
+    let common_object = match response_result {
+        Ok(response) => response.json::<Value>().await,
+        Err(err) => Err(err),
+    };
+
+    match common_object {
+        Ok(common_object) => extract_query_suggestions(&common_object),
+        Err(err) => {
+            log::warn!("Failed to suggest corrected query: {}", err);
+            Vec::default()
         }
     }
 }
 
-pub async fn parse_search_result(response: Response) -> Vec<Document> {
-    let common_object = response.json::<Value>().await.unwrap();
+/// Returns body of successful response or error reason, for example if
+/// regular expression of query is invalid or point-in-time of passed cursor
+/// has been expired.
+pub async fn read_response(response: Response) -> Result<Value, WebError> {
+    let status_code = response.status_code();
+    let common_object = response
+        .json::<Value>()
+        .await
+        .map_err(|err| WebError::SearchFailed(err.to_string()))?;
+
+    match status_code.is_success() {
+        true => Ok(common_object),
+        false => {
+            let reason = &common_object[&"error"][&"reason"];
+            let msg = reason.as_str().unwrap_or(status_code.as_str());
+            Err(WebError::SearchFailed(msg.to_string()))
+        }
+    }
+}
+
+pub async fn parse_search_result(
+    response: Response,
+    facets: &[Facet],
+) -> Result<SearchResponse, WebError> {
+    let common_object = read_response(response).await?;
+    Ok(extract_search_response(&common_object, facets))
+}
+
+pub fn extract_search_response(common_object: &Value, facets: &[Facet]) -> SearchResponse {
+    let hits_object = &common_object[&"hits"];
+    SearchResponse {
+        total: hits_object[&"total"][&"value"].as_u64().unwrap_or_default(),
+        took: common_object[&"took"].as_u64().unwrap_or_default(),
+        max_score: hits_object[&"max_score"].as_f64(),
+        hits: extract_hits(common_object),
+        aggregations: extract_aggregations(common_object, facets),
+        timed_out: common_object[&"timed_out"].as_bool().unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+pub fn extract_hits(common_object: &Value) -> Vec<SearchHit> {
     let document_json = &common_object[&"hits"][&"hits"];
     let own_document = document_json.to_owned();
     let default_vec: Vec<Value> = Vec::default();
@@ -87,13 +213,23 @@ pub async fn parse_search_result(response: Response) -> Vec<Document> {
 
     json_array
         .iter()
-        .map(parse_document_highlight)
+        .map(parse_search_hit)
         .map(Result::ok)
         .filter(Option::is_some)
         .flatten()
         .collect()
 }
 
+pub fn parse_search_hit(value: &Value) -> Result<SearchHit, serde_json::Error> {
+    let document = parse_document_highlight(value)?;
+    Ok(SearchHit {
+        id: value[&"_id"].as_str().unwrap_or_default().to_string(),
+        index: value[&"_index"].as_str().unwrap_or_default().to_string(),
+        score: value[&"_score"].as_f64(),
+        document,
+    })
+}
+
 pub fn parse_document_highlight(value: &Value) -> Result<Document, serde_json::Error> {
     let source_value = value[&"_source"].to_owned();
     let mut document = Document::deserialize(source_value)?;
@@ -105,46 +241,298 @@ pub fn parse_document_highlight(value: &Value) -> Result<Document, serde_json::E
     Ok(document)
 }
 
-pub fn build_search_query(parameters: &SearchParams) -> Value {
+/// Builds search query where match options which are not passed by search
+/// parameters are filled by passed defaults of searched buckets.
+pub fn build_search_query(
+    parameters: &SearchParams,
+    match_defaults: &MatchOptions,
+) -> Result<Value, WebError> {
     let doc_size_to = parameters.document_size_to;
     let doc_size_from = parameters.document_size_from;
     let doc_cr_to = parameters.created_date_to.as_str();
     let doc_cr_from = parameters.created_date_from.as_str();
-    let doc_ext = parameters.document_extension.as_str();
+    let doc_md_to = parameters.modified_date_to.as_str();
+    let doc_md_from = parameters.modified_date_from.as_str();
+    let doc_ext = parameters.document_extension();
     let doc_type = parameters.document_type.as_str();
+    let doc_path_regex = parameters.document_path_regex().unwrap_or_default();
+    let bucket_path_regex = parameters.bucket_path_regex().unwrap_or_default();
 
     let common_filter = CommonFilter::new()
         .with_date::<FilterRange, CreateDateQuery>("document_created", doc_cr_from, doc_cr_to)
+        .with_date::<FilterRange, ModifyDateQuery>("document_modified", doc_md_from, doc_md_to)
         .with_range::<FilterRange>("document_size", doc_size_from, doc_size_to)
-        .with_term::<FilterTerm>("document_extension", doc_ext)
-        .with_term::<FilterTerm>("document_type", doc_type)
+        .with_term::<FilterTermIgnoreCase>("document_extension", &doc_ext)
+        .with_term::<FilterTermIgnoreCase>("document_type", doc_type)
+        .with_any_of::<FilterTermIgnoreCase>("document_extension", &parameters.any_extensions())
+        .with_any_of::<FilterTermIgnoreCase>("document_type", &parameters.any_types)
+        .without_any_of::<FilterTermIgnoreCase>(
+            "document_extension",
+            &parameters.exclude_extensions(),
+        )
+        .without_any_of::<FilterTermIgnoreCase>("document_type", &parameters.exclude_types)
+        .with_any_of::<FilterPrefix>("document_path.keyword", &parameters.path_prefixes)
+        .without_any_of::<FilterPrefix>("document_path.keyword", &parameters.exclude_paths)
+        .with_term::<FilterRegexp>("document_path.keyword", &doc_path_regex)
+        .with_term::<FilterRegexp>("bucket_path", &bucket_path_regex)
         .build();
 
-    let match_query = MultiMatchQuery::new(parameters.query.as_str());
+    let match_options = parameters.match_options.or_defaults(match_defaults);
+    validate_match_options(&match_options)?;
+    let match_query = compile_query(parameters.query.as_str(), &match_options)?;
+    let mut must_queries = vec![match_query];
+    must_queries.extend(parameters.proximity.iter().map(build_proximity_query));
+    must_queries.extend(parameters.pattern.iter().map(build_pattern_query));
 
-    json!({
+    let mut query = json!({
+        "track_total_hits": true,
         "query": {
             "bool": {
-                "must": match_query,
+                "must": must_queries,
                 "filter": common_filter
             }
         },
-        "highlight" : {
-            "order": "score",
-            "fields" : {
-                "entity_data": {
-                    "pre_tags" : [""],
-                    "post_tags" : [""]
+        "highlight": build_highlight(&parameters.highlight),
+    });
+
+    if !parameters.sort.is_empty() {
+        query["sort"] = build_sort(&parameters.sort);
+    }
+
+    if !parameters.aggregations.is_empty() {
+        query["aggs"] = build_aggregations(&parameters.aggregations, parameters.date_interval);
+    }
+
+    if let Some(pattern) = parameters.pattern.as_ref() {
+        query["timeout"] = json!(format!("{}ms", pattern.timeout().as_millis()));
+    }
+
+    Ok(query)
+}
+
+/// Builds request which completes prefix by documents names with completion
+/// suggester. Terms of documents text are found by `significant_text` over
+/// sample of documents containing them and ordered by count of documents in
+/// whole index. Last word of prefix is alphanumeric, so it is safe regexp.
+pub fn build_suggest_query(form: &SuggestForm) -> Value {
+    let mut query = json!({
+        "size": 0,
+        "suggest": {
+            "document_name": {
+                "prefix": form.prefix.trim_start(),
+                "completion": {
+                    "field": "document_name.suggest",
+                    "size": form.size,
+                    "skip_duplicates": true,
+                }
+            }
+        }
+    });
+
+    if let Some(term_prefix) = form.term_prefix() {
+        query["query"] = json!({ "prefix": { "entity_data": term_prefix } });
+        query["aggs"] = json!({
+            "sample": {
+                "sampler": { "shard_size": SUGGEST_SAMPLE_SIZE },
+                "aggs": {
+                    "entity_data": {
+                        "significant_text": {
+                            "field": "entity_data",
+                            "include": format!("{}.*", term_prefix),
+                            "size": form.size * 2,
+                            "min_doc_count": 1,
+                            "filter_duplicate_text": true,
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    query
+}
+
+pub fn extract_suggestions(common_object: &Value) -> Vec<Suggestion> {
+    let options = common_object[&"suggest"][&"document_name"][0][&"options"].as_array();
+    let names = options.into_iter().flatten().filter_map(|option| {
+        let text = option[&"text"].as_str()?;
+        Some(Suggestion::new(text, SuggestField::DocumentName, 1))
+    });
+
+    let buckets = common_object[&"aggregations"][&"sample"][&"entity_data"][&"buckets"].as_array();
+    let terms = buckets.into_iter().flatten().filter_map(|bucket| {
+        let text = bucket[&"key"].as_str()?;
+        let count = bucket[&"bg_count"].as_u64().unwrap_or_default();
+        Some(Suggestion::new(text, SuggestField::EntityData, count))
+    });
+
+    names.chain(terms).collect()
+}
+
+/// Builds phrase suggester request correcting words of documents text.
+/// Words are replaced only by more frequent terms and suggestions which do
+/// not match any document are pruned by collate query.
+pub fn build_did_you_mean_query(query: &str) -> Value {
+    json!({
+        "size": 0,
+        "suggest": {
+            "text": query,
+            "did_you_mean": {
+                "phrase": {
+                    "field": "entity_data",
+                    "size": DID_YOU_MEAN_SIZE,
+                    "max_errors": 2,
+                    "direct_generator": [{
+                        "field": "entity_data",
+                        "suggest_mode": "popular",
+                        "min_word_length": 3,
+                    }],
+                    "highlight": { "pre_tag": "<em>", "post_tag": "</em>" },
+                    "collate": {
+                        "query": {
+                            "source": {
+                                "match": {
+                                    "entity_data": {
+                                        "query": "{{suggestion}}",
+                                        "operator": "and",
+                                    }
+                                }
+                            }
+                        },
+                        "prune": false,
+                    }
                 }
             }
         }
     })
 }
 
+pub fn extract_query_suggestions(common_object: &Value) -> Vec<QuerySuggestion> {
+    let options = common_object[&"suggest"][&"did_you_mean"][0][&"options"].as_array();
+    options
+        .into_iter()
+        .flatten()
+        .filter_map(|option| {
+            let text = option[&"text"].as_str()?;
+            let highlighted = option[&"highlighted"].as_str().unwrap_or(text);
+            Some(QuerySuggestion {
+                text: text.to_string(),
+                highlighted: highlighted.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Builds highlight clause where fields which are not searched by query
+/// are also highlighted by its terms.
+pub fn build_highlight(options: &HighlightOptions) -> Value {
+    let fields = options
+        .fields
+        .iter()
+        .map(|field| (field.name().to_string(), json!({})))
+        .collect::<serde_json::Map<String, Value>>();
+
+    json!({
+        "order": "score",
+        "pre_tags": [options.pre_tag],
+        "post_tags": [options.post_tag],
+        "fragment_size": options.fragment_size,
+        "number_of_fragments": options.number_of_fragments,
+        "no_match_size": options.no_match_size,
+        "require_field_match": false,
+        "fields": fields,
+    })
+}
+
+/// Builds sort clause by passed keys with md5 hash as tiebreaker, so pages
+/// of equally sorted documents are stable between requests.
+pub fn build_sort(keys: &[SortKey]) -> Value {
+    let mut sort = keys
+        .iter()
+        .map(|key| {
+            let field = match key.field {
+                SortField::Created => "document_created",
+                SortField::Modified => "document_modified",
+                SortField::Size => "document_size",
+                SortField::Name => "document_name.keyword",
+                SortField::Score => "_score",
+            };
+            json!({ field: { "order": key.order() } })
+        })
+        .collect::<Vec<Value>>();
+
+    sort.push(json!({ "document_md5_hash": { "order": "asc" } }));
+    Value::Array(sort)
+}
+
+/// Builds aggregations of passed facets which are named as facets are.
+pub fn build_aggregations(facets: &[Facet], interval: DateInterval) -> Value {
+    let aggregations = facets
+        .iter()
+        .map(|facet| {
+            let field = facet.field();
+            let aggregation = match facet {
+                Facet::Created => json!({
+                    "date_histogram": {
+                        "field": field,
+                        "calendar_interval": interval.name(),
+                        "min_doc_count": 1
+                    }
+                }),
+                Facet::Size => {
+                    let ranges = SIZE_RANGES
+                        .iter()
+                        .map(|(key, from, to)| json!({ "key": key, "from": from, "to": to }))
+                        .collect::<Vec<Value>>();
+                    json!({ "range": { "field": field, "ranges": ranges } })
+                }
+                _ => json!({ "terms": { "field": field, "size": TERMS_FACET_SIZE } }),
+            };
+
+            (facet.name().to_string(), aggregation)
+        })
+        .collect::<serde_json::Map<String, Value>>();
+
+    Value::Object(aggregations)
+}
+
+/// Returns buckets of aggregated facets. Keys of date histogram are
+/// formatted from timestamps to be the same for all backends.
+pub fn extract_aggregations(common_object: &Value, facets: &[Facet]) -> Aggregations {
+    facets
+        .iter()
+        .filter_map(|facet| {
+            let buckets = common_object[&"aggregations"][facet.name()][&"buckets"].as_array()?;
+            let buckets = buckets
+                .iter()
+                .filter_map(|bucket| {
+                    let count = bucket[&"doc_count"].as_u64()?;
+                    let key = match facet {
+                        Facet::Created => {
+                            let timestamp = bucket[&"key"].as_i64()?;
+                            date_key(Utc.timestamp_millis_opt(timestamp).single()?)
+                        }
+                        _ => bucket[&"key"].as_str()?.to_string(),
+                    };
+
+                    Some(FacetBucket {
+                        from: bucket[&"from"].as_f64().map(|from| from as i64),
+                        to: bucket[&"to"].as_f64().map(|to| to as i64),
+                        ..FacetBucket::new(&key, count)
+                    })
+                })
+                .collect();
+
+            Some((*facet, buckets))
+        })
+        .collect()
+}
+
 pub fn build_search_similar_query(parameters: &SearchParams) -> Value {
     let ssdeep_hash = &parameters.query;
     println!("Need find by this: {:?}", ssdeep_hash);
     json!({
+        "track_total_hits": true,
         "query": {
             "more_like_this" : {
                 "fields" : [
@@ -243,8 +631,11 @@ pub async fn send_document(
 mod helper_tests {
     use super::*;
     use crate::searcher::elastic::query_builder::filter_query::{
-        CommonFilter, CreateDateQuery, FilterRange, FilterTerm,
+        CommonFilter, CreateDateQuery, FilterRange, FilterTermIgnoreCase,
     };
+    use crate::wrappers::highlight::HighlightField;
+    use crate::wrappers::pattern::{PatternMode, PatternQuery};
+    use crate::wrappers::proximity::ProximityQuery;
 
     #[test]
     fn build_filter_query() {
@@ -263,14 +654,194 @@ mod helper_tests {
                 "",
             )
             .with_range::<FilterRange>("document_size", doc_size_from, doc_size_to)
-            .with_term::<FilterTerm>("document_extension", doc_ext)
-            .with_term::<FilterTerm>("document_type", doc_type)
+            .with_term::<FilterTermIgnoreCase>("document_extension", doc_ext)
+            .with_term::<FilterTermIgnoreCase>("document_type", doc_type)
             .build();
 
         let val = serde_json::to_value(common_).unwrap();
         println!("{}", serde_json::to_string_pretty(&val).unwrap());
     }
 
+    #[test]
+    fn build_proximity_search_query() {
+        let params = SearchParams {
+            query: "password".to_string(),
+            proximity: vec![ProximityQuery {
+                terms: vec!["password".to_string(), "admin".to_string()],
+                within: 5,
+                ordered: true,
+            }],
+            ..Default::default()
+        };
+
+        let query = build_search_query(&params, &MatchOptions::default()).unwrap();
+        let must_queries = query["query"]["bool"]["must"].as_array().unwrap();
+        assert_eq!(must_queries.len(), 2);
+        assert_eq!(must_queries[0]["multi_match"]["query"], "password");
+
+        let all_of = &must_queries[1]["intervals"]["entity_data"]["all_of"];
+        assert_eq!(all_of["max_gaps"], 5);
+        assert_eq!(all_of["ordered"], true);
+    }
+
+    #[test]
+    fn build_pattern_search_query() {
+        let params = SearchParams {
+            pattern: Some(PatternQuery {
+                mode: PatternMode::Wildcard,
+                value: "*.bak".to_string(),
+                fields: Vec::default(),
+                timeout_ms: Some(2500),
+            }),
+            ..Default::default()
+        };
+
+        let query = build_search_query(&params, &MatchOptions::default()).unwrap();
+        let must_queries = query["query"]["bool"]["must"].as_array().unwrap();
+        assert_eq!(must_queries.len(), 2);
+        assert_eq!(query["timeout"], "2500ms");
+
+        let response = json!({ "took": 2500, "timed_out": true, "hits": { "hits": [] } });
+        assert!(extract_search_response(&response, &[]).timed_out);
+    }
+
+    #[test]
+    fn extract_aggregations_test() {
+        let facets = [Facet::Bucket, Facet::Created, Facet::Size];
+        let aggs = build_aggregations(&facets, DateInterval::Week);
+        assert_eq!(aggs["bucket"]["terms"]["field"], "bucket_uuid");
+        assert_eq!(
+            aggs["document_created"]["date_histogram"]["calendar_interval"],
+            "week"
+        );
+        assert_eq!(aggs["document_size"]["range"]["ranges"][1]["to"], 1 << 20);
+
+        let response = json!({
+            "aggregations": {
+                "bucket": { "buckets": [{ "key": "test_bucket", "doc_count": 4 }] },
+                "document_created": {
+                    "buckets": [{
+                        "key_as_string": "2023-09-11T00:00:00.000Z",
+                        "key": 1694390400000i64,
+                        "doc_count": 4
+                    }]
+                },
+                "document_size": {
+                    "buckets": [{ "key": "0-10KB", "to": 10240.0, "doc_count": 4 }]
+                }
+            }
+        });
+
+        let aggregations = extract_aggregations(&response, &facets);
+        assert_eq!(
+            aggregations[&Facet::Bucket],
+            [FacetBucket::new("test_bucket", 4)]
+        );
+        assert_eq!(aggregations[&Facet::Created][0].key, "2023-09-11T00:00:00Z");
+        assert_eq!(aggregations[&Facet::Size][0].to, Some(10240));
+    }
+
+    #[test]
+    fn parse_highlight_test() {
+        let options = HighlightOptions {
+            fields: vec![HighlightField::EntityData, HighlightField::DocumentName],
+            no_match_size: 50,
+            ..Default::default()
+        };
+
+        let highlight = build_highlight(&options);
+        assert_eq!(highlight["pre_tags"], json!(["<em>"]));
+        assert_eq!(highlight["no_match_size"], 50);
+        assert!(highlight["fields"]["document_name"].is_object());
+        assert!(highlight["fields"]["document_path"].is_null());
+
+        let hit = json!({
+            "_source": Document::default(),
+            "highlight": { "document_name": ["<em>report</em>.docx"] }
+        });
+
+        let document = parse_document_highlight(&hit).unwrap();
+        let highlight = document.highlight.unwrap();
+        assert!(highlight.entity_data.is_empty());
+        assert_eq!(highlight.document_name, ["<em>report</em>.docx"]);
+    }
+
+    #[test]
+    fn parse_suggestions_test() {
+        let form = SuggestForm {
+            prefix: " Quarterly Rep".to_string(),
+            size: 5,
+            buckets: None,
+        };
+
+        let query = build_suggest_query(&form);
+        let completion = &query["suggest"]["document_name"];
+        assert_eq!(completion["prefix"], "Quarterly Rep");
+        assert_eq!(completion["completion"]["field"], "document_name.suggest");
+        assert_eq!(query["query"]["prefix"]["entity_data"], "rep");
+        let terms = &query["aggs"]["sample"]["aggs"]["entity_data"]["significant_text"];
+        assert_eq!(terms["include"], "rep.*");
+
+        let form = SuggestForm {
+            prefix: "report ".to_string(),
+            ..form
+        };
+        assert!(build_suggest_query(&form)["aggs"].is_null());
+
+        let common_object = json!({
+            "suggest": {
+                "document_name": [{ "options": [{ "text": "Report.docx" }] }]
+            },
+            "aggregations": {
+                "sample": {
+                    "entity_data": {
+                        "buckets": [{ "key": "report", "doc_count": 2, "bg_count": 7 }]
+                    }
+                }
+            }
+        });
+
+        let suggestions = extract_suggestions(&common_object);
+        assert_eq!(
+            suggestions,
+            vec![
+                Suggestion::new("Report.docx", SuggestField::DocumentName, 1),
+                Suggestion::new("report", SuggestField::EntityData, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_query_suggestions_test() {
+        let query = build_did_you_mean_query("tenamt commandr");
+        let phrase = &query["suggest"]["did_you_mean"]["phrase"];
+        assert_eq!(query["suggest"]["text"], "tenamt commandr");
+        assert_eq!(phrase["field"], "entity_data");
+        assert_eq!(phrase["direct_generator"][0]["suggest_mode"], "popular");
+
+        let common_object = json!({
+            "suggest": {
+                "did_you_mean": [{
+                    "text": "tenamt commandr",
+                    "options": [{
+                        "text": "tenant commander",
+                        "highlighted": "<em>tenant</em> <em>commander</em>",
+                        "score": 0.25,
+                        "collate_match": true
+                    }]
+                }]
+            }
+        });
+
+        let suggestions = extract_query_suggestions(&common_object);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].text, "tenant commander");
+        assert_eq!(
+            suggestions[0].highlighted,
+            "<em>tenant</em> <em>commander</em>"
+        );
+    }
+
     #[test]
     fn load_directory_entity_test() {
         let file_path = "/Users/breadrock/Downloads/elastic-docstest/second";
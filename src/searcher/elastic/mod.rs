@@ -1,26 +1,44 @@
 pub mod client;
 pub mod context;
+pub mod cursor;
 pub mod helper;
+pub mod pool;
 mod query_builder;
+pub mod query_language;
+mod retry;
+pub mod saved_search;
 mod send_status;
+pub mod transport;
 
-use elasticsearch::auth::Credentials;
-use elasticsearch::cert::CertificateValidation;
-use elasticsearch::http::transport::{BuildError, SingleNodeConnectionPool, TransportBuilder};
-use elasticsearch::http::Url;
-use elasticsearch::Elasticsearch;
+use crate::searcher::elastic::context::ElasticContext;
+use crate::searcher::elastic::pool::*;
+use crate::searcher::elastic::transport::TransportConfig;
 
-pub type ElasticBuildResult = Result<Elasticsearch, BuildError>;
+use std::time::Duration;
 
+#[cfg(test)]
+pub type ElasticBuildResult = Result<elasticsearch::Elasticsearch, elasticsearch::Error>;
+
+/// Builds elastic client with connection pool over comma-separated list of
+/// hosts. Requests are balanced over nodes in round-robin order.
+#[cfg(test)]
 pub fn build_elastic_client(es_host: &str, es_user: &str, es_passwd: &str) -> ElasticBuildResult {
-    let es_url = Url::parse(es_host).unwrap();
-    let conn_pool = SingleNodeConnectionPool::new(es_url);
-    let creds = Credentials::Basic(es_user.into(), es_passwd.into());
-    let validation = CertificateValidation::None;
-    let transport = TransportBuilder::new(conn_pool)
-        .auth(creds)
-        .cert_validation(validation)
-        .build()?;
+    let conn_pool = MultiNodeConnectionPool::new(parse_hosts(es_host)?);
+    TransportConfig::basic(es_user, es_passwd).build_client(conn_pool)
+}
+
+/// Builds elastic client context and spawns monitor which removes failed
+/// nodes from the pool and re-adds recovered ones. Nodes of cluster are
+/// sniffed from `/_nodes` API if sniff interval is passed.
+pub fn build_elastic_context(
+    es_host: &str,
+    transport: TransportConfig,
+    sniff_interval: Option<Duration>,
+) -> Result<ElasticContext, elasticsearch::Error> {
+    let conn_pool = MultiNodeConnectionPool::new(parse_hosts(es_host)?);
+    let elastic = transport.build_client(conn_pool.clone())?;
+    let context = ElasticContext::_new(elastic);
 
-    Ok(Elasticsearch::new(transport))
+    NodesMonitor::new(context.clone(), conn_pool, transport, sniff_interval).spawn();
+    Ok(context)
 }
@@ -0,0 +1,300 @@
+use crate::searcher::elastic::context::ElasticContext;
+use crate::searcher::elastic::transport::TransportConfig;
+
+use elasticsearch::http::transport::{Connection, ConnectionPool, SingleNodeConnectionPool};
+use elasticsearch::http::Url;
+use elasticsearch::nodes::NodesInfoParts;
+use serde_json::Value;
+
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Connection pool over several elastic nodes which selects alive nodes in
+/// round-robin order. Nodes are marked dead or alive by `NodesMonitor`. When
+/// all nodes are dead, all of them are tried in turn.
+#[derive(Debug, Clone)]
+pub struct MultiNodeConnectionPool {
+    urls: Arc<Vec<Url>>,
+    connections: Arc<Vec<Connection>>,
+    alive: Arc<Vec<AtomicBool>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl MultiNodeConnectionPool {
+    pub fn new(urls: Vec<Url>) -> Self {
+        let connections = urls.iter().cloned().map(Connection::new).collect();
+        let alive = urls.iter().map(|_| AtomicBool::new(true)).collect();
+        MultiNodeConnectionPool {
+            urls: Arc::new(urls),
+            connections: Arc::new(connections),
+            alive: Arc::new(alive),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn urls(&self) -> &[Url] {
+        self.urls.as_slice()
+    }
+
+    pub fn is_alive(&self, index: usize) -> bool {
+        self.alive[index].load(Ordering::Relaxed)
+    }
+
+    pub fn set_alive(&self, index: usize, is_alive: bool) {
+        let was_alive = self.alive[index].swap(is_alive, Ordering::Relaxed);
+        match (was_alive, is_alive) {
+            (true, false) => log::warn!("Elastic node {} removed from pool", self.urls[index]),
+            (false, true) => log::info!("Elastic node {} returned to pool", self.urls[index]),
+            _ => {}
+        }
+    }
+}
+
+impl ConnectionPool for MultiNodeConnectionPool {
+    fn next(&self) -> &Connection {
+        let count = self.connections.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..count)
+            .map(|offset| (start + offset) % count)
+            .find(|index| self.is_alive(*index))
+            .map(|index| &self.connections[index])
+            .unwrap_or(&self.connections[start % count])
+    }
+}
+
+/// Parses comma-separated list of elastic hosts. Empty entries are skipped,
+/// but the list must contain at least one host because pool can not be
+/// built without nodes.
+pub fn parse_hosts(es_hosts: &str) -> Result<Vec<Url>, elasticsearch::Error> {
+    let urls = es_hosts
+        .split(',')
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(Url::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if urls.is_empty() {
+        let msg = format!("there is no elastic host in '{}'", es_hosts);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg).into());
+    }
+
+    Ok(urls)
+}
+
+/// Background task which checks health of pool nodes and optionally sniffs
+/// nodes of cluster from `/_nodes` API to replace the pool of client. The seed
+/// hosts are restored when there is no reachable sniffed node.
+pub struct NodesMonitor {
+    context: ElasticContext,
+    conn_pool: MultiNodeConnectionPool,
+    seed_urls: Vec<Url>,
+    transport: TransportConfig,
+    sniff_interval: Option<Duration>,
+}
+
+impl NodesMonitor {
+    pub fn new(
+        context: ElasticContext,
+        conn_pool: MultiNodeConnectionPool,
+        transport: TransportConfig,
+        sniff_interval: Option<Duration>,
+    ) -> Self {
+        let seed_urls = conn_pool.urls().to_vec();
+        NodesMonitor {
+            context,
+            conn_pool,
+            seed_urls,
+            transport,
+            sniff_interval,
+        }
+    }
+
+    pub fn spawn(mut self) {
+        tokio::spawn(async move {
+            let mut last_sniff = Instant::now();
+            loop {
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+                if let Err(err) = self.check_nodes().await {
+                    log::error!("Failed while restoring elastic seed nodes: {}", err);
+                }
+
+                let Some(sniff_interval) = self.sniff_interval else {
+                    continue;
+                };
+
+                if last_sniff.elapsed() >= sniff_interval {
+                    last_sniff = Instant::now();
+                    if let Err(err) = self.sniff_nodes().await {
+                        log::error!("Failed while sniffing elastic nodes: {}", err);
+                    }
+                }
+            }
+        });
+    }
+
+    async fn check_nodes(&mut self) -> Result<(), elasticsearch::Error> {
+        let mut has_alive = false;
+        for (index, url) in self.conn_pool.urls().iter().enumerate() {
+            let is_alive = self.ping_node(url).await;
+            self.conn_pool.set_alive(index, is_alive);
+            has_alive |= is_alive;
+        }
+
+        if has_alive || self.conn_pool.urls() == self.seed_urls.as_slice() {
+            return Ok(());
+        }
+
+        log::warn!("There is no alive sniffed elastic node, restoring seed nodes");
+        self.replace_pool(self.seed_urls.clone()).await
+    }
+
+    async fn ping_node(&self, url: &Url) -> bool {
+        let conn_pool = SingleNodeConnectionPool::new(url.clone());
+        let Ok(elastic) = self.transport.build_client(conn_pool) else {
+            return false;
+        };
+
+        elastic
+            .ping()
+            .request_timeout(HEALTH_CHECK_TIMEOUT)
+            .send()
+            .await
+            .map(|response| response.status_code().is_success())
+            .unwrap_or(false)
+    }
+
+    async fn sniff_nodes(&mut self) -> Result<(), elasticsearch::Error> {
+        let response = {
+            let elastic = self.context.get_cxt().read().await;
+            elastic
+                .nodes()
+                .info(NodesInfoParts::Metric(&["http"]))
+                .send()
+                .await?
+        };
+
+        let nodes_info = response.json::<Value>().await?;
+        let scheme = self.seed_urls[0].scheme().to_string();
+        let mut urls = Vec::default();
+        for url in parse_nodes_info(&nodes_info, scheme.as_str()) {
+            if self.ping_node(&url).await {
+                urls.push(url);
+            }
+        }
+
+        if urls.is_empty() {
+            log::warn!("There is no reachable sniffed elastic node, using seed nodes");
+            urls = self.seed_urls.clone();
+        }
+
+        let mut current_urls = self.conn_pool.urls().to_vec();
+        urls.sort();
+        current_urls.sort();
+        if urls == current_urls {
+            return Ok(());
+        }
+
+        log::info!("Sniffed elastic nodes: {:?}", urls);
+        self.replace_pool(urls).await
+    }
+
+    async fn replace_pool(&mut self, urls: Vec<Url>) -> Result<(), elasticsearch::Error> {
+        let conn_pool = MultiNodeConnectionPool::new(urls);
+        let elastic = self.transport.build_client(conn_pool.clone())?;
+        *self.context.get_cxt().write().await = elastic;
+        self.conn_pool = conn_pool;
+        Ok(())
+    }
+}
+
+/// Extracts http addresses of nodes from response of `/_nodes/http` API.
+/// The publish address has `host:port` or `hostname/ip:port` format, the
+/// hostname is kept in the second case to verify certificates of nodes.
+pub fn parse_nodes_info(nodes_info: &Value, scheme: &str) -> Vec<Url> {
+    let Some(nodes) = nodes_info["nodes"].as_object() else {
+        return Vec::default();
+    };
+
+    nodes
+        .values()
+        .filter_map(|node| node["http"]["publish_address"].as_str())
+        .map(parse_publish_address)
+        .filter_map(|address| Url::parse(&format!("{}://{}", scheme, address)).ok())
+        .collect()
+}
+
+fn parse_publish_address(address: &str) -> String {
+    let Some((hostname, ip_address)) = address.split_once('/') else {
+        return address.to_string();
+    };
+
+    match ip_address.rsplit_once(':') {
+        Some((_, port)) if !hostname.is_empty() => format!("{}:{}", hostname, port),
+        _ => ip_address.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod pool_tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_robin_over_alive_nodes() {
+        let urls = parse_hosts("http://node1:9200, http://node2:9200,http://node3:9200").unwrap();
+        let conn_pool = MultiNodeConnectionPool::new(urls);
+        let next_url = |pool: &MultiNodeConnectionPool| format!("{:?}", pool.next());
+
+        let first = next_url(&conn_pool);
+        let second = next_url(&conn_pool);
+        let third = next_url(&conn_pool);
+        assert_ne!(first, second);
+        assert_ne!(second, third);
+        assert_eq!(first, next_url(&conn_pool));
+
+        conn_pool.set_alive(1, false);
+        conn_pool.set_alive(2, false);
+        for _ in 0..3 {
+            assert!(next_url(&conn_pool).contains("node1"));
+        }
+
+        conn_pool.set_alive(0, false);
+        assert!(next_url(&conn_pool).contains("node"));
+    }
+
+    #[test]
+    fn reject_empty_hosts() {
+        assert!(parse_hosts(",").is_err());
+        assert!(parse_hosts(" ").is_err());
+        assert_eq!(parse_hosts("http://node1:9200,").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn parse_sniffed_nodes() {
+        let nodes_info = json!({
+            "nodes": {
+                "first": { "http": { "publish_address": "10.0.0.1:9200" } },
+                "second": { "http": { "publish_address": "es-node/10.0.0.2:9200" } },
+                "third": { "http": { "publish_address": "/10.0.0.3:9200" } },
+                "fourth": { "http": {} }
+            }
+        });
+
+        let mut urls = parse_nodes_info(&nodes_info, "https");
+        urls.sort();
+        let urls = urls.iter().map(Url::as_str).collect::<Vec<_>>();
+        assert_eq!(
+            urls,
+            vec![
+                "https://10.0.0.1:9200/",
+                "https://10.0.0.3:9200/",
+                "https://es-node:9200/"
+            ]
+        );
+    }
+}
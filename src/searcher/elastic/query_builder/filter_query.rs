@@ -1,15 +1,22 @@
-use chrono::Local;
 use serde_derive::Serialize;
 use serde_json::{json, Value};
 
 #[derive(Clone, Default, Serialize)]
-struct FilterMust {
+struct FilterBool {
     must: Vec<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    should: Vec<Value>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    must_not: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minimum_should_match: Option<usize>,
 }
 
+/// Boolean filter which is serialized to `{"bool": {...}}` object, so it
+/// may be nested into `must`, `should` or `must_not` clauses of other one.
 #[derive(Clone, Default, Serialize)]
 pub struct CommonFilter {
-    bool: FilterMust,
+    bool: FilterBool,
 }
 
 impl CommonFilter {
@@ -17,23 +24,50 @@ impl CommonFilter {
         CommonFilter::default()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.bool.must.is_empty() && self.bool.should.is_empty() && self.bool.must_not.is_empty()
+    }
+
     pub fn with_term<T>(mut self, key: &str, param: &str) -> Self
     where
         T: FilterItem + serde::Serialize,
     {
-        if !param.is_empty() {
-            let value = json!({ key: param });
-            let filter_term = T::create(value);
-            let serde_result = serde_json::to_value(filter_term);
-            if serde_result.is_ok() {
-                let filter_value = serde_result.unwrap();
-                self.bool.must.push(filter_value);
-            }
+        if let Some(filter_value) = create_filter_value::<T>(key, param) {
+            self.bool.must.push(filter_value);
+        }
+
+        self
+    }
+
+    pub fn with_should_term<T>(mut self, key: &str, param: &str) -> Self
+    where
+        T: FilterItem + serde::Serialize,
+    {
+        if let Some(filter_value) = create_filter_value::<T>(key, param) {
+            self.bool.should.push(filter_value);
         }
 
         self
     }
 
+    /// Requires any of passed values: it is used by filters like `prefix`
+    /// which accept single value only.
+    pub fn with_any_of<T>(self, key: &str, params: &[String]) -> Self
+    where
+        T: FilterItem + serde::Serialize,
+    {
+        self.with_filter(any_terms_filter::<T>(key, params))
+    }
+
+    /// Excludes documents which match any of passed values: it is used by
+    /// filters like `prefix` which accept single value only.
+    pub fn without_any_of<T>(self, key: &str, params: &[String]) -> Self
+    where
+        T: FilterItem + serde::Serialize,
+    {
+        self.without_filter(any_terms_filter::<T>(key, params))
+    }
+
     pub fn with_range<T>(mut self, key: &str, gte: i64, lte: i64) -> Self
     where
         T: FilterItem + serde::Serialize,
@@ -41,9 +75,7 @@ impl CommonFilter {
         let doc_size_query = DocumentSizeQuery::new(gte, lte);
         let value = json!({ key: doc_size_query });
         let filter_range = T::create(value);
-        let serde_result = serde_json::to_value(filter_range);
-        if serde_result.is_ok() {
-            let filter_value = serde_result.unwrap();
+        if let Ok(filter_value) = serde_json::to_value(filter_range) {
             self.bool.must.push(filter_value);
         }
 
@@ -55,13 +87,11 @@ impl CommonFilter {
         T: FilterItem + serde::Serialize,
         U: FilterDateQuery + serde::Serialize,
     {
-        if !gte.is_empty() {
+        if !gte.is_empty() || !lte.is_empty() {
             let doc_date_query = U::new(gte, lte);
             let value = json!({ key: doc_date_query });
             let filter_range = T::create(value);
-            let serde_result = serde_json::to_value(filter_range);
-            if serde_result.is_ok() {
-                let filter_value = serde_result.unwrap();
+            if let Ok(filter_value) = serde_json::to_value(filter_range) {
                 self.bool.must.push(filter_value);
             }
         }
@@ -69,23 +99,92 @@ impl CommonFilter {
         self
     }
 
+    pub fn with_filter(mut self, filter: CommonFilter) -> Self {
+        if let Some(filter_value) = nested_filter_value(filter) {
+            self.bool.must.push(filter_value);
+        }
+
+        self
+    }
+
+    pub fn without_filter(mut self, filter: CommonFilter) -> Self {
+        if let Some(filter_value) = nested_filter_value(filter) {
+            self.bool.must_not.push(filter_value);
+        }
+
+        self
+    }
+
+    pub fn with_minimum_should_match(mut self, count: usize) -> Self {
+        if !self.bool.should.is_empty() {
+            self.bool.minimum_should_match = Some(count);
+        }
+
+        self
+    }
+
     pub fn build(self) -> Self {
         self
     }
 }
 
+fn create_filter_value<T>(key: &str, param: &str) -> Option<Value>
+where
+    T: FilterItem + serde::Serialize,
+{
+    if param.is_empty() {
+        return None;
+    }
+
+    let value = json!({ key: param });
+    serde_json::to_value(T::create(value)).ok()
+}
+
+fn any_terms_filter<T>(key: &str, params: &[String]) -> CommonFilter
+where
+    T: FilterItem + serde::Serialize,
+{
+    params
+        .iter()
+        .fold(CommonFilter::new(), |filter, param| {
+            filter.with_should_term::<T>(key, param)
+        })
+        .with_minimum_should_match(1)
+}
+
+fn nested_filter_value(filter: CommonFilter) -> Option<Value> {
+    match filter.is_empty() {
+        true => None,
+        false => serde_json::to_value(filter).ok(),
+    }
+}
+
 pub trait FilterItem {
     fn create(value: Value) -> Self;
 }
 
+/// Term filter which ignores case of keyword value. The `terms` filter has
+/// no such option, so lists of values are combined by `with_any_of`.
 #[derive(Clone, Default, Serialize)]
-pub struct FilterTerm {
+pub struct FilterTermIgnoreCase {
     term: Option<Value>,
 }
 
-impl FilterItem for FilterTerm {
+impl FilterItem for FilterTermIgnoreCase {
     fn create(value: Value) -> Self {
-        FilterTerm { term: Some(value) }
+        let term = value.as_object().map(|fields| {
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    let query = json!({ "value": value, "case_insensitive": true });
+                    (key.to_owned(), query)
+                })
+                .collect::<serde_json::Map<_, _>>()
+        });
+
+        FilterTermIgnoreCase {
+            term: term.map(Value::Object),
+        }
     }
 }
 
@@ -113,6 +212,21 @@ impl FilterItem for FilterPrefix {
     }
 }
 
+#[derive(Clone, Default, Serialize)]
+pub struct FilterRegexp {
+    regexp: Option<Value>,
+}
+
+impl FilterItem for FilterRegexp {
+    fn create(value: Value) -> Self {
+        FilterRegexp {
+            regexp: Some(value),
+        }
+    }
+}
+
+/// Date range which may be open-ended on either side: empty bound is not
+/// serialized at all.
 pub trait FilterDateQuery {
     fn new(gte: &str, lte: &str) -> Self;
 }
@@ -120,21 +234,17 @@ pub trait FilterDateQuery {
 #[derive(Serialize)]
 #[serde(rename = "document_created")]
 pub struct CreateDateQuery {
-    gte: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gte: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     lte: Option<String>,
 }
 
 impl FilterDateQuery for CreateDateQuery {
     fn new(gte: &str, lte: &str) -> Self {
-        let lte_value = match lte.is_empty() {
-            false => Some(lte.to_string()),
-            true => Some(Local::now().format("%Y-%m-%d").to_string()),
-        };
-
         CreateDateQuery {
-            gte: gte.to_string(),
-            lte: lte_value,
+            gte: date_bound(gte),
+            lte: date_bound(lte),
         }
     }
 }
@@ -142,25 +252,28 @@ impl FilterDateQuery for CreateDateQuery {
 #[derive(Serialize)]
 #[serde(rename = "document_modified")]
 pub struct ModifyDateQuery {
-    gte: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gte: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     lte: Option<String>,
 }
 
 impl FilterDateQuery for ModifyDateQuery {
     fn new(gte: &str, lte: &str) -> Self {
-        let lte_value = match lte.is_empty() {
-            true => Some(lte.to_string()),
-            false => None,
-        };
-
         ModifyDateQuery {
-            gte: gte.to_string(),
-            lte: lte_value,
+            gte: date_bound(gte),
+            lte: date_bound(lte),
         }
     }
 }
 
+fn date_bound(value: &str) -> Option<String> {
+    match value.is_empty() {
+        true => None,
+        false => Some(value.to_string()),
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename = "document_size")]
 pub struct DocumentSizeQuery {
@@ -182,3 +295,112 @@ impl DocumentSizeQuery {
         }
     }
 }
+
+#[cfg(test)]
+mod filter_query_tests {
+    use super::*;
+
+    fn ignore_case_term(key: &str, value: &str) -> Value {
+        json!({ "term": { key: { "value": value, "case_insensitive": true } } })
+    }
+
+    #[test]
+    fn build_boolean_filter() {
+        let extensions = vec!["pdf".to_string(), "docx".to_string()];
+        let paths = vec!["/tmp/".to_string(), "/var/".to_string()];
+        let filter = CommonFilter::new()
+            .with_term::<FilterTermIgnoreCase>("document_type", "Document")
+            .with_any_of::<FilterTermIgnoreCase>("document_extension", &extensions)
+            .without_any_of::<FilterPrefix>("document_path", &paths)
+            .build();
+
+        let expected = json!({
+            "bool": {
+                "must": [
+                    ignore_case_term("document_type", "Document"),
+                    {
+                        "bool": {
+                            "must": [],
+                            "should": [
+                                ignore_case_term("document_extension", "pdf"),
+                                ignore_case_term("document_extension", "docx"),
+                            ],
+                            "minimum_should_match": 1,
+                        }
+                    },
+                ],
+                "must_not": [{
+                    "bool": {
+                        "must": [],
+                        "should": [
+                            { "prefix": { "document_path": "/tmp/" } },
+                            { "prefix": { "document_path": "/var/" } },
+                        ],
+                        "minimum_should_match": 1,
+                    }
+                }],
+            }
+        });
+
+        assert_eq!(serde_json::to_value(filter).unwrap(), expected);
+    }
+
+    #[test]
+    fn build_path_filter() {
+        let prefixes = vec!["/evidence/host42/".to_string(), "/mnt/".to_string()];
+        let filter = CommonFilter::new()
+            .with_any_of::<FilterPrefix>("document_path.keyword", &prefixes)
+            .with_term::<FilterRegexp>("bucket_path", "/data/[^/]*")
+            .build();
+
+        let expected = json!({
+            "bool": {
+                "must": [
+                    {
+                        "bool": {
+                            "must": [],
+                            "should": [
+                                { "prefix": { "document_path.keyword": "/evidence/host42/" } },
+                                { "prefix": { "document_path.keyword": "/mnt/" } },
+                            ],
+                            "minimum_should_match": 1,
+                        }
+                    },
+                    { "regexp": { "bucket_path": "/data/[^/]*" } },
+                ]
+            }
+        });
+
+        assert_eq!(serde_json::to_value(filter).unwrap(), expected);
+    }
+
+    #[test]
+    fn build_open_ended_dates() {
+        let filter = CommonFilter::new()
+            .with_date::<FilterRange, CreateDateQuery>("document_created", "", "2023-01-01")
+            .with_date::<FilterRange, ModifyDateQuery>("document_modified", "2023-05-01", "")
+            .with_date::<FilterRange, ModifyDateQuery>("document_modified", "", "")
+            .build();
+
+        let expected = json!({
+            "bool": {
+                "must": [
+                    { "range": { "document_created": { "lte": "2023-01-01" } } },
+                    { "range": { "document_modified": { "gte": "2023-05-01" } } },
+                ]
+            }
+        });
+
+        assert_eq!(serde_json::to_value(filter).unwrap(), expected);
+    }
+
+    #[test]
+    fn skip_empty_nested_filter() {
+        let filter = CommonFilter::new()
+            .with_any_of::<FilterTermIgnoreCase>("document_type", &[String::default()])
+            .without_any_of::<FilterPrefix>("document_path", &[])
+            .build();
+
+        assert!(filter.is_empty());
+    }
+}
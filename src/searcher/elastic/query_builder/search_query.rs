@@ -1,18 +1,54 @@
+use crate::errors::WebError;
+use crate::wrappers::pattern::*;
+use crate::wrappers::proximity::ProximityQuery;
+use crate::wrappers::search_params::MatchOptions;
+
 use serde_derive::Serialize;
+use serde_json::{json, Value};
+
+/// Fields which are searched if there are no fields in match options.
+pub const DEFAULT_FIELDS: [&str; 2] = ["entity_data", "document_path"];
+
+const OPERATORS: [&str; 2] = ["or", "and"];
+
+const MATCH_TYPES: [&str; 6] = [
+    "best_fields",
+    "most_fields",
+    "cross_fields",
+    "phrase",
+    "phrase_prefix",
+    "bool_prefix",
+];
+
+/// Types of `multi_match` query which do not support fuzziness.
+const NOT_FUZZY_TYPES: [&str; 3] = ["cross_fields", "phrase", "phrase_prefix"];
 
 #[derive(Serialize)]
 struct QueryString {
     query: String,
-    operator: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    operator: Option<String>,
     fields: Vec<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    match_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    minimum_should_match: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fuzziness: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    slop: Option<u32>,
 }
 
 impl QueryString {
-    pub fn new(value: &str) -> Self {
+    pub fn new(value: &str, options: &MatchOptions) -> Self {
         QueryString {
             query: value.to_string(),
-            operator: "or".to_string(),
-            fields: vec!["entity_data".to_string(), "document_path".to_string()],
+            operator: Some(options.operator.clone().unwrap_or("or".to_string())),
+            fields: match_fields(options),
+            match_type: options.match_type.clone(),
+            minimum_should_match: options.minimum_should_match.clone(),
+            fuzziness: options.fuzziness.clone(),
+            slop: options.slop,
         }
     }
 }
@@ -23,9 +59,278 @@ pub struct MultiMatchQuery {
 }
 
 impl MultiMatchQuery {
-    pub fn new(value: &str) -> Self {
+    pub fn new(value: &str, options: &MatchOptions) -> Self {
         MultiMatchQuery {
-            multi_match: QueryString::new(value),
+            multi_match: QueryString::new(value, options),
+        }
+    }
+
+    /// Builds query of `phrase` type over the same fields, terms operator
+    /// and fuzziness do not make sense for phrases.
+    pub fn phrase(value: &str, options: &MatchOptions) -> Self {
+        let mut query_string = QueryString::new(value, options);
+        query_string.operator = None;
+        query_string.minimum_should_match = None;
+        query_string.fuzziness = None;
+        query_string.match_type = Some("phrase".to_string());
+        MultiMatchQuery {
+            multi_match: query_string,
+        }
+    }
+}
+
+/// Builds `intervals` query where terms of proximity query are matched as
+/// phrases and separated by up to `within` other words.
+pub fn build_proximity_query(proximity: &ProximityQuery) -> Value {
+    let intervals = proximity
+        .terms
+        .iter()
+        .map(|term| json!({ "match": { "query": term, "max_gaps": 0, "ordered": true } }))
+        .collect::<Vec<_>>();
+
+    json!({
+        "intervals": {
+            "entity_data": {
+                "all_of": {
+                    "ordered": proximity.ordered,
+                    "max_gaps": proximity.within,
+                    "intervals": intervals,
+                }
+            }
+        }
+    })
+}
+
+/// Builds `regexp` or `wildcard` query over fields of pattern which match
+/// any of them. Regular expression is enclosed in `.*` to match any part of
+/// value like the embedded engines do, optional operators are disabled.
+pub fn build_pattern_query(pattern: &PatternQuery) -> Value {
+    let queries = pattern
+        .fields()
+        .iter()
+        .map(|field| {
+            let field = match field {
+                PatternField::EntityData => "entity_data.pattern",
+                PatternField::DocumentPath => "document_path.keyword",
+            };
+
+            match pattern.mode {
+                PatternMode::Regex => json!({
+                    "regexp": {
+                        field: {
+                            "value": format!(".*({}).*", pattern.value),
+                            "flags": "NONE",
+                            "max_determinized_states": MAX_DETERMINIZED_STATES,
+                        }
+                    }
+                }),
+                PatternMode::Wildcard => json!({
+                    "wildcard": { field: { "value": pattern.value, "case_insensitive": true } }
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    json!({ "bool": { "should": queries, "minimum_should_match": 1 } })
+}
+
+/// Returns searched fields with boosts.
+pub fn match_fields(options: &MatchOptions) -> Vec<String> {
+    match options.fields.is_empty() {
+        true => DEFAULT_FIELDS.iter().map(|it| it.to_string()).collect(),
+        false => options.fields.clone(),
+    }
+}
+
+pub fn validate_match_options(options: &MatchOptions) -> Result<(), WebError> {
+    let invalid_option = |msg: String| Err(WebError::SearchFailed(msg));
+
+    if let Some(operator) = options.operator.as_deref() {
+        if !OPERATORS.contains(&operator) {
+            return invalid_option(format!("Unknown match operator: {}", operator));
+        }
+    }
+
+    if let Some(match_type) = options.match_type.as_deref() {
+        if !MATCH_TYPES.contains(&match_type) {
+            return invalid_option(format!("Unknown multi_match type: {}", match_type));
+        }
+
+        if options.fuzziness.is_some() && NOT_FUZZY_TYPES.contains(&match_type) {
+            return invalid_option(format!("Fuzziness is not allowed for {} type", match_type));
         }
     }
+
+    options.validate_fuzziness()?;
+
+    for field in options.fields.iter() {
+        let (name, boost) = match field.split_once('^') {
+            Some((name, boost)) => (name, Some(boost)),
+            None => (field.as_str(), None),
+        };
+
+        let is_valid_boost = boost
+            .map(|value| value.parse::<f32>().map(|it| it >= 0.0).unwrap_or(false))
+            .unwrap_or(true);
+        if name.is_empty() || !is_valid_boost {
+            return invalid_option(format!("Invalid search field: {}", field));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod search_query_tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn build_multi_match_query() {
+        let options = MatchOptions {
+            fields: vec!["document_name^3".to_string(), "entity_data".to_string()],
+            operator: Some("and".to_string()),
+            match_type: Some("cross_fields".to_string()),
+            minimum_should_match: Some("75%".to_string()),
+            ..Default::default()
+        };
+
+        let query = serde_json::to_value(MultiMatchQuery::new("lease fee", &options)).unwrap();
+        let expected = json!({
+            "multi_match": {
+                "query": "lease fee",
+                "operator": "and",
+                "fields": ["document_name^3", "entity_data"],
+                "type": "cross_fields",
+                "minimum_should_match": "75%",
+            }
+        });
+        assert_eq!(query, expected);
+
+        let query = MultiMatchQuery::new("lease", &MatchOptions::default());
+        let expected = json!({
+            "multi_match": {
+                "query": "lease",
+                "operator": "or",
+                "fields": ["entity_data", "document_path"],
+            }
+        });
+        assert_eq!(serde_json::to_value(query).unwrap(), expected);
+    }
+
+    #[test]
+    fn build_fuzzy_and_proximity_queries() {
+        let options = MatchOptions {
+            fuzziness: Some("AUTO".to_string()),
+            slop: Some(2),
+            ..Default::default()
+        };
+
+        let query = serde_json::to_value(MultiMatchQuery::phrase("lease fee", &options)).unwrap();
+        let expected = json!({
+            "multi_match": {
+                "query": "lease fee",
+                "fields": ["entity_data", "document_path"],
+                "type": "phrase",
+                "slop": 2,
+            }
+        });
+        assert_eq!(query, expected);
+
+        let query = serde_json::to_value(MultiMatchQuery::new("lease", &options)).unwrap();
+        assert_eq!(query["multi_match"]["fuzziness"], "AUTO");
+
+        let proximity = ProximityQuery {
+            terms: vec!["password".to_string(), "admin".to_string()],
+            within: 5,
+            ordered: false,
+        };
+        let expected = json!({
+            "intervals": {
+                "entity_data": {
+                    "all_of": {
+                        "ordered": false,
+                        "max_gaps": 5,
+                        "intervals": [
+                            { "match": { "query": "password", "max_gaps": 0, "ordered": true } },
+                            { "match": { "query": "admin", "max_gaps": 0, "ordered": true } },
+                        ],
+                    }
+                }
+            }
+        });
+        assert_eq!(build_proximity_query(&proximity), expected);
+    }
+
+    #[test]
+    fn build_pattern_queries() {
+        let mut pattern = PatternQuery {
+            mode: PatternMode::Regex,
+            value: "AKIA[0-9A-Z]{16}".to_string(),
+            fields: vec![PatternField::EntityData],
+            timeout_ms: None,
+        };
+
+        let expected = json!({
+            "bool": {
+                "should": [{
+                    "regexp": {
+                        "entity_data.pattern": {
+                            "value": ".*(AKIA[0-9A-Z]{16}).*",
+                            "flags": "NONE",
+                            "max_determinized_states": 10000,
+                        }
+                    }
+                }],
+                "minimum_should_match": 1,
+            }
+        });
+        assert_eq!(build_pattern_query(&pattern), expected);
+
+        pattern.mode = PatternMode::Wildcard;
+        pattern.value = "*.bak".to_string();
+        pattern.fields = Vec::default();
+        let query = build_pattern_query(&pattern);
+        let should = query["bool"]["should"].as_array().unwrap();
+        assert_eq!(should.len(), 2);
+        assert_eq!(
+            should[1],
+            json!({
+                "wildcard": {
+                    "document_path.keyword": { "value": "*.bak", "case_insensitive": true }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn validate_options() {
+        let mut options = MatchOptions {
+            fields: vec!["document_name^1.5".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_match_options(&options).is_ok());
+
+        options.fields = vec!["document_name^high".to_string()];
+        assert!(validate_match_options(&options).is_err());
+
+        options.fields = Vec::default();
+        options.match_type = Some("fuzzy_fields".to_string());
+        assert!(validate_match_options(&options).is_err());
+
+        options.match_type = None;
+        options.operator = Some("xor".to_string());
+        assert!(validate_match_options(&options).is_err());
+
+        options.operator = None;
+        options.fuzziness = Some("3".to_string());
+        assert!(validate_match_options(&options).is_err());
+
+        options.fuzziness = Some("AUTO".to_string());
+        assert!(validate_match_options(&options).is_ok());
+
+        options.match_type = Some("cross_fields".to_string());
+        assert!(validate_match_options(&options).is_err());
+    }
 }
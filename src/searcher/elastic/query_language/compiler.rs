@@ -0,0 +1,317 @@
+use crate::searcher::elastic::query_builder::search_query::{match_fields, MultiMatchQuery};
+use crate::searcher::elastic::query_language::{Expr, TermValue};
+use crate::wrappers::search_params::MatchOptions;
+
+use serde_json::{json, Map, Value};
+use std::ops::Bound;
+
+/// Full-text fields which are searched by analyzed match queries, other
+/// fields are matched by exact terms.
+const TEXT_FIELDS: [&str; 3] = ["entity_data", "document_name", "document_path"];
+
+/// Text fields which have keyword sub-field for wildcard queries over the
+/// whole value.
+const KEYWORD_SUBFIELDS: [&str; 1] = ["document_path"];
+
+/// Compiles expression to elastic query. Terms without field are searched
+/// over fields of passed match options.
+pub fn compile(expr: &Expr, options: &MatchOptions) -> Value {
+    let compile_all = |operands: &[Expr]| {
+        operands
+            .iter()
+            .map(|operand| compile(operand, options))
+            .collect::<Vec<_>>()
+    };
+
+    match expr {
+        Expr::MatchAll => json!({ "match_all": {} }),
+        Expr::And(operands) => compile_and(operands, options),
+        Expr::Or(operands) => json!({
+            "bool": {
+                "should": compile_all(operands),
+                "minimum_should_match": 1,
+            }
+        }),
+        Expr::Not(operand) => json!({
+            "bool": { "must_not": [compile(operand, options)] }
+        }),
+        Expr::Term { field: None, value } => compile_default_term(value, options),
+        Expr::Term {
+            field: Some(field),
+            value,
+        } => compile_field_term(field, value, options),
+    }
+}
+
+/// Compiles required operands to `must` and excluded ones to `must_not` of
+/// the same `bool` query.
+fn compile_and(operands: &[Expr], options: &MatchOptions) -> Value {
+    let mut must = Vec::new();
+    let mut must_not = Vec::new();
+    for operand in operands.iter() {
+        match operand {
+            Expr::Not(excluded) => must_not.push(compile(excluded, options)),
+            required => must.push(compile(required, options)),
+        }
+    }
+
+    let mut query = Map::new();
+    if !must.is_empty() {
+        query.insert("must".to_string(), json!(must));
+    }
+
+    if !must_not.is_empty() {
+        query.insert("must_not".to_string(), json!(must_not));
+    }
+
+    json!({ "bool": query })
+}
+
+fn compile_default_term(value: &TermValue, options: &MatchOptions) -> Value {
+    match value {
+        TermValue::Word(word) => json!(MultiMatchQuery::new(word, options)),
+        TermValue::Phrase(phrase) => json!(MultiMatchQuery::phrase(phrase, options)),
+        TermValue::Wildcard(pattern) => {
+            any_of_fields(options, |field| wildcard_query(field, pattern))
+        }
+        TermValue::Range { .. } => {
+            any_of_fields(options, |field| compile_field_term(field, value, options))
+        }
+    }
+}
+
+/// Combines queries over match fields, boosts of fields are skipped.
+fn any_of_fields<F>(options: &MatchOptions, build_query: F) -> Value
+where
+    F: Fn(&str) -> Value,
+{
+    let queries = match_fields(options)
+        .iter()
+        .map(|field| field.split('^').next().unwrap_or_default())
+        .map(build_query)
+        .collect::<Vec<_>>();
+
+    json!({ "bool": { "should": queries, "minimum_should_match": 1 } })
+}
+
+fn wildcard_query(field: &str, pattern: &str) -> Value {
+    json!({
+        "wildcard": { field: { "value": pattern, "case_insensitive": true } }
+    })
+}
+
+/// Builds `match` query which is expanded by fuzziness if it is set.
+fn match_query(field: &str, word: &str, options: &MatchOptions) -> Value {
+    match options.fuzziness.as_deref() {
+        Some(fuzziness) => json!({
+            "match": { field: { "query": word, "fuzziness": fuzziness } }
+        }),
+        None => json!({ "match": { field: word } }),
+    }
+}
+
+/// Builds `match_phrase` query which allows slop if it is set.
+fn match_phrase_query(field: &str, phrase: &str, options: &MatchOptions) -> Value {
+    match options.slop {
+        Some(slop) => json!({
+            "match_phrase": { field: { "query": phrase, "slop": slop } }
+        }),
+        None => json!({ "match_phrase": { field: phrase } }),
+    }
+}
+
+fn compile_field_term(field: &str, value: &TermValue, options: &MatchOptions) -> Value {
+    let is_text = TEXT_FIELDS.contains(&field);
+    match value {
+        TermValue::Word(word) if is_text => match_query(field, word, options),
+        TermValue::Phrase(phrase) if is_text => match_phrase_query(field, phrase, options),
+        TermValue::Word(value) | TermValue::Phrase(value) => json!({ "term": { field: value } }),
+        TermValue::Wildcard(pattern) => match KEYWORD_SUBFIELDS.contains(&field) {
+            true => wildcard_query(&format!("{}.keyword", field), pattern),
+            false => wildcard_query(field, pattern),
+        },
+        TermValue::Range { lower, upper } => {
+            let mut bounds = Map::new();
+            match lower {
+                Bound::Included(value) => bounds.insert("gte".to_string(), json!(value)),
+                Bound::Excluded(value) => bounds.insert("gt".to_string(), json!(value)),
+                Bound::Unbounded => None,
+            };
+
+            match upper {
+                Bound::Included(value) => bounds.insert("lte".to_string(), json!(value)),
+                Bound::Excluded(value) => bounds.insert("lt".to_string(), json!(value)),
+                Bound::Unbounded => None,
+            };
+
+            json!({ "range": { field: bounds } })
+        }
+    }
+}
+
+#[cfg(test)]
+mod compiler_tests {
+    use crate::searcher::elastic::query_language::compile_query;
+    use crate::wrappers::search_params::MatchOptions;
+
+    use serde_json::json;
+
+    #[test]
+    fn compile_boolean_query() {
+        let query =
+            compile_query("ext:pdf AND -\"draft version\"", &MatchOptions::default()).unwrap();
+        let expected = json!({
+            "bool": {
+                "must": [{ "term": { "document_extension": "pdf" } }],
+                "must_not": [{
+                    "multi_match": {
+                        "query": "draft version",
+                        "type": "phrase",
+                        "fields": ["entity_data", "document_path"],
+                    }
+                }],
+            }
+        });
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn compile_term_with_exclusion() {
+        let query = compile_query("lease fee -draft", &MatchOptions::default()).unwrap();
+        let expected = json!({
+            "bool": {
+                "must": [{
+                    "multi_match": {
+                        "query": "lease fee",
+                        "operator": "or",
+                        "fields": ["entity_data", "document_path"],
+                    }
+                }],
+                "must_not": [{
+                    "multi_match": {
+                        "query": "draft",
+                        "operator": "or",
+                        "fields": ["entity_data", "document_path"],
+                    }
+                }],
+            }
+        });
+
+        assert_eq!(query, expected);
+
+        let query = compile_query("-draft", &MatchOptions::default()).unwrap();
+        assert!(query["bool"]["must"].is_null());
+        assert_eq!(
+            query["bool"]["must_not"][0]["multi_match"]["query"],
+            "draft"
+        );
+    }
+
+    #[test]
+    fn compile_range_and_wildcard() {
+        let query = "size:[1000 TO 5000] created:<2023-01-01 path:/home/*";
+        let query = compile_query(query, &MatchOptions::default()).unwrap();
+        let expected = json!({
+            "bool": {
+                "should": [
+                    { "range": { "document_size": { "gte": "1000", "lte": "5000" } } },
+                    { "range": { "document_created": { "lt": "2023-01-01" } } },
+                    {
+                        "wildcard": {
+                            "document_path.keyword": { "value": "/home/*", "case_insensitive": true }
+                        }
+                    },
+                ],
+                "minimum_should_match": 1,
+            }
+        });
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn compile_with_match_options() {
+        let options = MatchOptions {
+            fields: vec!["document_name^3".to_string(), "entity_data".to_string()],
+            operator: Some("and".to_string()),
+            ..Default::default()
+        };
+
+        let query = compile_query("\"lease fee\" OR ten*", &options).unwrap();
+        let expected = json!({
+            "bool": {
+                "should": [
+                    {
+                        "multi_match": {
+                            "query": "lease fee",
+                            "type": "phrase",
+                            "fields": ["document_name^3", "entity_data"],
+                        }
+                    },
+                    {
+                        "bool": {
+                            "should": [
+                                {
+                                    "wildcard": {
+                                        "document_name": { "value": "ten*", "case_insensitive": true }
+                                    }
+                                },
+                                {
+                                    "wildcard": {
+                                        "entity_data": { "value": "ten*", "case_insensitive": true }
+                                    }
+                                },
+                            ],
+                            "minimum_should_match": 1,
+                        }
+                    },
+                ],
+                "minimum_should_match": 1,
+            }
+        });
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn compile_adjacent_words() {
+        let options = MatchOptions {
+            operator: Some("and".to_string()),
+            ..Default::default()
+        };
+
+        let query = compile_query("lease fee", &options).unwrap();
+        let expected = json!({
+            "multi_match": {
+                "query": "lease fee",
+                "operator": "and",
+                "fields": ["entity_data", "document_path"],
+            }
+        });
+
+        assert_eq!(query, expected);
+    }
+
+    #[test]
+    fn compile_fuzzy_field_terms() {
+        let options = MatchOptions {
+            fuzziness: Some("1".to_string()),
+            slop: Some(3),
+            ..Default::default()
+        };
+
+        let query = compile_query("name:lease content:\"lease fee\"", &options).unwrap();
+        let expected = json!({
+            "bool": {
+                "should": [
+                    { "match": { "document_name": { "query": "lease", "fuzziness": "1" } } },
+                    { "match_phrase": { "entity_data": { "query": "lease fee", "slop": 3 } } },
+                ],
+                "minimum_should_match": 1,
+            }
+        });
+
+        assert_eq!(query, expected);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::searcher::elastic::query_language::{SyntaxError, SyntaxErrorKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    LParen,
+    RParen,
+    RangeStart { inclusive: bool },
+    RangeEnd { inclusive: bool },
+    Colon,
+    Minus,
+    Compare(Comparison),
+    And,
+    Or,
+    Not,
+    Word(String),
+    Phrase(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+/// Token with position of its first character in the query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spanned {
+    pub token: Token,
+    pub position: usize,
+}
+
+impl Token {
+    pub fn describe(&self) -> String {
+        match self {
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::RangeStart { inclusive: true } => "[".to_string(),
+            Token::RangeStart { inclusive: false } => "{".to_string(),
+            Token::RangeEnd { inclusive: true } => "]".to_string(),
+            Token::RangeEnd { inclusive: false } => "}".to_string(),
+            Token::Colon => ":".to_string(),
+            Token::Minus => "-".to_string(),
+            Token::Compare(Comparison::Gt) => ">".to_string(),
+            Token::Compare(Comparison::Gte) => ">=".to_string(),
+            Token::Compare(Comparison::Lt) => "<".to_string(),
+            Token::Compare(Comparison::Lte) => "<=".to_string(),
+            Token::And => "AND".to_string(),
+            Token::Or => "OR".to_string(),
+            Token::Not => "NOT".to_string(),
+            Token::Word(word) => word.clone(),
+            Token::Phrase(phrase) => format!("\"{}\"", phrase),
+        }
+    }
+}
+
+const SPECIAL_CHARS: [char; 8] = ['(', ')', '[', ']', '{', '}', ':', '"'];
+
+/// Characters which end literal text of unknown field like `C:\Users` or
+/// `http://host/path`, the rest of special characters are kept in it.
+const LITERAL_END_CHARS: [char; 3] = ['(', ')', '"'];
+
+pub fn tokenize(query: &str) -> Result<Vec<Spanned>, SyntaxError> {
+    let chars = query.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let symbol = chars[index];
+        let position = index;
+        let token = match symbol {
+            _ if symbol.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::RangeStart { inclusive: true },
+            '{' => Token::RangeStart { inclusive: false },
+            ']' => Token::RangeEnd { inclusive: true },
+            '}' => Token::RangeEnd { inclusive: false },
+            ':' => Token::Colon,
+            '-' | '!' if starts_operand(&chars, index + 1) => match symbol {
+                '-' => Token::Minus,
+                _ => Token::Not,
+            },
+            '>' | '<' => {
+                let inclusive = chars.get(index + 1) == Some(&'=');
+                if inclusive {
+                    index += 1;
+                }
+
+                Token::Compare(match (symbol, inclusive) {
+                    ('>', false) => Comparison::Gt,
+                    ('>', true) => Comparison::Gte,
+                    ('<', false) => Comparison::Lt,
+                    _ => Comparison::Lte,
+                })
+            }
+            '"' => {
+                let (phrase, next_index) = read_phrase(&chars, index)?;
+                tokens.push(Spanned {
+                    token: Token::Phrase(phrase),
+                    position,
+                });
+                index = next_index;
+                continue;
+            }
+            _ => {
+                let (word, next_index) = read_word(&chars, index, &SPECIAL_CHARS);
+                let token = match word.as_str() {
+                    "AND" | "&&" => Token::And,
+                    "OR" | "||" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                };
+                tokens.push(Spanned { token, position });
+                index = next_index;
+                continue;
+            }
+        };
+
+        tokens.push(Spanned { token, position });
+        index += 1;
+    }
+
+    Ok(tokens)
+}
+
+fn starts_operand(chars: &[char], index: usize) -> bool {
+    chars
+        .get(index)
+        .map(|symbol| !symbol.is_whitespace() && *symbol != ')')
+        .unwrap_or(false)
+}
+
+/// Returns true if backslash at the index escapes the next character. Only
+/// whitespaces and characters which have special meaning may be escaped,
+/// other backslashes are kept, so paths like `C:\Users` need no escaping.
+fn is_escape(chars: &[char], index: usize, special_chars: &[char]) -> bool {
+    let is_escaped = |next: &char| {
+        next.is_whitespace() || ['\\', '-', '!'].contains(next) || special_chars.contains(next)
+    };
+
+    chars[index] == '\\' && chars.get(index + 1).map(is_escaped).unwrap_or(false)
+}
+
+fn read_phrase(chars: &[char], start: usize) -> Result<(String, usize), SyntaxError> {
+    let mut phrase = String::new();
+    let mut index = start + 1;
+    while index < chars.len() {
+        match chars[index] {
+            '\\' if is_escape(chars, index, &['"']) => {
+                phrase.push(chars[index + 1]);
+                index += 2;
+            }
+            '"' => return Ok((phrase, index + 1)),
+            symbol => {
+                phrase.push(symbol);
+                index += 1;
+            }
+        }
+    }
+
+    Err(SyntaxError::new(start, SyntaxErrorKind::UnterminatedPhrase))
+}
+
+/// Reads literal text of unknown field from its start up to whitespace.
+pub fn read_literal(chars: &[char], start: usize) -> (String, usize) {
+    read_word(chars, start, &LITERAL_END_CHARS)
+}
+
+fn read_word(chars: &[char], start: usize, end_chars: &[char]) -> (String, usize) {
+    let mut word = String::new();
+    let mut index = start;
+    while index < chars.len() {
+        let symbol = chars[index];
+        if symbol.is_whitespace() || end_chars.contains(&symbol) {
+            break;
+        }
+
+        if is_escape(chars, index, &SPECIAL_CHARS) {
+            word.push(chars[index + 1]);
+            index += 2;
+            continue;
+        }
+
+        word.push(symbol);
+        index += 1;
+    }
+
+    (word, index)
+}
@@ -0,0 +1,73 @@
+//! Query language of search requests, which is compiled to elastic `bool`
+//! queries. It supports `field:value` terms, quoted phrases, `AND`/`OR`/`NOT`
+//! operators with parentheses, `-exclusion`, wildcards and ranges like
+//! `size:[1000 TO 5000]` or `created:>2023-01-01`. Terms without operator
+//! between them are combined by `OR` like elastic `query_string` does, and
+//! adjacent bare words are searched together by one `multi_match` query, so
+//! operator and minimum should match of match options apply to them.
+//!
+//! Words with unknown field like `owner:me`, `C:\Users\docs` or
+//! `http://host/path` are searched as literal text if the value follows the
+//! colon without whitespace. Backslash escapes whitespaces, backslash and
+//! characters `( ) [ ] { } : " - !`, so `name:\(draft\)` searches name
+//! `(draft)`; backslashes before other characters are kept as they are.
+
+mod compiler;
+mod lexer;
+mod parser;
+
+use crate::errors::WebError;
+use crate::wrappers::search_params::MatchOptions;
+
+pub use compiler::compile;
+pub use parser::{parse, Expr, TermValue};
+
+use serde_derive::Serialize;
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq, Serialize)]
+#[error("{kind} at position {position}")]
+pub struct SyntaxError {
+    /// Position of the problem in characters from the query start.
+    pub position: usize,
+    pub kind: SyntaxErrorKind,
+}
+
+impl SyntaxError {
+    pub fn new(position: usize, kind: SyntaxErrorKind) -> Self {
+        SyntaxError { position, kind }
+    }
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum SyntaxErrorKind {
+    #[error("unexpected '{0}'")]
+    UnexpectedToken(String),
+    #[error("unexpected end of query")]
+    UnexpectedEnd,
+    #[error("unterminated phrase")]
+    UnterminatedPhrase,
+    #[error("unclosed parenthesis")]
+    UnclosedParenthesis,
+    #[error("unknown field '{0}'")]
+    UnknownField(String),
+    #[error("missing value of field '{0}'")]
+    MissingValue(String),
+    #[error("invalid range: {0}")]
+    InvalidRange(String),
+}
+
+impl From<SyntaxError> for WebError {
+    fn from(value: SyntaxError) -> Self {
+        WebError::SearchFailed(format!("Query syntax error: {}", value))
+    }
+}
+
+/// Parses passed query and compiles it to elastic query.
+pub fn compile_query(
+    query: &str,
+    options: &MatchOptions,
+) -> Result<serde_json::Value, SyntaxError> {
+    parse(query).map(|expr| compile(&expr, options))
+}
@@ -0,0 +1,569 @@
+use crate::searcher::elastic::query_language::lexer::{
+    read_literal, tokenize, Comparison, Spanned, Token,
+};
+use crate::searcher::elastic::query_language::{SyntaxError, SyntaxErrorKind};
+
+use std::ops::Bound;
+
+/// Fields of documents which may be used within query and its aliases.
+const FIELD_ALIASES: [(&str, &str); 14] = [
+    ("content", "entity_data"),
+    ("text", "entity_data"),
+    ("name", "document_name"),
+    ("path", "document_path"),
+    ("bucket", "bucket_path"),
+    ("size", "document_size"),
+    ("type", "document_type"),
+    ("ext", "document_extension"),
+    ("extension", "document_extension"),
+    ("created", "document_created"),
+    ("modified", "document_modified"),
+    ("md5", "document_md5_hash"),
+    ("ssdeep", "document_ssdeep_hash"),
+    ("permissions", "document_permissions"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Term {
+        field: Option<String>,
+        value: TermValue,
+    },
+    MatchAll,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TermValue {
+    /// Single word or space-separated bare words without field and operators
+    /// between them, which are searched together by one match query.
+    Word(String),
+    Phrase(String),
+    Wildcard(String),
+    Range {
+        lower: Bound<String>,
+        upper: Bound<String>,
+    },
+}
+
+pub fn parse(query: &str) -> Result<Expr, SyntaxError> {
+    let tokens = tokenize(query)?;
+    if tokens.is_empty() || is_match_all(&tokens) {
+        return Ok(Expr::MatchAll);
+    }
+
+    let chars = query.chars().collect::<Vec<_>>();
+    let mut parser = Parser {
+        tokens,
+        index: 0,
+        end: chars.len(),
+        chars,
+    };
+
+    let expr = parser.parse_or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(spanned) => Err(parser.unexpected(spanned)),
+    }
+}
+
+fn is_match_all(tokens: &[Spanned]) -> bool {
+    tokens.len() == 1 && tokens[0].token == Token::Word("*".to_string())
+}
+
+fn resolve_field(name: &str) -> Option<&'static str> {
+    FIELD_ALIASES
+        .iter()
+        .find(|(alias, field)| *alias == name || *field == name)
+        .map(|(_, field)| *field)
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    index: usize,
+    end: usize,
+    chars: Vec<char>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Spanned> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Spanned> {
+        let spanned = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        spanned
+    }
+
+    fn is_next(&self, token: &Token) -> bool {
+        self.peek()
+            .map(|spanned| &spanned.token == token)
+            .unwrap_or(false)
+    }
+
+    fn unexpected(&self, spanned: &Spanned) -> SyntaxError {
+        let kind = SyntaxErrorKind::UnexpectedToken(spanned.token.describe());
+        SyntaxError::new(spanned.position, kind)
+    }
+
+    fn unexpected_end(&self) -> SyntaxError {
+        SyntaxError::new(self.end, SyntaxErrorKind::UnexpectedEnd)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, SyntaxError> {
+        let mut operands = vec![self.parse_and()?];
+        loop {
+            let is_implicit = match self.peek().map(|spanned| &spanned.token) {
+                None | Some(Token::RParen) => break,
+                Some(Token::Or) => {
+                    self.next();
+                    false
+                }
+                // Terms without operator between them are combined by OR.
+                Some(_) => true,
+            };
+
+            let operand = self.parse_and()?;
+            let is_joined = is_implicit
+                && operands
+                    .last_mut()
+                    .is_some_and(|previous| join_words(previous, &operand));
+
+            if !is_joined {
+                operands.push(operand);
+            }
+        }
+
+        Ok(build_or(operands))
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, SyntaxError> {
+        let mut operands = vec![self.parse_unary()?];
+        while self.is_next(&Token::And) {
+            self.next();
+            operands.push(self.parse_unary()?);
+        }
+
+        Ok(flatten(operands, Expr::And))
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, SyntaxError> {
+        match self.peek().map(|spanned| &spanned.token) {
+            Some(Token::Not) | Some(Token::Minus) => {
+                self.next();
+                let operand = self.parse_unary()?;
+                Ok(Expr::Not(Box::new(operand)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, SyntaxError> {
+        let spanned = self.next().ok_or_else(|| self.unexpected_end())?;
+        match spanned.token {
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Spanned {
+                        token: Token::RParen,
+                        ..
+                    }) => Ok(expr),
+                    _ => {
+                        let kind = SyntaxErrorKind::UnclosedParenthesis;
+                        Err(SyntaxError::new(spanned.position, kind))
+                    }
+                }
+            }
+            Token::Word(word) if self.is_next(&Token::Colon) => {
+                let colon = self.next().ok_or_else(|| self.unexpected_end())?;
+                let Some(field) = resolve_field(&word) else {
+                    return self.parse_literal(word, spanned.position, colon.position);
+                };
+
+                let value = self.parse_field_value(field)?;
+                Ok(Expr::Term {
+                    field: Some(field.to_string()),
+                    value,
+                })
+            }
+            Token::Word(word) => Ok(Expr::Term {
+                field: None,
+                value: word_value(word),
+            }),
+            Token::Phrase(phrase) => Ok(Expr::Term {
+                field: None,
+                value: TermValue::Phrase(phrase),
+            }),
+            _ => Err(self.unexpected(&spanned)),
+        }
+    }
+
+    /// Parses `word:value` with unknown field as literal text up to the next
+    /// whitespace, e.g. `C:\Users\docs` or `http://host/path`. Value must
+    /// follow the colon without whitespace, otherwise field is unknown.
+    fn parse_literal(
+        &mut self,
+        word: String,
+        start: usize,
+        colon: usize,
+    ) -> Result<Expr, SyntaxError> {
+        let has_value = self
+            .chars
+            .get(colon + 1)
+            .map(|symbol| !symbol.is_whitespace())
+            .unwrap_or(false);
+
+        if !has_value {
+            let kind = SyntaxErrorKind::UnknownField(word);
+            return Err(SyntaxError::new(start, kind));
+        }
+
+        let (literal, end) = read_literal(&self.chars, start);
+        while self.peek().is_some_and(|spanned| spanned.position < end) {
+            self.next();
+        }
+
+        Ok(Expr::Term {
+            field: None,
+            value: word_value(literal),
+        })
+    }
+
+    fn parse_field_value(&mut self, field: &str) -> Result<TermValue, SyntaxError> {
+        let missing_value = |position| {
+            let kind = SyntaxErrorKind::MissingValue(field.to_string());
+            SyntaxError::new(position, kind)
+        };
+
+        let spanned = self.next().ok_or_else(|| missing_value(self.end))?;
+        match spanned.token {
+            Token::Word(word) => Ok(word_value(word)),
+            Token::Phrase(phrase) => Ok(TermValue::Phrase(phrase)),
+            Token::RangeStart { inclusive } => self.parse_range(inclusive),
+            Token::Compare(comparison) => {
+                let bound = match self.next() {
+                    Some(Spanned {
+                        token: Token::Word(word),
+                        ..
+                    }) => word,
+                    Some(Spanned {
+                        token: Token::Phrase(phrase),
+                        ..
+                    }) => phrase,
+                    Some(other) => return Err(missing_value(other.position)),
+                    None => return Err(missing_value(self.end)),
+                };
+
+                Ok(comparison_range(comparison, bound))
+            }
+            _ => Err(missing_value(spanned.position)),
+        }
+    }
+
+    fn parse_range(&mut self, lower_inclusive: bool) -> Result<TermValue, SyntaxError> {
+        let lower = self.parse_range_bound(lower_inclusive)?;
+
+        match self.next() {
+            Some(Spanned {
+                token: Token::Word(word),
+                ..
+            }) if word == "TO" => {}
+            Some(other) => {
+                let kind = SyntaxErrorKind::InvalidRange("expected 'TO'".to_string());
+                return Err(SyntaxError::new(other.position, kind));
+            }
+            None => return Err(self.unexpected_end()),
+        }
+
+        let upper_spanned = self.next().ok_or_else(|| self.unexpected_end())?;
+        let upper_value = match upper_spanned.token {
+            Token::Word(word) => word,
+            Token::Phrase(phrase) => phrase,
+            _ => return Err(self.unexpected(&upper_spanned)),
+        };
+
+        let upper_inclusive = match self.next() {
+            Some(Spanned {
+                token: Token::RangeEnd { inclusive },
+                ..
+            }) => inclusive,
+            Some(other) => {
+                let kind = SyntaxErrorKind::InvalidRange("expected ']' or '}'".to_string());
+                return Err(SyntaxError::new(other.position, kind));
+            }
+            None => return Err(self.unexpected_end()),
+        };
+
+        Ok(TermValue::Range {
+            lower,
+            upper: range_bound(upper_value, upper_inclusive),
+        })
+    }
+
+    fn parse_range_bound(&mut self, inclusive: bool) -> Result<Bound<String>, SyntaxError> {
+        let spanned = self.next().ok_or_else(|| self.unexpected_end())?;
+        match spanned.token {
+            Token::Word(word) if word != "TO" => Ok(range_bound(word, inclusive)),
+            Token::Phrase(phrase) => Ok(range_bound(phrase, inclusive)),
+            _ => {
+                let kind = SyntaxErrorKind::InvalidRange("missing lower bound".to_string());
+                Err(SyntaxError::new(spanned.position, kind))
+            }
+        }
+    }
+}
+
+/// Builds OR group like elastic `query_string` does: excluded operands must
+/// not match and the rest ones are combined by OR, so `lease -draft` finds
+/// documents with "lease" and without "draft".
+fn build_or(operands: Vec<Expr>) -> Expr {
+    let (excluded, included): (Vec<_>, Vec<_>) = operands
+        .into_iter()
+        .partition(|operand| matches!(operand, Expr::Not(_)));
+
+    if excluded.is_empty() {
+        return flatten(included, Expr::Or);
+    }
+
+    let mut operands = Vec::with_capacity(excluded.len() + 1);
+    if !included.is_empty() {
+        operands.push(flatten(included, Expr::Or));
+    }
+
+    operands.extend(excluded);
+    flatten(operands, Expr::And)
+}
+
+fn flatten(mut operands: Vec<Expr>, build: fn(Vec<Expr>) -> Expr) -> Expr {
+    match operands.len() {
+        1 => operands.remove(0),
+        _ => build(operands),
+    }
+}
+
+/// Appends bare word to the previous bare words, so adjacent words are
+/// matched by one query which respects operator and minimum should match
+/// of match options. Returns false if operands are not bare words.
+fn join_words(previous: &mut Expr, operand: &Expr) -> bool {
+    match (previous, operand) {
+        (
+            Expr::Term {
+                field: None,
+                value: TermValue::Word(words),
+            },
+            Expr::Term {
+                field: None,
+                value: TermValue::Word(word),
+            },
+        ) => {
+            words.push(' ');
+            words.push_str(word);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn word_value(word: String) -> TermValue {
+    match word.contains(['*', '?']) {
+        true => TermValue::Wildcard(word),
+        false => TermValue::Word(word),
+    }
+}
+
+fn range_bound(value: String, inclusive: bool) -> Bound<String> {
+    match (value.as_str(), inclusive) {
+        ("*", _) => Bound::Unbounded,
+        (_, true) => Bound::Included(value),
+        (_, false) => Bound::Excluded(value),
+    }
+}
+
+fn comparison_range(comparison: Comparison, value: String) -> TermValue {
+    let (lower, upper) = match comparison {
+        Comparison::Gt => (Bound::Excluded(value), Bound::Unbounded),
+        Comparison::Gte => (Bound::Included(value), Bound::Unbounded),
+        Comparison::Lt => (Bound::Unbounded, Bound::Excluded(value)),
+        Comparison::Lte => (Bound::Unbounded, Bound::Included(value)),
+    };
+
+    TermValue::Range { lower, upper }
+}
+
+#[cfg(test)]
+mod parser_tests {
+    use super::*;
+
+    fn term(field: Option<&str>, value: TermValue) -> Expr {
+        let field = field.map(String::from);
+        Expr::Term { field, value }
+    }
+
+    fn word(value: &str) -> TermValue {
+        TermValue::Word(value.to_string())
+    }
+
+    #[test]
+    fn parse_operators_precedence() {
+        let expr = parse("lease OR tenant AND NOT (draft -archive)").unwrap();
+        let expected = Expr::Or(vec![
+            term(None, word("lease")),
+            Expr::And(vec![
+                term(None, word("tenant")),
+                Expr::Not(Box::new(Expr::And(vec![
+                    term(None, word("draft")),
+                    Expr::Not(Box::new(term(None, word("archive")))),
+                ]))),
+            ]),
+        ]);
+
+        assert_eq!(expr, expected);
+
+        let expr = parse("-draft lease OR tenant").unwrap();
+        let expected = Expr::And(vec![
+            Expr::Or(vec![term(None, word("lease")), term(None, word("tenant"))]),
+            Expr::Not(Box::new(term(None, word("draft")))),
+        ]);
+
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_adjacent_words() {
+        let expr = parse("lease fee OR tenant deposit ext:pdf fee").unwrap();
+        let expected = Expr::Or(vec![
+            term(None, word("lease fee")),
+            term(None, word("tenant deposit")),
+            term(Some("document_extension"), word("pdf")),
+            term(None, word("fee")),
+        ]);
+        assert_eq!(expr, expected);
+
+        let expr = parse("lease fee AND tenant").unwrap();
+        let expected = Expr::Or(vec![
+            term(None, word("lease")),
+            Expr::And(vec![term(None, word("fee")), term(None, word("tenant"))]),
+        ]);
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_fields_and_values() {
+        let expr = parse("ext:pdf name:\"annual report\" path:docs/* 2023-01-01").unwrap();
+        let expected = Expr::Or(vec![
+            term(Some("document_extension"), word("pdf")),
+            term(
+                Some("document_name"),
+                TermValue::Phrase("annual report".to_string()),
+            ),
+            term(
+                Some("document_path"),
+                TermValue::Wildcard("docs/*".to_string()),
+            ),
+            term(None, word("2023-01-01")),
+        ]);
+
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_unknown_fields_as_text() {
+        let expr = parse("owner:me AND C:\\Users\\docs").unwrap();
+        let expected = Expr::And(vec![
+            term(None, word("owner:me")),
+            term(None, word("C:\\Users\\docs")),
+        ]);
+        assert_eq!(expr, expected);
+
+        let expr = parse("(http://host:9200/path?q=1) name:\\(draft\\)").unwrap();
+        let expected = Expr::Or(vec![
+            term(
+                None,
+                TermValue::Wildcard("http://host:9200/path?q=1".to_string()),
+            ),
+            term(Some("document_name"), word("(draft)")),
+        ]);
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_ranges() {
+        let expr = parse("size:[1000 TO 5000} AND created:>=2023-01-01").unwrap();
+        let expected = Expr::And(vec![
+            term(
+                Some("document_size"),
+                TermValue::Range {
+                    lower: Bound::Included("1000".to_string()),
+                    upper: Bound::Excluded("5000".to_string()),
+                },
+            ),
+            term(
+                Some("document_created"),
+                TermValue::Range {
+                    lower: Bound::Included("2023-01-01".to_string()),
+                    upper: Bound::Unbounded,
+                },
+            ),
+        ]);
+
+        assert_eq!(expr, expected);
+        assert!(matches!(
+            parse("size:[* TO 10]").unwrap(),
+            Expr::Term {
+                value: TermValue::Range {
+                    lower: Bound::Unbounded,
+                    ..
+                },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_match_all() {
+        assert_eq!(parse("").unwrap(), Expr::MatchAll);
+        assert_eq!(parse("  *  ").unwrap(), Expr::MatchAll);
+    }
+
+    #[test]
+    fn parse_syntax_errors() {
+        let error = parse("lease AND (tenant").unwrap_err();
+        assert_eq!(error.kind, SyntaxErrorKind::UnclosedParenthesis);
+        assert_eq!(error.position, 10);
+
+        let error = parse("name:\"report").unwrap_err();
+        assert_eq!(error.kind, SyntaxErrorKind::UnterminatedPhrase);
+        assert_eq!(error.position, 5);
+
+        let error = parse("lease owner: me").unwrap_err();
+        assert_eq!(
+            error.kind,
+            SyntaxErrorKind::UnknownField("owner".to_string())
+        );
+        assert_eq!(error.position, 6);
+
+        let error = parse("size:[10 5000]").unwrap_err();
+        assert!(matches!(error.kind, SyntaxErrorKind::InvalidRange(_)));
+        assert_eq!(error.position, 9);
+
+        let error = parse("lease AND").unwrap_err();
+        assert_eq!(error.kind, SyntaxErrorKind::UnexpectedEnd);
+        assert_eq!(error.position, 9);
+
+        let error = parse("lease)").unwrap_err();
+        assert_eq!(
+            error.kind,
+            SyntaxErrorKind::UnexpectedToken(")".to_string())
+        );
+        assert_eq!(error.position, 5);
+
+        let error = parse("ext:").unwrap_err();
+        assert_eq!(
+            error.kind,
+            SyntaxErrorKind::MissingValue("document_extension".to_string())
+        );
+    }
+}
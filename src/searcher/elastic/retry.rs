@@ -0,0 +1,31 @@
+use std::future::Future;
+use std::time::Duration;
+
+const MAX_RETRIES: usize = 3;
+const RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Returns true for errors of sending request to node, which does not mean
+/// request has been processed, unlike errors of response status or body.
+pub fn is_connection_error(err: &elasticsearch::Error) -> bool {
+    err.is_timeout() || (err.status_code().is_none() && !err.is_json())
+}
+
+/// Sends idempotent request again on connection errors. Every attempt is
+/// sent to the next node of connection pool.
+pub async fn retry_idempotent<T, F, Fut>(send: F) -> Result<T, elasticsearch::Error>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, elasticsearch::Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match send().await {
+            Err(err) if attempt < MAX_RETRIES && is_connection_error(&err) => {
+                attempt += 1;
+                log::warn!("Retrying elastic request ({}): {}", attempt, err);
+                tokio::time::sleep(RETRY_DELAY * attempt as u32).await;
+            }
+            result => return result,
+        }
+    }
+}
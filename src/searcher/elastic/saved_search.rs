@@ -0,0 +1,376 @@
+use crate::errors::{WebError, WebResponse};
+use crate::searcher::elastic::retry::retry_idempotent;
+use crate::wrappers::saved_search::SavedSearch;
+
+use chrono::Utc;
+use elasticsearch::http::response::Response;
+use elasticsearch::http::StatusCode;
+use elasticsearch::indices::{IndicesCreateParts, IndicesExistsParts};
+use elasticsearch::params::Refresh;
+use elasticsearch::{
+    CreateParts, DeleteParts, Elasticsearch, GetParts, IndexParts, SearchParts, UpdateParts,
+};
+use hasher::{gen_hash, HashType};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// System index of saved searches. It is hidden and starts with dot, so it
+/// is not matched by `*` of searches over all buckets.
+pub const SAVED_SEARCHES_INDEX: &str = ".searcher-saved-searches";
+
+/// Saved searches are listed by a single request, so their count is limited
+/// by default result window.
+const MAX_SAVED_SEARCHES: i64 = 10_000;
+
+/// System index of names of saved searches, where document id is built from
+/// name. Name document is created before search is stored, so one name can
+/// not be taken by concurrent requests.
+pub const SAVED_SEARCH_NAMES_INDEX: &str = ".searcher-saved-search-names";
+
+pub fn is_saved_search_index(index: &str) -> bool {
+    index == SAVED_SEARCHES_INDEX || index == SAVED_SEARCH_NAMES_INDEX
+}
+
+/// Search parameters are kept in source only, so arbitrary parameters do
+/// not change mapping of index.
+fn saved_searches_scheme() -> Value {
+    json!({
+        "settings": { "index.hidden": true },
+        "mappings": {
+            "dynamic": false,
+            "properties": {
+                "id": { "type": "keyword" },
+                "name": { "type": "keyword" },
+                "owner": { "type": "keyword" },
+                "created": { "type": "date" },
+                "last_run": { "type": "date" },
+            }
+        }
+    })
+}
+
+fn saved_search_names_scheme() -> Value {
+    json!({
+        "settings": { "index.hidden": true },
+        "mappings": {
+            "dynamic": false,
+            "properties": {
+                "search_id": { "type": "keyword" },
+            }
+        }
+    })
+}
+
+/// Loads all saved searches sorted by name, there are no searches until the
+/// index is created by the first one.
+pub async fn load_saved_searches(elastic: &Elasticsearch) -> WebResponse<Vec<SavedSearch>> {
+    let indexes = [SAVED_SEARCHES_INDEX];
+    let response = retry_idempotent(|| {
+        elastic
+            .search(SearchParts::Index(&indexes))
+            .size(MAX_SAVED_SEARCHES)
+            .ignore_unavailable(true)
+            .body(json!({
+                "query": { "match_all": {} },
+                "sort": [{ "name": "asc" }],
+            }))
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    let common_object = read_response(response).await?;
+    Ok(extract_saved_searches(&common_object))
+}
+
+/// Returns search by id or by name if there is no such id.
+pub async fn get_saved_search(
+    elastic: &Elasticsearch,
+    id_or_name: &str,
+) -> WebResponse<SavedSearch> {
+    let response = retry_idempotent(|| {
+        elastic
+            .get(GetParts::IndexId(SAVED_SEARCHES_INDEX, id_or_name))
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    if response.status_code() != StatusCode::NOT_FOUND {
+        let common_object = read_response(response).await?;
+        return SavedSearch::deserialize(&common_object[&"_source"])
+            .map_err(|err| WebError::SavedSearch(err.to_string()));
+    }
+
+    let name = id_or_name.trim();
+    let indexes = [SAVED_SEARCHES_INDEX];
+    let response = retry_idempotent(|| {
+        elastic
+            .search(SearchParts::Index(&indexes))
+            .size(1)
+            .ignore_unavailable(true)
+            .body(json!({ "query": { "term": { "name": name } } }))
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    let common_object = read_response(response).await?;
+    extract_saved_searches(&common_object)
+        .into_iter()
+        .next()
+        .ok_or_else(|| WebError::SavedSearch(format!("no such saved search: {}", id_or_name)))
+}
+
+/// Stores new or changed search. Name of new or renamed search is taken
+/// before search is stored and the previous name is released after it.
+pub async fn store_saved_search(
+    elastic: &Elasticsearch,
+    search: &SavedSearch,
+    previous_name: Option<&str>,
+) -> WebResponse<()> {
+    create_saved_search_indexes(elastic).await?;
+    let is_renamed = previous_name != Some(search.name.as_str());
+    if is_renamed {
+        take_name(elastic, search).await?;
+    }
+
+    if let Err(err) = index_saved_search(elastic, search).await {
+        if is_renamed {
+            release_name(elastic, &search.name)
+                .await
+                .unwrap_or_else(|err| {
+                    log::warn!("Failed to release saved search name: {}", err);
+                });
+        }
+
+        return Err(err);
+    }
+
+    match previous_name {
+        Some(name) if is_renamed => release_name(elastic, name).await,
+        _ => Ok(()),
+    }
+}
+
+/// Records the last run time by partial update, so concurrent changes of
+/// search are not overwritten.
+pub async fn mark_saved_search_run(
+    elastic: &Elasticsearch,
+    id_or_name: &str,
+) -> WebResponse<SavedSearch> {
+    let mut search = get_saved_search(elastic, id_or_name).await?;
+    let last_run = Utc::now();
+    let response = retry_idempotent(|| {
+        elastic
+            .update(UpdateParts::IndexId(SAVED_SEARCHES_INDEX, &search.id))
+            .refresh(Refresh::WaitFor)
+            .body(json!({ "doc": { "last_run": last_run } }))
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    read_response(response).await?;
+    search.last_run = Some(last_run);
+    Ok(search)
+}
+
+pub async fn delete_saved_search(elastic: &Elasticsearch, id_or_name: &str) -> WebResponse<()> {
+    let search = get_saved_search(elastic, id_or_name).await?;
+    let response = retry_idempotent(|| {
+        elastic
+            .delete(DeleteParts::IndexId(SAVED_SEARCHES_INDEX, &search.id))
+            .refresh(Refresh::WaitFor)
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    read_response(response).await?;
+    release_name(elastic, &search.name).await
+}
+
+/// Stores search by its id and waits until it is visible to search, so the
+/// next request finds it by name.
+async fn index_saved_search(elastic: &Elasticsearch, search: &SavedSearch) -> WebResponse<()> {
+    let response = retry_idempotent(|| {
+        elastic
+            .index(IndexParts::IndexId(SAVED_SEARCHES_INDEX, &search.id))
+            .refresh(Refresh::WaitFor)
+            .body(search)
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    read_response(response).await.map(|_| ())
+}
+
+/// Creates document of search name which fails if name is already taken.
+/// Creation is not idempotent, so request is not retried.
+async fn take_name(elastic: &Elasticsearch, search: &SavedSearch) -> WebResponse<()> {
+    let name_id = build_name_id(&search.name);
+    let response = elastic
+        .create(CreateParts::IndexId(SAVED_SEARCH_NAMES_INDEX, &name_id))
+        .body(json!({ "search_id": search.id }))
+        .send()
+        .await
+        .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    match response.status_code() {
+        StatusCode::CONFLICT => {
+            let msg = format!("saved search {} already exists", search.name);
+            Err(WebError::SavedSearch(msg))
+        }
+        _ => read_response(response).await.map(|_| ()),
+    }
+}
+
+async fn release_name(elastic: &Elasticsearch, name: &str) -> WebResponse<()> {
+    let name_id = build_name_id(name);
+    let response = retry_idempotent(|| {
+        elastic
+            .delete(DeleteParts::IndexId(SAVED_SEARCH_NAMES_INDEX, &name_id))
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    match response.status_code() {
+        StatusCode::NOT_FOUND => Ok(()),
+        _ => read_response(response).await.map(|_| ()),
+    }
+}
+
+/// Builds id of name document, names may be longer than allowed document id.
+/// Names are trimmed when saved search is created or updated.
+fn build_name_id(name: &str) -> String {
+    gen_hash(HashType::MD5, name.as_bytes())
+        .map(|hashed| hashed.get_hash_data().to_string())
+        .unwrap_or_else(|_| name.to_string())
+}
+
+async fn create_saved_search_indexes(elastic: &Elasticsearch) -> WebResponse<()> {
+    create_index(elastic, SAVED_SEARCHES_INDEX, saved_searches_scheme()).await?;
+    create_index(
+        elastic,
+        SAVED_SEARCH_NAMES_INDEX,
+        saved_search_names_scheme(),
+    )
+    .await
+}
+
+async fn create_index(elastic: &Elasticsearch, index: &str, scheme: Value) -> WebResponse<()> {
+    let indices = elastic.indices();
+    let indexes = [index];
+    let exists = retry_idempotent(|| indices.exists(IndicesExistsParts::Index(&indexes)).send())
+        .await
+        .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    if exists.status_code().is_success() {
+        return Ok(());
+    }
+
+    let response = retry_idempotent(|| {
+        indices
+            .create(IndicesCreateParts::Index(index))
+            .body(&scheme)
+            .send()
+    })
+    .await
+    .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    read_response(response).await.map(|_| ())
+}
+
+pub fn extract_saved_searches(common_object: &Value) -> Vec<SavedSearch> {
+    let Some(hits) = common_object[&"hits"][&"hits"].as_array() else {
+        return Vec::default();
+    };
+
+    hits.iter()
+        .filter_map(|hit| match SavedSearch::deserialize(&hit[&"_source"]) {
+            Ok(search) => Some(search),
+            Err(err) => {
+                log::warn!("Failed to parse saved search: {}", err);
+                None
+            }
+        })
+        .collect()
+}
+
+async fn read_response(response: Response) -> Result<Value, WebError> {
+    let status_code = response.status_code();
+    let common_object = response
+        .json::<Value>()
+        .await
+        .map_err(|err| WebError::SavedSearch(err.to_string()))?;
+
+    match status_code.is_success() {
+        true => Ok(common_object),
+        false => {
+            let reason = &common_object[&"error"][&"reason"];
+            let msg = reason.as_str().unwrap_or(status_code.as_str());
+            Err(WebError::SavedSearch(msg.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod saved_search_tests {
+    use super::*;
+    use crate::wrappers::saved_search::SavedSearchForm;
+    use crate::wrappers::search_params::SearchParams;
+
+    #[test]
+    fn extract_saved_searches_test() {
+        let params = SearchParams {
+            query: "lease fee".to_string(),
+            ..Default::default()
+        };
+
+        let common_object = json!({
+            "hits": {
+                "hits": [
+                    {
+                        "_id": "first",
+                        "_source": {
+                            "id": "first",
+                            "name": "leases",
+                            "owner": "analyst",
+                            "buckets": "reports",
+                            "params": params,
+                            "created": "2024-01-10T08:00:00Z",
+                            "last_run": "2024-01-11T08:00:00Z",
+                        }
+                    },
+                    { "_id": "broken", "_source": { "id": "broken" } },
+                ]
+            }
+        });
+
+        let searches = extract_saved_searches(&common_object);
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches[0].name, "leases");
+        assert_eq!(searches[0].buckets.as_deref(), Some("reports"));
+        assert_eq!(searches[0].params.query, "lease fee");
+        assert!(searches[0].last_run.is_some());
+        assert!(extract_saved_searches(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn build_name_id_test() {
+        let form = SavedSearchForm {
+            name: " leases ".to_string(),
+            owner: "analyst".to_string(),
+            buckets: None,
+            params: SearchParams::default(),
+        };
+
+        let search = SavedSearch::new(&form);
+        assert_eq!(build_name_id("leases"), build_name_id(&search.name));
+        assert_ne!(build_name_id("leases"), build_name_id("Leases"));
+        assert_eq!(build_name_id(&"a".repeat(1024)).len(), 32);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::service::{CertValidation, ElasticAuth, ElasticTls};
+
+use anyhow::anyhow;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use elasticsearch::auth::{ClientCertificate, Credentials};
+use elasticsearch::cert::{Certificate, CertificateValidation};
+use elasticsearch::http::headers::{HeaderValue, AUTHORIZATION};
+use elasticsearch::http::transport::{ConnectionPool, TransportBuilder};
+use elasticsearch::Elasticsearch;
+use openssl::pkcs12::Pkcs12;
+use openssl::pkey::PKey;
+use openssl::x509::X509;
+
+/// Credentials and TLS settings of elastic transport loaded from service
+/// configuration, which are used to build clients of connection pools.
+#[derive(Clone, Default)]
+pub struct TransportConfig {
+    credentials: Option<Credentials>,
+    auth_header: Option<HeaderValue>,
+    cert_validation: CertValidation,
+    ca_cert: Option<Vec<u8>>,
+}
+
+impl TransportConfig {
+    #[cfg(test)]
+    pub fn basic(es_user: &str, es_passwd: &str) -> Self {
+        TransportConfig {
+            credentials: Some(Credentials::Basic(es_user.into(), es_passwd.into())),
+            cert_validation: CertValidation::None,
+            ..Default::default()
+        }
+    }
+
+    /// Loads certificates and keys passed by TLS settings. Client certificate
+    /// replaces credentials of transport, so the other auth method is passed
+    /// by default `Authorization` header.
+    pub fn load(
+        es_user: &str,
+        es_passwd: &str,
+        es_auth: &ElasticAuth,
+        es_tls: &ElasticTls,
+    ) -> Result<Self, anyhow::Error> {
+        let credentials = match es_auth {
+            ElasticAuth::Basic => Some(Credentials::Basic(es_user.into(), es_passwd.into())),
+            ElasticAuth::Bearer { token } => Some(Credentials::Bearer(token.clone())),
+            ElasticAuth::ApiKey { id, key } => Some(Credentials::ApiKey(id.clone(), key.clone())),
+            ElasticAuth::None => None,
+        };
+
+        let ca_cert = match es_tls.ca_cert.as_ref() {
+            Some(path) => Some(std::fs::read(path)?),
+            None => None,
+        };
+
+        let needs_ca_cert = matches!(
+            es_tls.cert_validation,
+            CertValidation::Full | CertValidation::Certificate
+        );
+        if needs_ca_cert && ca_cert.is_none() {
+            let msg = "There is not ELASTIC_CA_CERT for certificate validation";
+            return Err(anyhow!(msg));
+        }
+
+        let mut config = TransportConfig {
+            credentials,
+            auth_header: None,
+            cert_validation: es_tls.cert_validation,
+            ca_cert,
+        };
+
+        if let Some(cert_path) = es_tls.client_cert.as_ref() {
+            let client_cert = load_client_cert(cert_path, es_tls)?;
+            config.auth_header = config.credentials.as_ref().map(build_auth_header);
+            config.credentials = Some(Credentials::Certificate(client_cert));
+        }
+
+        Ok(config)
+    }
+
+    pub fn build_client<P>(&self, conn_pool: P) -> Result<Elasticsearch, elasticsearch::Error>
+    where
+        P: ConnectionPool + Clone + 'static,
+    {
+        let mut builder = TransportBuilder::new(conn_pool).cert_validation(self.validation()?);
+        if let Some(credentials) = self.credentials.clone() {
+            builder = builder.auth(credentials);
+        }
+
+        if let Some(auth_header) = self.auth_header.clone() {
+            builder = builder.header(AUTHORIZATION, auth_header);
+        }
+
+        Ok(Elasticsearch::new(builder.build()?))
+    }
+
+    fn validation(&self) -> Result<CertificateValidation, elasticsearch::Error> {
+        let ca_cert = match self.ca_cert.as_ref() {
+            Some(pem) => Some(Certificate::from_pem(pem.as_slice())?),
+            None => None,
+        };
+
+        let validation = match (self.cert_validation, ca_cert) {
+            (CertValidation::Full, Some(ca_cert)) => CertificateValidation::Full(ca_cert),
+            (CertValidation::Certificate, Some(ca_cert)) => {
+                CertificateValidation::Certificate(ca_cert)
+            }
+            (CertValidation::None, _) => CertificateValidation::None,
+            _ => CertificateValidation::Default,
+        };
+
+        Ok(validation)
+    }
+}
+
+/// Loads PKCS#12 archive or builds it from PEM certificate and private key.
+fn load_client_cert(
+    cert_path: &str,
+    es_tls: &ElasticTls,
+) -> Result<ClientCertificate, anyhow::Error> {
+    let cert_data = std::fs::read(cert_path)?;
+    let passwd = es_tls.client_cert_passwd.clone();
+    let Some(key_path) = es_tls.client_key.as_ref() else {
+        return Ok(ClientCertificate::Pkcs12(cert_data, passwd));
+    };
+
+    let key_data = std::fs::read(key_path)?;
+    let pkey = match passwd.as_ref() {
+        Some(passwd) => PKey::private_key_from_pem_passphrase(&key_data, passwd.as_bytes())?,
+        None => PKey::private_key_from_pem(&key_data)?,
+    };
+
+    let mut certs = X509::stack_from_pem(&cert_data)?.into_iter();
+    let Some(cert) = certs.next() else {
+        return Err(anyhow!("There is no certificate in {}", cert_path));
+    };
+
+    let mut chain = openssl::stack::Stack::new()?;
+    for ca_cert in certs {
+        chain.push(ca_cert)?;
+    }
+
+    let pkcs12 = Pkcs12::builder()
+        .name("searcher")
+        .pkey(&pkey)
+        .cert(&cert)
+        .ca(chain)
+        .build2("")?;
+
+    Ok(ClientCertificate::Pkcs12(pkcs12.to_der()?, None))
+}
+
+fn build_auth_header(credentials: &Credentials) -> HeaderValue {
+    let value = match credentials {
+        Credentials::Basic(user, passwd) => {
+            format!("Basic {}", STANDARD.encode(format!("{}:{}", user, passwd)))
+        }
+        Credentials::Bearer(token) => format!("Bearer {}", token),
+        Credentials::ApiKey(id, key) => {
+            format!("ApiKey {}", STANDARD.encode(format!("{}:{}", id, key)))
+        }
+        Credentials::Certificate(_) => String::default(),
+    };
+
+    HeaderValue::from_str(value.as_str()).unwrap_or(HeaderValue::from_static(""))
+}
+
+#[cfg(test)]
+mod transport_tests {
+    use super::*;
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::rsa::Rsa;
+    use openssl::x509::X509NameBuilder;
+
+    fn write_client_cert(name: &str) -> (String, String) {
+        let pkey = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let mut subject = X509NameBuilder::new().unwrap();
+        subject.append_entry_by_text("CN", "searcher").unwrap();
+        let subject = subject.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_subject_name(&subject).unwrap();
+        builder.set_issuer_name(&subject).unwrap();
+        builder.set_pubkey(&pkey).unwrap();
+        builder
+            .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        builder.sign(&pkey, MessageDigest::sha256()).unwrap();
+        let cert = builder.build();
+
+        let cert_path = std::env::temp_dir().join(format!("{}.crt", name));
+        let key_path = std::env::temp_dir().join(format!("{}.key", name));
+        std::fs::write(&cert_path, cert.to_pem().unwrap()).unwrap();
+        std::fs::write(&key_path, pkey.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        (
+            cert_path.to_string_lossy().to_string(),
+            key_path.to_string_lossy().to_string(),
+        )
+    }
+
+    #[test]
+    fn build_auth_headers() {
+        let basic = Credentials::Basic("elastic".into(), "passwd".into());
+        assert_eq!(build_auth_header(&basic), "Basic ZWxhc3RpYzpwYXNzd2Q=");
+
+        let api_key = Credentials::ApiKey("id".into(), "key".into());
+        assert_eq!(build_auth_header(&api_key), "ApiKey aWQ6a2V5");
+
+        let bearer = Credentials::Bearer("token".into());
+        assert_eq!(build_auth_header(&bearer), "Bearer token");
+    }
+
+    #[test]
+    fn require_ca_cert_for_validation() {
+        let es_tls = ElasticTls {
+            cert_validation: CertValidation::Full,
+            ..Default::default()
+        };
+
+        let result = TransportConfig::load("elastic", "passwd", &ElasticAuth::Basic, &es_tls);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn load_pem_client_cert() {
+        let (cert_path, key_path) = write_client_cert("searcher_transport_client");
+        let es_tls = ElasticTls {
+            cert_validation: CertValidation::None,
+            client_cert: Some(cert_path),
+            client_key: Some(key_path),
+            ..Default::default()
+        };
+
+        let es_auth = ElasticAuth::ApiKey {
+            id: "id".to_string(),
+            key: "key".to_string(),
+        };
+
+        let config = TransportConfig::load("", "", &es_auth, &es_tls).unwrap();
+        assert!(matches!(
+            config.credentials,
+            Some(Credentials::Certificate(_))
+        ));
+        assert_eq!(config.auth_header.unwrap(), "ApiKey aWQ6a2V5");
+    }
+}
@@ -0,0 +1,254 @@
+use crate::errors::{WebError, WebResponse};
+use crate::searcher::federated::context::FederatedContext;
+use crate::searcher::federated::helper::*;
+use crate::searcher::service_client::{DocumentStream, JsonResponse, ServiceClient};
+use crate::wrappers::bucket::{Bucket, BucketForm};
+use crate::wrappers::cluster::Cluster;
+use crate::wrappers::document::Document;
+use crate::wrappers::saved_search::{SavedSearch, SavedSearchForm};
+use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::{merge_suggestions, SuggestForm, Suggestion};
+
+use actix_web::{web, HttpResponse};
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
+
+/// Composite client which sends search requests to all backends and merges
+/// their results. Documents are read from the first backend containing them
+/// and all write requests are sent to the primary backend, which also keeps
+/// saved searches.
+#[async_trait::async_trait]
+impl ServiceClient for FederatedContext {
+    async fn get_all_clusters(&self) -> JsonResponse<Vec<Cluster>> {
+        self.primary().client().get_all_clusters().await
+    }
+
+    async fn get_cluster(&self, cluster_id: &str) -> JsonResponse<Cluster> {
+        self.primary().client().get_cluster(cluster_id).await
+    }
+
+    async fn create_cluster(&self, cluster_id: &str) -> HttpResponse {
+        self.primary().client().create_cluster(cluster_id).await
+    }
+
+    async fn delete_cluster(&self, cluster_id: &str) -> HttpResponse {
+        self.primary().client().delete_cluster(cluster_id).await
+    }
+
+    async fn get_all_buckets(&self) -> JsonResponse<Vec<Bucket>> {
+        let futures = self
+            .backends()
+            .iter()
+            .map(|backend| with_timeout(backend, backend.client().get_all_buckets()));
+
+        let buckets = join_all(futures)
+            .await
+            .into_iter()
+            .filter_map(Result::ok)
+            .flatten()
+            .collect();
+
+        Ok(web::Json(buckets))
+    }
+
+    async fn get_bucket(&self, bucket_id: &str) -> JsonResponse<Bucket> {
+        for backend in self.backends() {
+            if let Ok(bucket) = with_timeout(backend, backend.client().get_bucket(bucket_id)).await
+            {
+                return Ok(web::Json(bucket));
+            }
+        }
+
+        let msg = format!("no such bucket: {}", bucket_id);
+        Err(WebError::GetBucket(msg))
+    }
+
+    async fn delete_bucket(&self, bucket_id: &str) -> HttpResponse {
+        self.primary().client().delete_bucket(bucket_id).await
+    }
+
+    async fn create_bucket(&self, bucket_form: &BucketForm) -> HttpResponse {
+        self.primary().client().create_bucket(bucket_form).await
+    }
+
+    #[cfg(feature = "elastic-search")]
+    async fn check_duplication(&self, bucket_id: &str, document_id: &str) -> bool {
+        self.primary()
+            .client()
+            .check_duplication(bucket_id, document_id)
+            .await
+    }
+
+    async fn get_document(&self, bucket_id: &str, doc_id: &str) -> JsonResponse<Document> {
+        for backend in self.backends() {
+            let future = backend.client().get_document(bucket_id, doc_id);
+            if let Ok(document) = with_timeout(backend, future).await {
+                return Ok(web::Json(document));
+            }
+        }
+
+        let msg = format!("no such document: {}", doc_id);
+        Err(WebError::GetDocument(msg))
+    }
+
+    async fn create_document(&self, doc_form: &Document) -> HttpResponse {
+        self.primary().client().create_document(doc_form).await
+    }
+
+    async fn update_document(&self, doc_form: &Document) -> HttpResponse {
+        self.primary().client().update_document(doc_form).await
+    }
+
+    async fn delete_document(&self, bucket_id: &str, doc_id: &str) -> HttpResponse {
+        self.primary()
+            .client()
+            .delete_document(bucket_id, doc_id)
+            .await
+    }
+
+    async fn load_file_to_bucket(&self, bucket_id: &str, file_path: &str) -> HttpResponse {
+        self.primary()
+            .client()
+            .load_file_to_bucket(bucket_id, file_path)
+            .await
+    }
+
+    async fn search_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse> {
+        if s_params.is_cursor_pagination() {
+            return self.primary().client().search_all(s_params).await;
+        }
+
+        let backend_params = build_backend_params(s_params);
+        search_backends(
+            self.backends(),
+            s_params,
+            &backend_params,
+            |client, params| client.search_all(params),
+        )
+        .await
+    }
+
+    async fn search_bucket(
+        &self,
+        buckets_ids: &str,
+        s_params: &SearchParams,
+    ) -> JsonResponse<SearchResponse> {
+        if s_params.is_cursor_pagination() {
+            let client = self.primary().client();
+            return client.search_bucket(buckets_ids, s_params).await;
+        }
+
+        let backend_params = build_backend_params(s_params);
+        search_backends(
+            self.backends(),
+            s_params,
+            &backend_params,
+            |client, params| client.search_bucket(buckets_ids, params),
+        )
+        .await
+    }
+
+    /// Cursors are opened by primary backend only because results of
+    /// several backends can not be paginated by one cursor.
+    async fn close_cursor(&self, cursor: &str) -> HttpResponse {
+        self.primary().client().close_cursor(cursor).await
+    }
+
+    /// Merges completions of backends which have returned them in time.
+    async fn suggest(
+        &self,
+        buckets_ids: Option<&str>,
+        form: &SuggestForm,
+    ) -> JsonResponse<Vec<Suggestion>> {
+        let futures = self
+            .backends()
+            .iter()
+            .map(|backend| with_timeout(backend, backend.client().suggest(buckets_ids, form)));
+
+        let suggestions = join_all(futures)
+            .await
+            .into_iter()
+            .filter_map(Result::ok)
+            .flatten()
+            .collect();
+
+        Ok(web::Json(merge_suggestions(suggestions, form.size)))
+    }
+
+    /// Exports documents of every backend one after another. Unlike search
+    /// results, exported documents are not merged by rank.
+    async fn export_documents(
+        &self,
+        buckets_ids: Option<&str>,
+        s_params: &SearchParams,
+    ) -> WebResponse<DocumentStream> {
+        let mut streams = Vec::with_capacity(self.backends().len());
+        for backend in self.backends() {
+            let client = backend.client();
+            streams.push(client.export_documents(buckets_ids, s_params).await?);
+        }
+
+        Ok(Box::pin(stream::iter(streams).flatten()))
+    }
+
+    async fn similar_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse> {
+        let backend_params = build_backend_params(s_params);
+        search_backends(
+            self.backends(),
+            s_params,
+            &backend_params,
+            |client, params| client.similar_all(params),
+        )
+        .await
+    }
+
+    async fn similar_bucket(
+        &self,
+        buckets_ids: &str,
+        s_params: &SearchParams,
+    ) -> JsonResponse<SearchResponse> {
+        let backend_params = build_backend_params(s_params);
+        search_backends(
+            self.backends(),
+            s_params,
+            &backend_params,
+            |client, params| client.similar_bucket(buckets_ids, params),
+        )
+        .await
+    }
+
+    async fn get_all_saved_searches(&self) -> JsonResponse<Vec<SavedSearch>> {
+        self.primary().client().get_all_saved_searches().await
+    }
+
+    async fn get_saved_search(&self, search_id: &str) -> JsonResponse<SavedSearch> {
+        self.primary().client().get_saved_search(search_id).await
+    }
+
+    async fn create_saved_search(&self, form: &SavedSearchForm) -> JsonResponse<SavedSearch> {
+        self.primary().client().create_saved_search(form).await
+    }
+
+    async fn update_saved_search(
+        &self,
+        search_id: &str,
+        form: &SavedSearchForm,
+    ) -> JsonResponse<SavedSearch> {
+        self.primary()
+            .client()
+            .update_saved_search(search_id, form)
+            .await
+    }
+
+    async fn delete_saved_search(&self, search_id: &str) -> HttpResponse {
+        self.primary().client().delete_saved_search(search_id).await
+    }
+
+    async fn mark_saved_search_run(&self, search_id: &str) -> JsonResponse<SavedSearch> {
+        self.primary()
+            .client()
+            .mark_saved_search_run(search_id)
+            .await
+    }
+}
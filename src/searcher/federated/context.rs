@@ -0,0 +1,53 @@
+use crate::searcher::service_client::ServiceClient;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+pub struct FederatedBackend {
+    name: String,
+    timeout: Duration,
+    client: Box<dyn ServiceClient>,
+}
+
+impl FederatedBackend {
+    pub fn new(name: String, timeout: Duration, client: Box<dyn ServiceClient>) -> Self {
+        FederatedBackend {
+            name,
+            timeout,
+            client,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn client(&self) -> &dyn ServiceClient {
+        self.client.as_ref()
+    }
+}
+
+#[derive(Clone)]
+pub struct FederatedContext {
+    backends: Arc<Vec<FederatedBackend>>,
+}
+
+impl FederatedContext {
+    pub fn _new(backends: Vec<FederatedBackend>) -> Self {
+        let backends = Arc::new(backends);
+        FederatedContext { backends }
+    }
+
+    pub fn backends(&self) -> &[FederatedBackend] {
+        self.backends.as_slice()
+    }
+
+    /// Returns backend which receives all write requests.
+    pub fn primary(&self) -> &FederatedBackend {
+        &self.backends[0]
+    }
+}
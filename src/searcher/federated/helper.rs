@@ -0,0 +1,289 @@
+use crate::errors::WebError;
+use crate::searcher::federated::context::FederatedBackend;
+use crate::searcher::service_client::{JsonResponse, ServiceClient};
+use crate::wrappers::aggregation::merge_aggregations;
+use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::search_response::{FailedBackend, SearchHit, SearchResponse};
+use crate::wrappers::suggest::{is_low_result, merge_query_suggestions};
+
+use actix_web::web;
+use futures::future::{join_all, BoxFuture};
+
+use std::cmp::Ordering;
+use std::future::Future;
+use std::time::Instant;
+
+/// Awaits response of backend within its timeout.
+pub async fn with_timeout<T, F>(backend: &FederatedBackend, future: F) -> Result<T, FailedBackend>
+where
+    F: Future<Output = JsonResponse<T>>,
+{
+    let error = match tokio::time::timeout(backend.timeout(), future).await {
+        Ok(Ok(response)) => return Ok(response.0),
+        Ok(Err(err)) => err.to_string(),
+        Err(_) => format!("timed out after {} ms", backend.timeout().as_millis()),
+    };
+
+    log::warn!("Backend {} failed: {}", backend.name(), error);
+    Err(FailedBackend {
+        backend: backend.name().to_string(),
+        error,
+    })
+}
+
+/// Runs passed search on all backends concurrently and merges found documents.
+/// Backends must be asked with parameters built by `build_backend_params`
+/// because the page of merged results may consist of documents of any backend.
+pub async fn search_backends<'a, F>(
+    backends: &'a [FederatedBackend],
+    s_params: &SearchParams,
+    backend_params: &'a SearchParams,
+    search: F,
+) -> JsonResponse<SearchResponse>
+where
+    F: Fn(&'a dyn ServiceClient, &'a SearchParams) -> BoxFuture<'a, JsonResponse<SearchResponse>>,
+{
+    let started = Instant::now();
+    let search = &search;
+    let futures = backends
+        .iter()
+        .map(|backend| with_timeout(backend, search(backend.client(), backend_params)));
+
+    let mut responses = Vec::with_capacity(backends.len());
+    let mut failed_backends = Vec::new();
+    for result in join_all(futures).await {
+        match result {
+            Ok(response) => responses.push(response),
+            Err(failed) => failed_backends.push(failed),
+        }
+    }
+
+    if responses.is_empty() && !failed_backends.is_empty() {
+        let msg = failed_backends
+            .iter()
+            .map(|failed| format!("{}: {}", failed.backend, failed.error))
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(WebError::SearchFailed(msg));
+    }
+
+    let aggregations = responses
+        .iter_mut()
+        .map(|response| std::mem::take(&mut response.aggregations))
+        .collect();
+
+    let did_you_mean = responses
+        .iter_mut()
+        .flat_map(|response| std::mem::take(&mut response.did_you_mean))
+        .collect();
+
+    // Backends store different documents, so total is the sum of totals
+    // of all answered backends.
+    let total = responses.iter().map(|response| response.total).sum();
+    let timed_out = responses.iter().any(|response| response.timed_out);
+    let hits = merge_results(responses, s_params);
+    let max_score = hits.iter().filter_map(|hit| hit.score).reduce(f64::max);
+    Ok(web::Json(SearchResponse {
+        total,
+        took: started.elapsed().as_millis() as u64,
+        max_score,
+        hits,
+        failed_backends,
+        cursor: None,
+        aggregations: merge_aggregations(aggregations),
+        did_you_mean: match is_low_result(total, &s_params.query) {
+            true => merge_query_suggestions(did_you_mean),
+            false => Vec::default(),
+        },
+        timed_out,
+    }))
+}
+
+/// Returns parameters to ask backends for the first `offset + size` documents.
+pub fn build_backend_params(s_params: &SearchParams) -> SearchParams {
+    SearchParams {
+        result_offset: 0,
+        result_size: s_params.result_offset.max(0) + s_params.result_size.max(0),
+        ..s_params.clone()
+    }
+}
+
+/// Merges results of backends and returns the requested page. Scores of
+/// backends are not comparable, so scores of every backend are min-max
+/// normalised into [0, 1]: the best document of each backend has score 1.0
+/// and the worst one has 0.0. Backends which return documents without
+/// scores (e.g. sorted ones) are normalised by rank of documents instead.
+/// If sort keys are passed documents are ordered by them with normalised
+/// scores. Returned hits have normalised scores instead of scores of backends.
+pub fn merge_results(responses: Vec<SearchResponse>, s_params: &SearchParams) -> Vec<SearchHit> {
+    let mut scored = responses
+        .into_iter()
+        .enumerate()
+        .flat_map(|(backend_index, response)| {
+            normalise_scores(response.hits)
+                .into_iter()
+                .map(move |(score, hit)| (score, backend_index, hit))
+        })
+        .collect::<Vec<_>>();
+
+    scored.sort_by(|a, b| match s_params.sort.is_empty() {
+        true => {
+            b.0.partial_cmp(&a.0)
+                .unwrap_or(Ordering::Equal)
+                .then(a.1.cmp(&b.1))
+        }
+        false => s_params.compare_documents((a.0, &a.2), (b.0, &b.2)),
+    });
+
+    let offset = s_params.result_offset.max(0) as usize;
+    let size = s_params.result_size.max(0) as usize;
+    scored
+        .into_iter()
+        .skip(offset)
+        .take(size)
+        .map(|(score, _, hit)| SearchHit {
+            score: Some(score),
+            ..hit
+        })
+        .collect()
+}
+
+/// Returns hits of single backend with scores normalised into [0, 1].
+fn normalise_scores(hits: Vec<SearchHit>) -> Vec<(f64, SearchHit)> {
+    let scores = hits
+        .iter()
+        .map(|hit| hit.score.filter(|score| score.is_finite()))
+        .collect::<Option<Vec<_>>>();
+
+    let Some(scores) = scores else {
+        let hits_count = hits.len() as f64;
+        return hits
+            .into_iter()
+            .enumerate()
+            .map(|(rank, hit)| (1.0 - rank as f64 / hits_count, hit))
+            .collect();
+    };
+
+    let min = scores.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    scores
+        .into_iter()
+        .zip(hits)
+        .map(|(score, hit)| match max > min {
+            true => ((score - min) / (max - min), hit),
+            false => (1.0, hit),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod federated_tests {
+    use super::*;
+    use crate::wrappers::document::Document;
+    use crate::wrappers::search_params::{SortField, SortKey};
+
+    fn build_response(ids: &[&str]) -> SearchResponse {
+        build_scored_response(&ids.iter().map(|id| (*id, None)).collect::<Vec<_>>())
+    }
+
+    fn build_scored_response(hits: &[(&str, Option<f64>)]) -> SearchResponse {
+        let hits = hits
+            .iter()
+            .map(|(id, score)| SearchHit {
+                id: id.to_string(),
+                index: String::default(),
+                score: *score,
+                document: Document {
+                    document_md5_hash: id.to_string(),
+                    ..Default::default()
+                },
+            })
+            .collect::<Vec<_>>();
+
+        SearchResponse {
+            total: hits.len() as u64,
+            hits,
+            ..Default::default()
+        }
+    }
+
+    fn merged_ids(documents: &[SearchHit]) -> Vec<&str> {
+        documents
+            .iter()
+            .map(|doc| doc.document_md5_hash.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn merge_normalised_results() {
+        let responses = vec![
+            build_response(&["a1", "a2", "a3", "a4"]),
+            build_response(&["b1", "b2"]),
+        ];
+
+        let s_params = SearchParams::default();
+        let merged = merge_results(responses.clone(), &s_params);
+        assert_eq!(
+            merged_ids(&merged),
+            vec!["a1", "b1", "a2", "a3", "b2", "a4"]
+        );
+
+        let s_params = SearchParams {
+            result_offset: 1,
+            result_size: 2,
+            ..Default::default()
+        };
+        let merged = merge_results(responses, &s_params);
+        assert_eq!(merged_ids(&merged), vec!["b1", "a2"]);
+        assert_eq!(merged[0].score, Some(1.0));
+    }
+
+    #[test]
+    fn merge_scored_results() {
+        let responses = vec![
+            build_scored_response(&[("a1", Some(12.0)), ("a2", Some(11.5)), ("a3", Some(2.0))]),
+            build_scored_response(&[("b1", Some(0.8)), ("b2", Some(0.2))]),
+            build_scored_response(&[("c1", Some(3.0))]),
+        ];
+
+        let merged = merge_results(responses, &SearchParams::default());
+        assert_eq!(
+            merged_ids(&merged),
+            vec!["a1", "b1", "c1", "a2", "a3", "b2"]
+        );
+
+        let scores = merged.iter().map(|hit| hit.score.unwrap());
+        assert_eq!(
+            scores.collect::<Vec<_>>(),
+            vec![1.0, 1.0, 1.0, 0.95, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn merge_sorted_results() {
+        let responses = vec![build_response(&["b", "d"]), build_response(&["c", "a"])];
+        let s_params = SearchParams {
+            sort: vec![SortKey {
+                field: SortField::Size,
+                order: None,
+            }],
+            ..Default::default()
+        };
+
+        let merged = merge_results(responses, &s_params);
+        assert_eq!(merged_ids(&merged), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn build_params_for_backends() {
+        let s_params = SearchParams {
+            result_offset: 10,
+            result_size: 5,
+            ..Default::default()
+        };
+
+        let backend_params = build_backend_params(&s_params);
+        assert_eq!(backend_params.result_offset, 0);
+        assert_eq!(backend_params.result_size, 15);
+    }
+}
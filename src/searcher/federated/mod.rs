@@ -0,0 +1,120 @@
+pub mod client;
+pub mod context;
+pub mod helper;
+
+use crate::searcher::build_search_client;
+use crate::searcher::federated::context::{FederatedBackend, FederatedContext};
+#[cfg(feature = "elastic-search")]
+use crate::service::{ElasticAuth, ElasticTls};
+use crate::service::{SearchBackend, ServiceParametersBuilder};
+
+use anyhow::anyhow;
+use serde_derive::Deserialize;
+
+use std::str::FromStr;
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// Federated search configuration which is loaded from json file passed by
+/// `FEDERATED_CONFIG` env variable.
+#[derive(Deserialize)]
+pub struct FederatedConfig {
+    pub backends: Vec<BackendConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct BackendConfig {
+    pub name: String,
+    pub backend: String,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_host: String,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_user: String,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_passwd: String,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_auth: ElasticAuth,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_tls: ElasticTls,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_sniff_interval_secs: Option<u64>,
+    #[cfg(feature = "elastic-search")]
+    #[serde(default)]
+    pub es_match_config: Option<String>,
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_TIMEOUT_MS
+}
+
+/// Builds federated client which wraps clients of all configured backends.
+/// The first backend is the primary one, which receives all write requests.
+pub fn build_federated_client(config_path: &str) -> Result<FederatedContext, anyhow::Error> {
+    let config_data = std::fs::read(config_path)?;
+    let config: FederatedConfig = serde_json::from_slice(config_data.as_slice())?;
+    if config.backends.is_empty() {
+        return Err(anyhow!("There are no backends in federated config"));
+    }
+
+    let mut backends = Vec::with_capacity(config.backends.len());
+    for backend_config in config.backends {
+        let search_backend = SearchBackend::from_str(backend_config.backend.as_str())?;
+        if search_backend == SearchBackend::Federated {
+            let msg = "Federated backend can not be nested";
+            return Err(anyhow!("{}: {}", msg, backend_config.name));
+        }
+
+        let mut builder = ServiceParametersBuilder::default();
+        builder
+            .search_backend(search_backend)
+            .service_addr(String::default())
+            .service_port(0)
+            .cors_origin(String::default())
+            .federated_config(None);
+
+        #[cfg(feature = "elastic-search")]
+        builder
+            .es_host(backend_config.es_host)
+            .es_user(backend_config.es_user)
+            .es_passwd(backend_config.es_passwd)
+            .es_auth(backend_config.es_auth)
+            .es_tls(backend_config.es_tls)
+            .es_sniff_interval(
+                backend_config
+                    .es_sniff_interval_secs
+                    .map(Duration::from_secs),
+            )
+            .es_match_config(backend_config.es_match_config);
+
+        #[cfg(any(
+            feature = "default-search",
+            feature = "tantivy-search",
+            feature = "sqlite-search"
+        ))]
+        builder.data_dir(backend_config.data_dir);
+
+        let parameters = builder.build()?;
+
+        let client = build_search_client(&parameters)?;
+        let timeout = Duration::from_millis(backend_config.timeout_ms);
+        backends.push(FederatedBackend::new(backend_config.name, timeout, client));
+    }
+
+    Ok(FederatedContext::_new(backends))
+}
@@ -1,3 +1,110 @@
+#[cfg(all(
+    test,
+    any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    )
+))]
+mod backend_tests;
+#[cfg(feature = "elastic-search")]
 pub mod elastic;
+pub mod federated;
+#[cfg(feature = "default-search")]
 pub mod own_engine;
 pub mod service_client;
+#[cfg(feature = "sqlite-search")]
+pub mod sqlite_engine;
+#[cfg(feature = "tantivy-search")]
+pub mod tantivy_engine;
+
+use crate::searcher::service_client::ServiceClient;
+use crate::service::{SearchBackend, ServiceParameters};
+
+use anyhow::anyhow;
+
+pub type SearchClientResult = Result<Box<dyn ServiceClient>, anyhow::Error>;
+
+/// Builds client of search backend selected by service configuration.
+/// Backends which are not enabled by cargo features are rejected.
+pub fn build_search_client(service_parameters: &ServiceParameters) -> SearchClientResult {
+    let search_backend = service_parameters.search_backend();
+    log::info!("Using {} search backend", search_backend);
+    match search_backend {
+        #[cfg(feature = "elastic-search")]
+        SearchBackend::Elastic => build_elastic_service(service_parameters),
+        #[cfg(feature = "default-search")]
+        SearchBackend::Embedded => build_own_service(service_parameters),
+        #[cfg(feature = "tantivy-search")]
+        SearchBackend::Tantivy => build_tantivy_service(service_parameters),
+        #[cfg(feature = "sqlite-search")]
+        SearchBackend::Sqlite => build_sqlite_service(service_parameters),
+        SearchBackend::Federated => build_federated_service(service_parameters),
+        #[allow(unreachable_patterns)]
+        backend => Err(anyhow!("{} backend is not enabled in this build", backend)),
+    }
+}
+
+fn build_federated_service(service_parameters: &ServiceParameters) -> SearchClientResult {
+    use crate::searcher::federated::build_federated_client;
+
+    let Some(config_path) = service_parameters.federated_config() else {
+        return Err(anyhow!("There is not FEDERATED_CONFIG env variable!"));
+    };
+
+    let client = build_federated_client(config_path)?;
+    Ok(Box::new(client))
+}
+
+#[cfg(feature = "elastic-search")]
+fn build_elastic_service(service_parameters: &ServiceParameters) -> SearchClientResult {
+    use crate::searcher::elastic::build_elastic_context;
+    use crate::searcher::elastic::transport::TransportConfig;
+
+    let es_host = service_parameters.es_host();
+    let es_user = service_parameters.es_user();
+    let es_passwd = service_parameters.es_passwd();
+    if es_host.is_empty() {
+        return Err(anyhow!("There is not ELASTIC_HOST env variable!"));
+    }
+
+    let es_auth = service_parameters.es_auth();
+    let es_tls = service_parameters.es_tls();
+    let transport = TransportConfig::load(es_user, es_passwd, es_auth, es_tls)?;
+    let sniff_interval = service_parameters.es_sniff_interval();
+    let mut context = build_elastic_context(es_host, transport, sniff_interval)?;
+    if let Some(config_path) = service_parameters.es_match_config() {
+        let config_data = std::fs::read(config_path)?;
+        let match_defaults = serde_json::from_slice(config_data.as_slice())?;
+        context = context.with_match_defaults(match_defaults);
+    }
+
+    Ok(Box::new(context))
+}
+
+#[cfg(feature = "default-search")]
+fn build_own_service(service_parameters: &ServiceParameters) -> SearchClientResult {
+    use crate::searcher::own_engine::build_own_client;
+
+    let data_dir = service_parameters.data_dir();
+    let client = build_own_client(data_dir).map_err(|err| anyhow!(err.to_string()))?;
+    Ok(Box::new(client))
+}
+
+#[cfg(feature = "tantivy-search")]
+fn build_tantivy_service(service_parameters: &ServiceParameters) -> SearchClientResult {
+    use crate::searcher::tantivy_engine::build_tantivy_client;
+
+    let data_dir = service_parameters.data_dir();
+    let client = build_tantivy_client(data_dir).map_err(|err| anyhow!(err.to_string()))?;
+    Ok(Box::new(client))
+}
+
+#[cfg(feature = "sqlite-search")]
+fn build_sqlite_service(service_parameters: &ServiceParameters) -> SearchClientResult {
+    use crate::searcher::sqlite_engine::build_sqlite_client;
+
+    let data_dir = service_parameters.data_dir();
+    let client = build_sqlite_client(data_dir).map_err(|err| anyhow!(err.to_string()))?;
+    Ok(Box::new(client))
+}
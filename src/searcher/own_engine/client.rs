@@ -1,20 +1,24 @@
-use crate::errors::{SuccessfulResponse, WebResponse};
+use crate::errors::{SuccessfulResponse, WebError, WebResponse};
 use crate::searcher::own_engine::context::OtherContext;
-use crate::searcher::service_client::ServiceClient;
+use crate::searcher::own_engine::helper::*;
+use crate::searcher::service_client::*;
 use crate::wrappers::bucket::{Bucket, BucketForm};
 use crate::wrappers::cluster::Cluster;
 use crate::wrappers::document::Document;
+use crate::wrappers::saved_search::{SavedSearch, SavedSearchForm};
 use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::{SuggestForm, Suggestion};
 
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpResponse, ResponseError};
 
 #[async_trait::async_trait]
 impl ServiceClient for OtherContext {
-    async fn get_all_clusters(&self) -> WebResponse<web::Json<Vec<Cluster>>> {
+    async fn get_all_clusters(&self) -> JsonResponse<Vec<Cluster>> {
         Ok(web::Json(Vec::default()))
     }
 
-    async fn get_cluster(&self, _cluster_id: &str) -> WebResponse<web::Json<Cluster>> {
+    async fn get_cluster(&self, _cluster_id: &str) -> JsonResponse<Cluster> {
         Ok(web::Json(Cluster::default()))
     }
 
@@ -26,79 +30,201 @@ impl ServiceClient for OtherContext {
         SuccessfulResponse::ok_response("Ok")
     }
 
-    async fn get_all_buckets(&self) -> WebResponse<web::Json<Vec<Bucket>>> {
-        Ok(web::Json(Vec::default()))
+    async fn get_all_buckets(&self) -> JsonResponse<Vec<Bucket>> {
+        let engine = self.get_cxt().read().await;
+        let buckets = engine.buckets().map(extract_bucket_stats).collect();
+        Ok(web::Json(buckets))
     }
 
-    async fn get_bucket(&self, _bucket_id: &str) -> WebResponse<web::Json<Bucket>> {
-        Ok(web::Json(Bucket::default()))
+    async fn get_bucket(&self, bucket_id: &str) -> JsonResponse<Bucket> {
+        let engine = self.get_cxt().read().await;
+        let bucket = engine.bucket(bucket_id)?;
+        Ok(web::Json(extract_bucket_stats(bucket)))
     }
 
-    async fn delete_bucket(&self, _bucket_id: &str) -> HttpResponse {
-        SuccessfulResponse::ok_response("Ok")
+    async fn delete_bucket(&self, bucket_id: &str) -> HttpResponse {
+        let mut engine = self.get_cxt().write().await;
+        match engine.delete_bucket(bucket_id) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
     }
 
-    async fn create_bucket(&self, _bucket_form: &BucketForm) -> HttpResponse {
-        SuccessfulResponse::ok_response("Ok")
+    async fn create_bucket(&self, bucket_form: &BucketForm) -> HttpResponse {
+        let mut engine = self.get_cxt().write().await;
+        match engine.create_bucket(bucket_form.get_name()) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
     }
 
-    async fn check_duplication(&self, _bucket_id: &str, _document_id: &str) -> bool {
-        false
+    #[cfg(feature = "elastic-search")]
+    async fn check_duplication(&self, bucket_id: &str, document_id: &str) -> bool {
+        let engine = self.get_cxt().read().await;
+        engine.contains_document(bucket_id, document_id)
     }
 
-    async fn get_document(
-        &self,
-        _bucket_id: &str,
-        _doc_id: &str,
-    ) -> WebResponse<web::Json<Document>> {
-        Ok(web::Json(Document::default()))
+    async fn get_document(&self, bucket_id: &str, doc_id: &str) -> JsonResponse<Document> {
+        let engine = self.get_cxt().read().await;
+        let document = engine.get_document(bucket_id, doc_id)?;
+        Ok(web::Json(document))
     }
 
-    async fn create_document(&self, _doc_form: &Document) -> HttpResponse {
-        SuccessfulResponse::ok_response("Ok")
-    }
+    async fn create_document(&self, doc_form: &Document) -> HttpResponse {
+        let bucket_name = &doc_form.bucket_uuid;
+        let document_id = &doc_form.document_md5_hash;
+        let mut engine = self.get_cxt().write().await;
+        if engine.contains_document(bucket_name.as_str(), document_id.as_str()) {
+            let msg = format!("Passed document: {} already exists", document_id);
+            return WebError::CreateDocument(msg).error_response();
+        }
 
-    async fn update_document(&self, _doc_form: &Document) -> HttpResponse {
-        SuccessfulResponse::ok_response("Ok")
+        match engine.index_documents(vec![doc_form.clone()]) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
     }
 
-    async fn delete_document(&self, _bucket_id: &str, _doc_id: &str) -> HttpResponse {
-        SuccessfulResponse::ok_response("Ok")
+    async fn update_document(&self, doc_form: &Document) -> HttpResponse {
+        let mut engine = self.get_cxt().write().await;
+        match engine.index_documents(vec![doc_form.clone()]) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
     }
 
-    async fn load_file_to_bucket(&self, _bucket_id: &str, _file_path: &str) -> HttpResponse {
-        SuccessfulResponse::ok_response("Ok")
+    async fn delete_document(&self, bucket_id: &str, doc_id: &str) -> HttpResponse {
+        let mut engine = self.get_cxt().write().await;
+        match engine.delete_document(bucket_id, doc_id) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
     }
 
-    // async fn upload_file_to_all(&self, _part: Multipart) -> HttpResponse {
-    //     SuccessfulResponse::ok_response("Ok")
-    // }
-    //
-    // async fn upload_file_to_bucket(&self, _bucket_id: &str, _part: Multipart) -> HttpResponse {
-    //     SuccessfulResponse::ok_response("Ok")
-    // }
+    async fn load_file_to_bucket(&self, bucket_id: &str, file_path: &str) -> HttpResponse {
+        let file_path_ = std::path::Path::new(file_path);
+        if !file_path_.exists() {
+            let err = WebError::LoadFileFailed(file_path.to_string());
+            return err.error_response();
+        }
 
-    async fn search_all(&self, _s_params: &SearchParams) -> WebResponse<web::Json<Vec<Document>>> {
-        Ok(web::Json(Vec::default()))
+        let documents = load_directory_entity(file_path_, bucket_id);
+        let mut engine = self.get_cxt().write().await;
+        match engine.index_documents(documents) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
+    }
+
+    async fn search_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse> {
+        check_offset_pagination(s_params)?;
+        let engine = self.get_cxt().read().await;
+        Ok(web::Json(engine.search(None, s_params)))
     }
 
     async fn search_bucket(
         &self,
-        _bucket_id: &str,
-        _s_params: &SearchParams,
-    ) -> WebResponse<web::Json<Vec<Document>>> {
-        Ok(web::Json(Vec::default()))
+        buckets_ids: &str,
+        s_params: &SearchParams,
+    ) -> JsonResponse<SearchResponse> {
+        check_offset_pagination(s_params)?;
+        let engine = self.get_cxt().read().await;
+        let indexes: Vec<&str> = buckets_ids.split(',').collect();
+        Ok(web::Json(engine.search(Some(indexes.as_slice()), s_params)))
     }
 
-    async fn similar_all(&self, _s_params: &SearchParams) -> WebResponse<web::Json<Vec<Document>>> {
-        Ok(web::Json(Vec::default()))
+    async fn close_cursor(&self, _cursor: &str) -> HttpResponse {
+        cursor_not_supported().error_response()
+    }
+
+    async fn suggest(
+        &self,
+        buckets_ids: Option<&str>,
+        form: &SuggestForm,
+    ) -> JsonResponse<Vec<Suggestion>> {
+        let engine = self.get_cxt().read().await;
+        let indexes = buckets_ids.map(|ids| ids.split(',').collect::<Vec<_>>());
+        Ok(web::Json(engine.suggest(indexes.as_deref(), form)))
+    }
+
+    async fn export_documents(
+        &self,
+        buckets_ids: Option<&str>,
+        s_params: &SearchParams,
+    ) -> WebResponse<DocumentStream> {
+        let cxt = self.get_cxt().clone();
+        let buckets_ids =
+            buckets_ids.map(|ids| ids.split(',').map(String::from).collect::<Vec<_>>());
+        let documents = export_by_offset(s_params, move |s_params| {
+            let cxt = cxt.clone();
+            let buckets_ids = buckets_ids.clone();
+            async move {
+                let engine = cxt.read().await;
+                let indexes = buckets_ids
+                    .as_ref()
+                    .map(|ids| ids.iter().map(String::as_str).collect::<Vec<_>>());
+                Ok(engine.search(indexes.as_deref(), &s_params))
+            }
+        });
+
+        Ok(documents)
+    }
+
+    async fn similar_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse> {
+        let engine = self.get_cxt().read().await;
+        let search_response = engine.similar(None, s_params);
+        Ok(web::Json(search_response))
     }
 
     async fn similar_bucket(
         &self,
-        _bucket_id: &str,
-        _s_params: &SearchParams,
-    ) -> WebResponse<web::Json<Vec<Document>>> {
-        Ok(web::Json(Vec::default()))
+        buckets_ids: &str,
+        s_params: &SearchParams,
+    ) -> JsonResponse<SearchResponse> {
+        let engine = self.get_cxt().read().await;
+        let indexes: Vec<&str> = buckets_ids.split(',').collect();
+        let search_response = engine.similar(Some(indexes.as_slice()), s_params);
+        Ok(web::Json(search_response))
+    }
+
+    async fn get_all_saved_searches(&self) -> JsonResponse<Vec<SavedSearch>> {
+        let engine = self.get_cxt().read().await;
+        Ok(web::Json(engine.saved_searches().all()))
+    }
+
+    async fn get_saved_search(&self, search_id: &str) -> JsonResponse<SavedSearch> {
+        let engine = self.get_cxt().read().await;
+        let search = engine.saved_searches().get(search_id)?;
+        Ok(web::Json(search.clone()))
+    }
+
+    async fn create_saved_search(&self, form: &SavedSearchForm) -> JsonResponse<SavedSearch> {
+        let mut engine = self.get_cxt().write().await;
+        let search = engine.saved_searches().created(form)?;
+        Ok(web::Json(engine.save_search(search)?))
+    }
+
+    async fn update_saved_search(
+        &self,
+        search_id: &str,
+        form: &SavedSearchForm,
+    ) -> JsonResponse<SavedSearch> {
+        let mut engine = self.get_cxt().write().await;
+        let search = engine.saved_searches().updated(search_id, form)?;
+        Ok(web::Json(engine.save_search(search)?))
+    }
+
+    async fn delete_saved_search(&self, search_id: &str) -> HttpResponse {
+        let mut engine = self.get_cxt().write().await;
+        match engine.delete_saved_search(search_id) {
+            Ok(_) => SuccessfulResponse::ok_response("Ok"),
+            Err(err) => err.error_response(),
+        }
+    }
+
+    async fn mark_saved_search_run(&self, search_id: &str) -> JsonResponse<SavedSearch> {
+        let mut engine = self.get_cxt().write().await;
+        let search = engine.saved_searches().ran(search_id)?;
+        Ok(web::Json(engine.save_search(search)?))
     }
 }
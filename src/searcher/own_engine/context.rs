@@ -1,18 +1,20 @@
+use crate::searcher::own_engine::engine::SearchEngine;
+
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 #[derive(Default, Clone)]
 pub struct OtherContext {
-    context: Arc<RwLock<String>>,
+    context: Arc<RwLock<SearchEngine>>,
 }
 
 impl OtherContext {
-    pub fn _new(elastic: String) -> Self {
-        let elastic = Arc::new(RwLock::new(elastic));
-        OtherContext { context: elastic }
+    pub fn _new(engine: SearchEngine) -> Self {
+        let context = Arc::new(RwLock::new(engine));
+        OtherContext { context }
     }
 
-    pub fn get_cxt(&self) -> &Arc<RwLock<String>> {
+    pub fn get_cxt(&self) -> &Arc<RwLock<SearchEngine>> {
         &self.context
     }
 }
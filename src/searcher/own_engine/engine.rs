@@ -0,0 +1,524 @@
+use crate::errors::WebError;
+use crate::searcher::own_engine::helper::*;
+use crate::searcher::own_engine::index::{field_value, BucketIndex};
+use crate::searcher::own_engine::storage::{Operation, Storage, StorageResult};
+use crate::searcher::own_engine::tokenizer::tokenize_terms;
+use crate::wrappers::aggregation::{aggregate_documents, Aggregations};
+use crate::wrappers::document::Document;
+use crate::wrappers::highlight::highlight_document;
+use crate::wrappers::pattern::TextFilter;
+use crate::wrappers::saved_search::*;
+use crate::wrappers::search_params::{MatchOptions, SearchParams};
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::*;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+const SEARCH_FIELDS: [&str; 2] = ["entity_data", "document_path"];
+const SIMILAR_FIELDS: [&str; 1] = ["entity_data"];
+const MAX_QUERY_TERMS: usize = 25;
+
+#[derive(Default)]
+pub struct SearchEngine {
+    buckets: HashMap<String, BucketIndex>,
+    saved_searches: SavedSearches,
+    storage: Option<Storage>,
+}
+
+impl SearchEngine {
+    pub fn new() -> Self {
+        SearchEngine::default()
+    }
+
+    /// Opens engine persisted in passed directory and restores index state
+    /// from segments and write-ahead log.
+    pub fn open(data_dir: &Path) -> StorageResult<Self> {
+        let (storage, operations) = Storage::open(data_dir)?;
+        let mut engine = SearchEngine {
+            storage: Some(storage),
+            ..Default::default()
+        };
+
+        for operation in operations {
+            engine.apply(operation);
+        }
+
+        Ok(engine)
+    }
+
+    pub fn storage(&self) -> Option<&Storage> {
+        self.storage.as_ref()
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = &BucketIndex> {
+        self.buckets.values()
+    }
+
+    pub fn bucket(&self, bucket_id: &str) -> Result<&BucketIndex, WebError> {
+        match self.buckets.get(bucket_id) {
+            Some(bucket) => Ok(bucket),
+            None => Err(WebError::GetBucket(format!(
+                "no such bucket: {}",
+                bucket_id
+            ))),
+        }
+    }
+
+    pub fn create_bucket(&mut self, bucket_id: &str) -> Result<(), WebError> {
+        if self.buckets.contains_key(bucket_id) {
+            let msg = format!("bucket {} already exists", bucket_id);
+            return Err(WebError::CreateBucket(msg));
+        }
+
+        let bucket_id = bucket_id.to_string();
+        self.log_and_apply(vec![Operation::CreateBucket { bucket_id }])
+    }
+
+    pub fn delete_bucket(&mut self, bucket_id: &str) -> Result<(), WebError> {
+        if !self.buckets.contains_key(bucket_id) {
+            let msg = format!("no such bucket: {}", bucket_id);
+            return Err(WebError::DeleteBucket(msg));
+        }
+
+        let bucket_id = bucket_id.to_string();
+        self.log_and_apply(vec![Operation::DeleteBucket { bucket_id }])
+    }
+
+    pub fn get_document(&self, bucket_id: &str, doc_id: &str) -> Result<Document, WebError> {
+        let document = self
+            .buckets
+            .get(bucket_id)
+            .and_then(|bucket| bucket.get(doc_id));
+
+        match document {
+            Some(document) => Ok(document.clone()),
+            None => Err(WebError::GetDocument(format!(
+                "no such document: {}",
+                doc_id
+            ))),
+        }
+    }
+
+    pub fn contains_document(&self, bucket_id: &str, doc_id: &str) -> bool {
+        self.buckets
+            .get(bucket_id)
+            .map(|bucket| bucket.contains(doc_id))
+            .unwrap_or(false)
+    }
+
+    /// Stores passed documents to the buckets with names from `bucket_uuid`
+    /// field and replaces existing ones with the same id. The bucket is
+    /// created if it does not exist, like elastic creates an index on first
+    /// indexing.
+    pub fn index_documents(&mut self, documents: Vec<Document>) -> Result<(), WebError> {
+        let operations = documents
+            .into_iter()
+            .map(|document| Operation::Index {
+                document: Box::new(document),
+            })
+            .collect();
+
+        self.log_and_apply(operations)
+    }
+
+    pub fn delete_document(&mut self, bucket_id: &str, doc_id: &str) -> Result<(), WebError> {
+        if !self.contains_document(bucket_id, doc_id) {
+            let msg = format!("no such document: {}", doc_id);
+            return Err(WebError::DeleteDocument(msg));
+        }
+
+        let bucket_id = bucket_id.to_string();
+        let doc_id = doc_id.to_string();
+        self.log_and_apply(vec![Operation::Delete { bucket_id, doc_id }])
+    }
+
+    pub fn saved_searches(&self) -> &SavedSearches {
+        &self.saved_searches
+    }
+
+    /// Stores new or changed saved search which replaces the one with the
+    /// same id.
+    pub fn save_search(&mut self, search: SavedSearch) -> Result<SavedSearch, WebError> {
+        let operation = Operation::SaveSearch {
+            search: Box::new(search.clone()),
+        };
+
+        self.log_and_apply(vec![operation])?;
+        Ok(search)
+    }
+
+    pub fn delete_saved_search(&mut self, id_or_name: &str) -> Result<(), WebError> {
+        let search_id = self.saved_searches.get(id_or_name)?.id.clone();
+        self.log_and_apply(vec![Operation::DeleteSearch { search_id }])
+    }
+
+    fn log_and_apply(&mut self, operations: Vec<Operation>) -> Result<(), WebError> {
+        if let Some(storage) = self.storage.as_ref() {
+            storage.log(&operations)?;
+        }
+
+        for operation in operations {
+            self.apply(operation);
+        }
+
+        Ok(())
+    }
+
+    fn apply(&mut self, operation: Operation) {
+        match operation {
+            Operation::CreateBucket { bucket_id } => {
+                self.buckets
+                    .entry(bucket_id.clone())
+                    .or_insert_with(|| BucketIndex::new(bucket_id.as_str()));
+            }
+            Operation::DeleteBucket { bucket_id } => {
+                self.buckets.remove(&bucket_id);
+            }
+            Operation::Index { document } => {
+                let bucket_id = document.bucket_uuid.clone();
+                self.buckets
+                    .entry(bucket_id.clone())
+                    .or_insert_with(|| BucketIndex::new(bucket_id.as_str()))
+                    .insert(*document);
+            }
+            Operation::Delete { bucket_id, doc_id } => {
+                if let Some(bucket) = self.buckets.get_mut(&bucket_id) {
+                    bucket.remove(&doc_id);
+                }
+            }
+            Operation::SaveSearch { search } => {
+                self.saved_searches.insert(*search);
+            }
+            Operation::DeleteSearch { search_id } => {
+                self.saved_searches.remove(&search_id);
+            }
+        }
+    }
+
+    /// Searches documents and counts all matched documents by facets of
+    /// parameters, so documents are scored once for hits and aggregations.
+    pub fn search(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> SearchResponse {
+        let started = Instant::now();
+        let query = TextQuery::parse(parameters.query.as_str());
+        let options = &parameters.match_options;
+        let highlight_terms = self
+            .select_buckets(buckets_ids)
+            .iter()
+            .flat_map(|bucket| bucket.matched_terms(&query, &SEARCH_FIELDS, options))
+            .collect::<HashSet<_>>();
+
+        let text_filter = TextFilter::new(parameters);
+        let scored = self.score_documents(
+            buckets_ids,
+            &query,
+            &SEARCH_FIELDS,
+            options,
+            &text_filter,
+            parameters,
+        );
+        let aggregations = match parameters.aggregations.is_empty() {
+            true => Aggregations::default(),
+            false => aggregate_documents(
+                &parameters.aggregations,
+                parameters.date_interval,
+                scored.iter().map(|(_, document)| *document),
+            ),
+        };
+
+        let mut search_response = SearchResponse::paginate(scored.len() as u64, scored, parameters);
+        search_response.aggregations = aggregations;
+        search_response.timed_out = text_filter.is_timed_out();
+        for hit in search_response.hits.iter_mut() {
+            let highlight =
+                highlight_document(&hit.document, &highlight_terms, &parameters.highlight);
+            hit.document.append_highlight(highlight);
+        }
+
+        let query = parameters.query.as_str();
+        if is_low_result(search_response.total, query) {
+            search_response.did_you_mean = self.suggest_corrections(buckets_ids, query);
+        }
+
+        search_response.took = started.elapsed().as_millis() as u64;
+        search_response
+    }
+
+    /// Suggests query where words of documents text which are missing or
+    /// rare are replaced by close frequent terms of indexed documents.
+    fn suggest_corrections(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        query: &str,
+    ) -> Vec<QuerySuggestion> {
+        let buckets = self.select_buckets(buckets_ids);
+        let suggestion = correct_query(query, |word| {
+            let word_count = buckets
+                .iter()
+                .map(|bucket| bucket.term_count(word))
+                .sum::<usize>();
+            let candidates = buckets
+                .iter()
+                .flat_map(|bucket| bucket.similar_terms(word, max_edits(word)))
+                .map(|(term, count)| (term, count as u64));
+            best_correction(word, word_count as u64, candidates)
+        });
+
+        suggestion.into_iter().collect()
+    }
+
+    /// Searches documents like the passed text as elastic `more_like_this`
+    /// query does: the most significant terms of text are selected and
+    /// documents containing any of them are returned.
+    pub fn similar(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> SearchResponse {
+        let started = Instant::now();
+        let like = parameters.query.as_str();
+        let buckets = self.select_buckets(buckets_ids);
+        let mut terms = tokenize_terms(like);
+        terms.sort();
+        terms.dedup();
+
+        let significance = |term: &String| -> f64 {
+            buckets
+                .iter()
+                .filter_map(|bucket| {
+                    let field = bucket.field("entity_data")?;
+                    let doc_freq = field.doc_freq(term);
+                    match doc_freq {
+                        0 => None,
+                        _ => Some((bucket.docs_count() as f64 / doc_freq as f64).ln() + 1.0),
+                    }
+                })
+                .fold(0.0, f64::max)
+        };
+
+        let mut weighted = terms
+            .into_iter()
+            .map(|term| (significance(&term), term))
+            .filter(|(weight, _)| *weight > 0.0)
+            .collect::<Vec<_>>();
+
+        weighted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+        let terms = weighted
+            .into_iter()
+            .take(MAX_QUERY_TERMS)
+            .map(|(_, term)| term)
+            .collect::<Vec<_>>();
+
+        let query = TextQuery::from_terms(terms);
+        let options = MatchOptions::default();
+        let mut scored = match query.is_empty() {
+            true => Vec::default(),
+            false => {
+                let fields = &SIMILAR_FIELDS;
+                let text_filter = TextFilter::default();
+                self.score_documents(
+                    buckets_ids,
+                    &query,
+                    fields,
+                    &options,
+                    &text_filter,
+                    parameters,
+                )
+            }
+        };
+
+        let doc_filter = DocumentFilter::new(parameters);
+        let same_hash = buckets
+            .iter()
+            .flat_map(|bucket| bucket.documents())
+            .filter(|(_, doc)| field_value(doc, "document_ssdeep_hash") == like)
+            .filter(|(_, doc)| doc_filter.matches(doc))
+            .map(|(_, doc)| doc)
+            .collect::<Vec<_>>();
+
+        scored.retain(|(_, doc)| !same_hash.iter().any(|same| std::ptr::eq(*same, *doc)));
+        scored.extend(same_hash.into_iter().map(|doc| (f64::INFINITY, doc)));
+        scored.sort_by(|a, b| parameters.compare_documents(*a, *b));
+        let mut search_response = SearchResponse::paginate(scored.len() as u64, scored, parameters);
+        search_response.took = started.elapsed().as_millis() as u64;
+        search_response
+    }
+
+    /// Completes prefix by names of documents and terms of documents text
+    /// which are stored by prefix trees of buckets.
+    pub fn suggest(&self, buckets_ids: Option<&[&str]>, form: &SuggestForm) -> Vec<Suggestion> {
+        let name_prefix = form.name_prefix();
+        let term_prefix = form.term_prefix();
+        let mut suggestions = Vec::new();
+        for bucket in self.select_buckets(buckets_ids) {
+            let names = bucket.complete_names(&name_prefix, form.size);
+            suggestions.extend(names.into_iter().map(|(text, count)| {
+                Suggestion::new(text, SuggestField::DocumentName, count as u64)
+            }));
+
+            if let Some(term_prefix) = term_prefix.as_deref() {
+                let terms = bucket.complete_terms(term_prefix, form.size);
+                suggestions.extend(terms.into_iter().map(|(text, count)| {
+                    Suggestion::new(text, SuggestField::EntityData, count as u64)
+                }));
+            }
+        }
+
+        merge_suggestions(suggestions, form.size)
+    }
+
+    fn select_buckets(&self, buckets_ids: Option<&[&str]>) -> Vec<&BucketIndex> {
+        match buckets_ids {
+            None => self.buckets.values().collect(),
+            Some(ids) => ids.iter().filter_map(|id| self.buckets.get(*id)).collect(),
+        }
+    }
+
+    /// Scores documents matched by query and filters. Text of documents is
+    /// checked by text filter after other filters as the most expensive.
+    fn score_documents(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        query: &TextQuery,
+        fields: &[&str],
+        options: &MatchOptions,
+        text_filter: &TextFilter,
+        parameters: &SearchParams,
+    ) -> Vec<(f64, &Document)> {
+        let mut scored = Vec::new();
+        let doc_filter = DocumentFilter::new(parameters);
+        let matches = |doc: &Document| doc_filter.matches(doc) && text_filter.matches(doc);
+
+        for bucket in self.select_buckets(buckets_ids) {
+            if query.is_empty() {
+                let all_docs = bucket
+                    .documents()
+                    .filter(|(_, doc)| matches(doc))
+                    .map(|(_, doc)| (1.0, doc));
+                scored.extend(all_docs);
+                continue;
+            }
+
+            let matched = bucket
+                .score_query(query, fields, options)
+                .into_iter()
+                .filter_map(|(doc_id, score)| bucket.get(doc_id.as_str()).map(|doc| (score, doc)))
+                .filter(|(_, doc)| matches(doc));
+            scored.extend(matched);
+        }
+
+        scored.sort_by(|a, b| parameters.compare_documents(*a, *b));
+        scored
+    }
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use crate::searcher::backend_tests::{build_document, build_documents, search_params};
+    use crate::wrappers::proximity::ProximityQuery;
+
+    fn build_engine() -> SearchEngine {
+        let mut engine = SearchEngine::new();
+        engine.index_documents(build_documents()).unwrap();
+        engine
+    }
+
+    #[test]
+    fn search_in_unknown_bucket() {
+        let engine = build_engine();
+        let founded = engine
+            .search(Some(&["other_bucket"]), &search_params("tenant"))
+            .hits;
+        assert!(founded.is_empty());
+    }
+
+    #[test]
+    fn search_fuzzy_phrases_and_proximity() {
+        let engine = build_engine();
+        assert!(engine
+            .search(None, &search_params("tenamt"))
+            .hits
+            .is_empty());
+
+        let mut params = search_params("tenamt");
+        params.match_options.fuzziness = Some("AUTO".to_string());
+        let search_response = engine.search(None, &params);
+        assert_eq!(search_response.total, 2);
+        let highlight = search_response.hits[0].highlight.as_ref().unwrap();
+        assert!(highlight.entity_data[0].contains("<em>Tenant</em>"));
+
+        let founded = engine.search(None, &search_params("\"lease fee\"")).hits;
+        assert_eq!(founded.len(), 1);
+
+        let mut params = search_params("\"lease paid\"");
+        assert!(engine.search(None, &params).hits.is_empty());
+        params.match_options.slop = Some(3);
+        assert_eq!(engine.search(None, &params).hits.len(), 1);
+
+        let mut params = search_params("*");
+        params.proximity = vec![ProximityQuery {
+            terms: vec!["lease".to_string(), "tenant".to_string()],
+            within: 6,
+            ordered: true,
+        }];
+        assert_eq!(engine.search(None, &params).total, 1);
+        params.proximity[0].within = 5;
+        assert_eq!(engine.search(None, &params).total, 0);
+    }
+
+    #[test]
+    fn restore_persisted_engine() {
+        let data_dir = std::env::temp_dir().join("searcher_engine_restore");
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        let mut engine = SearchEngine::open(&data_dir).unwrap();
+        engine.create_bucket("empty_bucket").unwrap();
+        let document = build_document("test_bucket", "first", 1024, "Persisted agreement");
+        engine.index_documents(vec![document]).unwrap();
+        drop(engine);
+
+        let engine = SearchEngine::open(&data_dir).unwrap();
+        assert!(engine.bucket("empty_bucket").is_ok());
+        assert_eq!(
+            engine.search(None, &search_params("agreement")).hits.len(),
+            1
+        );
+    }
+
+    #[test]
+    fn restore_saved_searches() {
+        let data_dir = std::env::temp_dir().join("searcher_engine_saved_searches");
+        let _ = std::fs::remove_dir_all(&data_dir);
+
+        let mut engine = SearchEngine::open(&data_dir).unwrap();
+        let mut form = SavedSearchForm {
+            name: "agreements".to_string(),
+            owner: "analyst".to_string(),
+            buckets: Some("test_bucket".to_string()),
+            params: search_params("agreement"),
+        };
+        let search = engine.saved_searches().created(&form).unwrap();
+        let search = engine.save_search(search).unwrap();
+        form.name = "leases".to_string();
+        let other = engine.saved_searches().created(&form).unwrap();
+        engine.save_search(other).unwrap();
+        let ran = engine.saved_searches().ran("agreements").unwrap();
+        engine.save_search(ran).unwrap();
+        engine.delete_saved_search("leases").unwrap();
+        drop(engine);
+
+        let engine = SearchEngine::open(&data_dir).unwrap();
+        let searches = engine.saved_searches().all();
+        assert_eq!(searches.len(), 1);
+        assert_eq!(searches[0].id, search.id);
+        assert!(searches[0].last_run.is_some());
+        assert_eq!(searches[0].params.query, "agreement");
+    }
+}
@@ -0,0 +1,227 @@
+use crate::searcher::own_engine::index::BucketIndex;
+use crate::searcher::own_engine::tokenizer::tokenize_terms;
+use crate::wrappers::bucket::{Bucket, BucketBuilder};
+use crate::wrappers::document::Document;
+use crate::wrappers::search_params::SearchParams;
+
+use chrono::{DateTime, Utc};
+use datetime::{parse_datetime, parse_datetime_end};
+use hasher::{gen_hash, HashType};
+use regex::Regex;
+
+use std::path::Path;
+
+pub fn is_match_all(query: &str) -> bool {
+    let query = query.trim();
+    query.is_empty() || query == "*"
+}
+
+/// Words and quoted phrases of full-text query, match-all query has none.
+#[derive(Debug, Default, PartialEq)]
+pub struct TextQuery {
+    pub words: Vec<String>,
+    pub phrases: Vec<Vec<String>>,
+}
+
+impl TextQuery {
+    /// Parses query where phrases are enclosed in double quotes, phrase of
+    /// the single word is matched as word.
+    pub fn parse(query: &str) -> Self {
+        let mut text_query = TextQuery::default();
+        if is_match_all(query) {
+            return text_query;
+        }
+
+        for (index, part) in query.split('"').enumerate() {
+            let terms = tokenize_terms(part);
+            match index % 2 == 1 && terms.len() > 1 {
+                true => text_query.phrases.push(terms),
+                false => text_query.words.extend(terms),
+            }
+        }
+
+        text_query
+    }
+
+    pub fn from_terms(terms: Vec<String>) -> Self {
+        TextQuery {
+            words: terms,
+            phrases: Vec::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.phrases.is_empty()
+    }
+
+    /// Returns all words of query including words of phrases.
+    pub fn terms(&self) -> impl Iterator<Item = String> + '_ {
+        self.words
+            .iter()
+            .chain(self.phrases.iter().flatten())
+            .cloned()
+    }
+}
+
+/// Filter of documents by search parameters. Extension groups and path
+/// globs are prepared once and reused for every checked document.
+pub struct DocumentFilter<'a> {
+    parameters: &'a SearchParams,
+    any_extensions: Vec<String>,
+    exclude_extensions: Vec<String>,
+    document_path: Option<Regex>,
+    bucket_path: Option<Regex>,
+}
+
+impl<'a> DocumentFilter<'a> {
+    pub fn new(parameters: &'a SearchParams) -> Self {
+        let compile = |regex: Option<String>| {
+            regex.map(|regex| {
+                // Invalid pattern must not match anything instead of being ignored.
+                Regex::new(&format!("^(?:{})$", regex))
+                    .unwrap_or_else(|_| Regex::new("[^\\s\\S]").unwrap())
+            })
+        };
+
+        DocumentFilter {
+            parameters,
+            any_extensions: parameters.any_extensions(),
+            exclude_extensions: parameters.exclude_extensions(),
+            document_path: compile(parameters.document_path_regex()),
+            bucket_path: compile(parameters.bucket_path_regex()),
+        }
+    }
+
+    pub fn matches(&self, document: &Document) -> bool {
+        let parameters = self.parameters;
+        let doc_size = document.document_size as i64;
+        if doc_size < parameters.document_size_from {
+            return false;
+        }
+
+        if parameters.document_size_to > 0 && doc_size > parameters.document_size_to {
+            return false;
+        }
+
+        let doc_ext = parameters.document_extension();
+        if !doc_ext.is_empty() && !document.document_extension.eq_ignore_ascii_case(&doc_ext) {
+            return false;
+        }
+
+        let doc_type = parameters.document_type.as_str();
+        if !doc_type.is_empty() && !document.document_type.eq_ignore_ascii_case(doc_type) {
+            return false;
+        }
+
+        let any_of = |values: &[String], value: &str| {
+            values.is_empty() || values.iter().any(|it| it.eq_ignore_ascii_case(value))
+        };
+        let none_of = |values: &[String], value: &str| {
+            !values.iter().any(|it| it.eq_ignore_ascii_case(value))
+        };
+
+        if !any_of(&self.any_extensions, &document.document_extension)
+            || !any_of(&parameters.any_types, &document.document_type)
+            || !none_of(&self.exclude_extensions, &document.document_extension)
+            || !none_of(&parameters.exclude_types, &document.document_type)
+        {
+            return false;
+        }
+
+        let doc_path = document.document_path.as_str();
+        let is_path_prefixed = parameters.path_prefixes.is_empty()
+            || parameters
+                .path_prefixes
+                .iter()
+                .any(|prefix| doc_path.starts_with(prefix.as_str()));
+        if !is_path_prefixed {
+            return false;
+        }
+
+        let path_globs = [
+            (doc_path, self.document_path.as_ref()),
+            (document.bucket_path.as_str(), self.bucket_path.as_ref()),
+        ];
+        for (path, regex) in path_globs {
+            if regex.map(|regex| !regex.is_match(path)).unwrap_or(false) {
+                return false;
+            }
+        }
+
+        let excluded_path = parameters
+            .exclude_paths
+            .iter()
+            .any(|prefix| !prefix.is_empty() && doc_path.starts_with(prefix.as_str()));
+        if excluded_path {
+            return false;
+        }
+
+        let doc_created = document.document_created;
+        let doc_cr_from = parameters.created_date_from.as_str();
+        let doc_cr_to = parameters.created_date_to.as_str();
+        if !is_in_date_range(doc_created, doc_cr_from, doc_cr_to) {
+            return false;
+        }
+
+        let doc_modified = document.document_modified;
+        let doc_md_from = parameters.modified_date_from.as_str();
+        let doc_md_to = parameters.modified_date_to.as_str();
+        is_in_date_range(doc_modified, doc_md_from, doc_md_to)
+    }
+}
+
+/// Checks date by range which may be open-ended on either side. Documents
+/// without date never match bounded range. Upper bound without time includes
+/// the whole day.
+fn is_in_date_range(value: Option<DateTime<Utc>>, gte: &str, lte: &str) -> bool {
+    if gte.is_empty() && lte.is_empty() {
+        return true;
+    }
+
+    let Some(value) = value else {
+        return false;
+    };
+
+    let is_before = parse_datetime(gte).map(|gte| value < gte).unwrap_or(false);
+    let is_after = parse_datetime_end(lte)
+        .map(|lte| value > lte)
+        .unwrap_or(false);
+    !is_before && !is_after
+}
+
+pub fn extract_bucket_stats(bucket: &BucketIndex) -> Bucket {
+    let bucket_name = bucket.name();
+    let store_size: usize = bucket
+        .documents()
+        .map(|(_, document)| document.entity_data.len())
+        .sum();
+
+    let uuid = gen_hash(HashType::MD5, bucket_name.as_bytes())
+        .map(|hashed| hashed.get_hash_data().to_string())
+        .unwrap_or_default();
+
+    BucketBuilder::default()
+        .health("green".to_string())
+        .status("open".to_string())
+        .index(bucket_name.to_string())
+        .uuid(uuid)
+        .docs_count(bucket.docs_count().to_string())
+        .docs_deleted("0".to_string())
+        .store_size(store_size.to_string())
+        .pri_store_size(store_size.to_string())
+        .pri(None)
+        .rep(None)
+        .build()
+        .unwrap()
+}
+
+pub fn load_directory_entity(directory: &Path, bucket_id: &str) -> Vec<Document> {
+    file_loader::load_directory_entity(directory)
+        .into_iter()
+        .map(Document::from)
+        .map(|mut document| {
+            document.bucket_uuid = bucket_id.to_string();
+            document
+        })
+        .collect()
+}
@@ -0,0 +1,309 @@
+use crate::searcher::own_engine::helper::TextQuery;
+use crate::searcher::own_engine::tokenizer::{tokenize, tokenize_terms};
+use crate::searcher::own_engine::trie::PrefixTrie;
+use crate::wrappers::document::Document;
+use crate::wrappers::proximity::min_gaps;
+use crate::wrappers::search_params::MatchOptions;
+use crate::wrappers::suggest::edit_distance;
+
+use std::collections::{HashMap, HashSet};
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+pub const INDEXED_FIELDS: [&str; 3] = ["entity_data", "document_path", "document_name"];
+
+pub fn field_value<'a>(document: &'a Document, field: &str) -> &'a str {
+    match field {
+        "entity_data" => document.entity_data.as_str(),
+        "document_path" => document.document_path.as_str(),
+        "document_name" => document.document_name.as_str(),
+        "document_ssdeep_hash" => document.document_ssdeep_hash.as_str(),
+        _ => "",
+    }
+}
+
+#[derive(Default)]
+pub struct FieldIndex {
+    postings: HashMap<String, HashMap<String, Vec<u32>>>,
+    lengths: HashMap<String, usize>,
+    total_length: usize,
+}
+
+impl FieldIndex {
+    fn insert(&mut self, doc_id: &str, text: &str) {
+        let tokens = tokenize(text);
+        self.total_length += tokens.len();
+        self.lengths.insert(doc_id.to_string(), tokens.len());
+        for token in tokens {
+            self.postings
+                .entry(token.term)
+                .or_default()
+                .entry(doc_id.to_string())
+                .or_default()
+                .push(token.position);
+        }
+    }
+
+    fn remove(&mut self, doc_id: &str, text: &str) {
+        let length = self.lengths.remove(doc_id).unwrap_or(0);
+        self.total_length -= length;
+        for token in tokenize(text) {
+            if let Some(docs) = self.postings.get_mut(&token.term) {
+                docs.remove(doc_id);
+                if docs.is_empty() {
+                    self.postings.remove(&token.term);
+                }
+            }
+        }
+    }
+
+    pub fn postings(&self, term: &str) -> Option<&HashMap<String, Vec<u32>>> {
+        self.postings.get(term)
+    }
+
+    /// Returns indexed terms which differ from the word by up to passed
+    /// count of edits, the word itself is included if it is indexed.
+    pub fn fuzzy_terms<'a>(&'a self, word: &'a str, max_edits: usize) -> Vec<&'a str> {
+        if max_edits == 0 {
+            return match self.postings.contains_key(word) {
+                true => vec![word],
+                false => Vec::default(),
+            };
+        }
+
+        let word_length = word.chars().count();
+        self.postings
+            .keys()
+            .filter(|term| term.chars().count().abs_diff(word_length) <= max_edits)
+            .filter(|term| edit_distance(term, word) <= max_edits)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Scores documents where words of phrase follow each other with up to
+    /// `slop` other words between them by sum of words scores.
+    fn score_phrase(&self, phrase: &[String], slop: u32, docs_count: usize) -> HashMap<&str, f64> {
+        let Some(first_docs) = phrase.first().and_then(|word| self.postings(word)) else {
+            return HashMap::default();
+        };
+
+        let mut scores = HashMap::new();
+        for doc_id in first_docs.keys() {
+            let occurrences = phrase
+                .iter()
+                .map(|word| {
+                    let positions = self.postings(word).and_then(|docs| docs.get(doc_id));
+                    positions
+                        .into_iter()
+                        .flatten()
+                        .map(|position| *position..*position + 1)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            if min_gaps(&occurrences, true).is_some_and(|gaps| gaps <= slop) {
+                let score = phrase
+                    .iter()
+                    .map(|word| self.term_score(word, doc_id, docs_count))
+                    .sum();
+                scores.insert(doc_id.as_str(), score);
+            }
+        }
+
+        scores
+    }
+
+    pub fn doc_freq(&self, term: &str) -> usize {
+        self.postings(term).map(HashMap::len).unwrap_or(0)
+    }
+
+    pub fn term_score(&self, term: &str, doc_id: &str, docs_count: usize) -> f64 {
+        let term_freq = self
+            .postings(term)
+            .and_then(|docs| docs.get(doc_id))
+            .map(Vec::len)
+            .unwrap_or(0);
+
+        if term_freq == 0 || docs_count == 0 {
+            return 0.0;
+        }
+
+        let doc_freq = self.doc_freq(term) as f64;
+        let docs_count = docs_count as f64;
+        let idf = (1.0 + (docs_count - doc_freq + 0.5) / (doc_freq + 0.5)).ln();
+
+        let avg_length = self.total_length as f64 / docs_count;
+        let doc_length = *self.lengths.get(doc_id).unwrap_or(&0) as f64;
+        let norm = 1.0 - BM25_B + BM25_B * doc_length / avg_length.max(1.0);
+        let term_freq = term_freq as f64;
+        idf * term_freq * (BM25_K1 + 1.0) / (term_freq + BM25_K1 * norm)
+    }
+}
+
+#[derive(Default)]
+pub struct BucketIndex {
+    name: String,
+    documents: HashMap<String, Document>,
+    fields: HashMap<&'static str, FieldIndex>,
+    /// Documents names and terms of `entity_data` to complete prefixes.
+    names: PrefixTrie,
+    terms: PrefixTrie,
+}
+
+impl BucketIndex {
+    pub fn new(name: &str) -> Self {
+        BucketIndex {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    pub fn docs_count(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn contains(&self, doc_id: &str) -> bool {
+        self.documents.contains_key(doc_id)
+    }
+
+    pub fn get(&self, doc_id: &str) -> Option<&Document> {
+        self.documents.get(doc_id)
+    }
+
+    pub fn documents(&self) -> impl Iterator<Item = (&String, &Document)> {
+        self.documents.iter()
+    }
+
+    pub fn field(&self, field: &str) -> Option<&FieldIndex> {
+        self.fields.get(field)
+    }
+
+    pub fn insert(&mut self, document: Document) {
+        let doc_id = document.document_md5_hash.clone();
+        self.remove(doc_id.as_str());
+        for field in INDEXED_FIELDS {
+            let text = field_value(&document, field);
+            self.fields.entry(field).or_default().insert(&doc_id, text);
+        }
+
+        self.names.insert(&document.document_name);
+        unique_terms(&document.entity_data).for_each(|term| self.terms.insert(&term));
+        self.documents.insert(doc_id, document);
+    }
+
+    pub fn remove(&mut self, doc_id: &str) -> Option<Document> {
+        let document = self.documents.remove(doc_id)?;
+        for field in INDEXED_FIELDS {
+            let text = field_value(&document, field);
+            if let Some(field_index) = self.fields.get_mut(field) {
+                field_index.remove(doc_id, text);
+            }
+        }
+
+        self.names.remove(&document.document_name);
+        unique_terms(&document.entity_data).for_each(|term| self.terms.remove(&term));
+        Some(document)
+    }
+
+    pub fn complete_names(&self, prefix: &str, size: usize) -> Vec<(&str, usize)> {
+        self.names.complete(prefix, size)
+    }
+
+    pub fn complete_terms(&self, prefix: &str, size: usize) -> Vec<(&str, usize)> {
+        self.terms.complete(prefix, size)
+    }
+
+    pub fn term_count(&self, term: &str) -> usize {
+        self.terms.count(term)
+    }
+
+    pub fn similar_terms(&self, word: &str, max_edits: usize) -> Vec<(&str, usize)> {
+        self.terms.similar(word, max_edits)
+    }
+
+    /// Scores documents matching any of words or phrases of query like the
+    /// `multi_match` query with `best_fields` type: the best scored field
+    /// wins. Fuzzy variants of word are scored by the best of them.
+    pub fn score_query(
+        &self,
+        query: &TextQuery,
+        fields: &[&str],
+        options: &MatchOptions,
+    ) -> HashMap<String, f64> {
+        let docs_count = self.docs_count();
+        let mut scores: HashMap<String, f64> = HashMap::new();
+        for field in fields {
+            let Some(field_index) = self.field(field) else {
+                continue;
+            };
+
+            let mut field_scores: HashMap<&str, f64> = HashMap::new();
+            for word in query.words.iter() {
+                let mut word_scores: HashMap<&str, f64> = HashMap::new();
+                for term in field_index.fuzzy_terms(word, options.fuzzy_edits(word)) {
+                    let Some(docs) = field_index.postings(term) else {
+                        continue;
+                    };
+
+                    for doc_id in docs.keys() {
+                        let score = field_index.term_score(term, doc_id, docs_count);
+                        let best_score = word_scores.entry(doc_id).or_default();
+                        *best_score = best_score.max(score);
+                    }
+                }
+
+                for (doc_id, score) in word_scores {
+                    *field_scores.entry(doc_id).or_default() += score;
+                }
+            }
+
+            let slop = options.slop.unwrap_or_default();
+            for phrase in query.phrases.iter() {
+                for (doc_id, score) in field_index.score_phrase(phrase, slop, docs_count) {
+                    *field_scores.entry(doc_id).or_default() += score;
+                }
+            }
+
+            for (doc_id, score) in field_scores {
+                let best_score = scores.entry(doc_id.to_string()).or_default();
+                *best_score = best_score.max(score);
+            }
+        }
+
+        scores
+    }
+
+    /// Returns terms of passed fields which are matched by words of query
+    /// including its fuzzy variants.
+    pub fn matched_terms(
+        &self,
+        query: &TextQuery,
+        fields: &[&str],
+        options: &MatchOptions,
+    ) -> HashSet<String> {
+        let fuzzy_terms = fields
+            .iter()
+            .filter_map(|field| self.field(field))
+            .flat_map(|field_index| {
+                query
+                    .words
+                    .iter()
+                    .flat_map(|word| field_index.fuzzy_terms(word, options.fuzzy_edits(word)))
+            })
+            .map(str::to_string);
+
+        query.terms().chain(fuzzy_terms).collect()
+    }
+}
+
+fn unique_terms(text: &str) -> impl Iterator<Item = String> {
+    tokenize_terms(text)
+        .into_iter()
+        .collect::<HashSet<_>>()
+        .into_iter()
+}
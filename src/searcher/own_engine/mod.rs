@@ -1,11 +1,30 @@
 pub mod client;
 pub mod context;
+pub mod engine;
+pub mod helper;
+mod index;
+mod storage;
+mod tokenizer;
+mod trie;
 
 use crate::searcher::own_engine::context::OtherContext;
+use crate::searcher::own_engine::engine::SearchEngine;
 use std::error::Error;
+use std::path::Path;
 
 pub type ClientBuildResult = Result<OtherContext, Box<dyn Error>>;
 
-pub fn build_own_client(_es_host: &str, _es_user: &str, _es_passwd: &str) -> ClientBuildResult {
-    Ok(OtherContext::_new(String::from("Own client")))
+/// Builds own engine client which keeps data in passed directory or in
+/// memory only if data directory is not specified.
+pub fn build_own_client(data_dir: Option<&str>) -> ClientBuildResult {
+    let Some(data_dir) = data_dir else {
+        return Ok(OtherContext::_new(SearchEngine::new()));
+    };
+
+    let engine = SearchEngine::open(Path::new(data_dir))?;
+    if let Some(storage) = engine.storage() {
+        storage.spawn_merger();
+    }
+
+    Ok(OtherContext::_new(engine))
 }
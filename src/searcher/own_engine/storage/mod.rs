@@ -0,0 +1,363 @@
+mod segment;
+mod wal;
+
+use crate::errors::WebError;
+use crate::searcher::own_engine::storage::segment::*;
+use crate::searcher::own_engine::storage::wal::Wal;
+use crate::wrappers::document::Document;
+use crate::wrappers::saved_search::SavedSearch;
+
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const WAL_FILE_NAME: &str = "wal.log";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const FLUSH_THRESHOLD: usize = 1000;
+const MERGE_THRESHOLD: usize = 4;
+const MERGE_INTERVAL: Duration = Duration::from_secs(30);
+
+pub type StorageResult<T> = Result<T, StorageError>;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Failed while accessing data files: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed while (de)serializing data files: {0}")]
+    Serde(#[from] serde_json::Error),
+    #[error("Storage lock has been poisoned")]
+    Poisoned,
+}
+
+impl From<StorageError> for WebError {
+    fn from(value: StorageError) -> Self {
+        WebError::StorageFailed(value.to_string())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operation {
+    CreateBucket { bucket_id: String },
+    DeleteBucket { bucket_id: String },
+    Index { document: Box<Document> },
+    Delete { bucket_id: String, doc_id: String },
+    SaveSearch { search: Box<SavedSearch> },
+    DeleteSearch { search_id: String },
+}
+
+/// List of live segments in order of their creation and the sequence number
+/// of the last operation stored to segments.
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct Manifest {
+    next_segment_id: u64,
+    flushed_seq: u64,
+    segments: Vec<u64>,
+}
+
+impl Manifest {
+    fn load(data_dir: &Path) -> StorageResult<Manifest> {
+        let path = data_dir.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+
+        let data = fs::read(path)?;
+        Ok(serde_json::from_slice(data.as_slice())?)
+    }
+
+    fn store(&self, data_dir: &Path) -> StorageResult<()> {
+        let path = data_dir.join(MANIFEST_FILE_NAME);
+        let tmp_path = path.with_extension("tmp");
+        let file = File::create(&tmp_path)?;
+        serde_json::to_writer(&file, self)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &path)?;
+        File::open(data_dir)?.sync_all()?;
+        Ok(())
+    }
+}
+
+/// On-disk storage of own engine: operations are appended to write-ahead
+/// log and flushed to immutable segment files, which are merged by the
+/// background task.
+#[derive(Clone)]
+pub struct Storage {
+    data_dir: PathBuf,
+    wal: Arc<Mutex<Wal>>,
+    manifest: Arc<Mutex<Manifest>>,
+    merging: Arc<Mutex<()>>,
+}
+
+impl Storage {
+    /// Opens storage in passed directory and returns operations of all
+    /// segments and write-ahead log to restore index state.
+    pub fn open(data_dir: &Path) -> StorageResult<(Storage, Vec<Operation>)> {
+        fs::create_dir_all(data_dir)?;
+        let manifest = Manifest::load(data_dir)?;
+        remove_orphan_segments(data_dir, &manifest)?;
+
+        let mut operations = Vec::new();
+        for segment_id in manifest.segments.iter() {
+            operations.extend(read_segment(data_dir, *segment_id)?);
+        }
+
+        let wal = Wal::open(&data_dir.join(WAL_FILE_NAME), manifest.flushed_seq)?;
+        operations.extend_from_slice(wal.pending());
+        log::info!(
+            "Restored {} segments and {} log entries from {:?}",
+            manifest.segments.len(),
+            wal.pending().len(),
+            data_dir
+        );
+
+        let storage = Storage {
+            data_dir: data_dir.to_path_buf(),
+            wal: Arc::new(Mutex::new(wal)),
+            manifest: Arc::new(Mutex::new(manifest)),
+            merging: Arc::new(Mutex::new(())),
+        };
+
+        Ok((storage, operations))
+    }
+
+    /// Durably stores passed operations. They must be applied to the index
+    /// only after this method returns successfully.
+    pub fn log(&self, operations: &[Operation]) -> StorageResult<()> {
+        let mut wal = self.wal.lock().map_err(|_| StorageError::Poisoned)?;
+        wal.append(operations)?;
+        if wal.pending().len() >= FLUSH_THRESHOLD {
+            self.flush_wal(&mut wal)?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub fn flush(&self) -> StorageResult<()> {
+        let mut wal = self.wal.lock().map_err(|_| StorageError::Poisoned)?;
+        self.flush_wal(&mut wal)
+    }
+
+    fn flush_wal(&self, wal: &mut Wal) -> StorageResult<()> {
+        if wal.pending().is_empty() {
+            return Ok(());
+        }
+
+        let mut manifest = self.manifest.lock().map_err(|_| StorageError::Poisoned)?;
+        let mut flushed = manifest.clone();
+        let segment_id = flushed.next_segment_id;
+        write_segment(&self.data_dir, segment_id, wal.pending())?;
+
+        flushed.next_segment_id += 1;
+        flushed.flushed_seq = wal.last_seq();
+        flushed.segments.push(segment_id);
+        flushed.store(&self.data_dir)?;
+        *manifest = flushed;
+
+        wal.truncate()
+    }
+
+    /// Merges all live segments into a single one when their count exceeds
+    /// the threshold. Segments flushed while merging are kept after merged.
+    pub fn merge_segments(&self) -> StorageResult<()> {
+        let _merging = self.merging.lock().map_err(|_| StorageError::Poisoned)?;
+        let (merged_ids, segment_id) = {
+            let mut manifest = self.manifest.lock().map_err(|_| StorageError::Poisoned)?;
+            if manifest.segments.len() < MERGE_THRESHOLD {
+                return Ok(());
+            }
+
+            let segment_id = manifest.next_segment_id;
+            manifest.next_segment_id += 1;
+            (manifest.segments.clone(), segment_id)
+        };
+
+        let mut operations = Vec::new();
+        for merged_id in merged_ids.iter() {
+            operations.extend(read_segment(&self.data_dir, *merged_id)?);
+        }
+
+        write_segment(&self.data_dir, segment_id, &compact(operations))?;
+
+        {
+            let mut manifest = self.manifest.lock().map_err(|_| StorageError::Poisoned)?;
+            let mut merged = manifest.clone();
+            merged.segments.retain(|id| !merged_ids.contains(id));
+            merged.segments.insert(0, segment_id);
+            merged.store(&self.data_dir)?;
+            *manifest = merged;
+        }
+
+        for merged_id in merged_ids.iter() {
+            fs::remove_file(segment_path(&self.data_dir, *merged_id))?;
+        }
+
+        log::info!("Merged {} segments into {}", merged_ids.len(), segment_id);
+        Ok(())
+    }
+
+    pub fn spawn_merger(&self) {
+        let storage = self.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(MERGE_INTERVAL);
+            if let Err(err) = storage.merge_segments() {
+                log::error!("Failed while merging segments: {}", err);
+            }
+        });
+    }
+}
+
+/// Removes segments left by flush or merge interrupted before the manifest
+/// has been stored.
+fn remove_orphan_segments(data_dir: &Path, manifest: &Manifest) -> StorageResult<()> {
+    for entry in fs::read_dir(data_dir)? {
+        let path = entry?.path();
+        let is_orphan = match parse_segment_id(&path) {
+            Some(segment_id) => !manifest.segments.contains(&segment_id),
+            None => path.extension().map(|ext| ext == "tmp").unwrap_or(false),
+        };
+
+        if is_orphan {
+            log::warn!("Removing orphan data file: {:?}", path);
+            fs::remove_file(path)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod storage_tests {
+    use super::*;
+    use crate::wrappers::document::DocumentBuilder;
+
+    fn build_document(bucket: &str, id: &str, entity_data: &str) -> Document {
+        DocumentBuilder::default()
+            .bucket_uuid(bucket.to_string())
+            .bucket_path("/tmp/test_document".to_string())
+            .document_name(id.to_string())
+            .document_path("/tmp/dir/".to_string())
+            .document_size(1024)
+            .document_type("document".to_string())
+            .document_extension("txt".to_string())
+            .document_permissions(777)
+            .document_md5_hash(id.to_string())
+            .document_ssdeep_hash("3a:34gh5".to_string())
+            .entity_data(entity_data.to_string())
+            .entity_keywords(Vec::default())
+            .highlight(None)
+            .document_created(None)
+            .document_modified(None)
+            .build()
+            .unwrap()
+    }
+
+    fn test_data_dir(name: &str) -> PathBuf {
+        let data_dir = std::env::temp_dir().join(format!("searcher_storage_{}", name));
+        let _ = fs::remove_dir_all(&data_dir);
+        data_dir
+    }
+
+    fn indexed_ids(operations: &[Operation]) -> Vec<String> {
+        compact(operations.to_vec())
+            .into_iter()
+            .filter_map(|operation| match operation {
+                Operation::Index { document } => Some(document.document_md5_hash),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn restore_after_reopen() {
+        let data_dir = test_data_dir("reopen");
+        let (storage, operations) = Storage::open(&data_dir).unwrap();
+        assert!(operations.is_empty());
+
+        storage
+            .log(&[
+                Operation::CreateBucket {
+                    bucket_id: "test_bucket".to_string(),
+                },
+                Operation::Index {
+                    document: Box::new(build_document("test_bucket", "first", "first text")),
+                },
+            ])
+            .unwrap();
+        storage.flush().unwrap();
+        storage
+            .log(&[
+                Operation::Index {
+                    document: Box::new(build_document("test_bucket", "second", "second text")),
+                },
+                Operation::Delete {
+                    bucket_id: "test_bucket".to_string(),
+                    doc_id: "first".to_string(),
+                },
+            ])
+            .unwrap();
+        drop(storage);
+
+        let (_, operations) = Storage::open(&data_dir).unwrap();
+        assert_eq!(operations.len(), 4);
+        assert_eq!(indexed_ids(&operations), vec!["second"]);
+    }
+
+    #[test]
+    fn skip_broken_wal_tail() {
+        let data_dir = test_data_dir("broken_tail");
+        let (storage, _) = Storage::open(&data_dir).unwrap();
+        let document = Box::new(build_document("test_bucket", "first", "first text"));
+        storage.log(&[Operation::Index { document }]).unwrap();
+        drop(storage);
+
+        let mut wal_data = fs::read(data_dir.join(WAL_FILE_NAME)).unwrap();
+        wal_data.extend_from_slice(b"{\"seq\":2,\"operation\":{\"type\":\"ind");
+        fs::write(data_dir.join(WAL_FILE_NAME), wal_data).unwrap();
+
+        let (storage, operations) = Storage::open(&data_dir).unwrap();
+        assert_eq!(indexed_ids(&operations), vec!["first"]);
+
+        let document = Box::new(build_document("test_bucket", "second", "second text"));
+        storage.log(&[Operation::Index { document }]).unwrap();
+        drop(storage);
+
+        let (_, operations) = Storage::open(&data_dir).unwrap();
+        assert_eq!(indexed_ids(&operations), vec!["first", "second"]);
+    }
+
+    #[test]
+    fn merge_segments() {
+        let data_dir = test_data_dir("merge");
+        let (storage, _) = Storage::open(&data_dir).unwrap();
+        for index in 0..MERGE_THRESHOLD {
+            let doc_id = format!("doc_{}", index);
+            let document = Box::new(build_document("test_bucket", &doc_id, "text"));
+            storage.log(&[Operation::Index { document }]).unwrap();
+            storage.flush().unwrap();
+        }
+
+        let bucket_id = "test_bucket".to_string();
+        let doc_id = "doc_0".to_string();
+        storage
+            .log(&[Operation::Delete { bucket_id, doc_id }])
+            .unwrap();
+        storage.flush().unwrap();
+        storage.merge_segments().unwrap();
+
+        let segments = fs::read_dir(&data_dir)
+            .unwrap()
+            .filter_map(|entry| parse_segment_id(&entry.unwrap().path()))
+            .count();
+        assert_eq!(segments, 1);
+
+        drop(storage);
+        let (_, operations) = Storage::open(&data_dir).unwrap();
+        assert_eq!(indexed_ids(&operations), vec!["doc_1", "doc_2", "doc_3"]);
+    }
+}
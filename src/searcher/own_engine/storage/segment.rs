@@ -0,0 +1,111 @@
+use crate::searcher::own_engine::storage::{Operation, StorageResult};
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const SEGMENT_PREFIX: &str = "segment_";
+const SEGMENT_EXTENSION: &str = "seg";
+
+pub fn segment_path(data_dir: &Path, segment_id: u64) -> PathBuf {
+    data_dir.join(format!(
+        "{}{:08}.{}",
+        SEGMENT_PREFIX, segment_id, SEGMENT_EXTENSION
+    ))
+}
+
+pub fn parse_segment_id(path: &Path) -> Option<u64> {
+    if path.extension()? != SEGMENT_EXTENSION {
+        return None;
+    }
+
+    let file_stem = path.file_stem()?.to_str()?;
+    file_stem.strip_prefix(SEGMENT_PREFIX)?.parse().ok()
+}
+
+/// Writes immutable segment file. The data is written to a temporary file
+/// which is renamed after sync, so a segment file is either complete or
+/// does not exist at all.
+pub fn write_segment(
+    data_dir: &Path,
+    segment_id: u64,
+    operations: &[Operation],
+) -> StorageResult<()> {
+    let path = segment_path(data_dir, segment_id);
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    for operation in operations {
+        serde_json::to_writer(&mut writer, operation)?;
+        writer.write_all(b"\n")?;
+    }
+
+    let file = writer.into_inner().map_err(|err| err.into_error())?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+pub fn read_segment(data_dir: &Path, segment_id: u64) -> StorageResult<Vec<Operation>> {
+    let path = segment_path(data_dir, segment_id);
+    let reader = BufReader::new(File::open(path)?);
+    let mut operations = Vec::new();
+    for line in reader.lines() {
+        let operation = serde_json::from_str(line?.as_str())?;
+        operations.push(operation);
+    }
+
+    Ok(operations)
+}
+
+/// Collapses operations of several segments into the final state: deleted
+/// buckets, documents and saved searches are dropped and only the last
+/// version of each document and saved search is kept.
+pub fn compact(operations: Vec<Operation>) -> Vec<Operation> {
+    let mut buckets = BTreeMap::new();
+    let mut searches = BTreeMap::new();
+    for operation in operations {
+        match operation {
+            Operation::CreateBucket { bucket_id } => {
+                buckets.entry(bucket_id).or_insert_with(BTreeMap::new);
+            }
+            Operation::DeleteBucket { bucket_id } => {
+                buckets.remove(&bucket_id);
+            }
+            Operation::Index { document } => {
+                let bucket_id = document.bucket_uuid.clone();
+                let doc_id = document.document_md5_hash.clone();
+                buckets
+                    .entry(bucket_id)
+                    .or_insert_with(BTreeMap::new)
+                    .insert(doc_id, document);
+            }
+            Operation::Delete { bucket_id, doc_id } => {
+                if let Some(documents) = buckets.get_mut(&bucket_id) {
+                    documents.remove(&doc_id);
+                }
+            }
+            Operation::SaveSearch { search } => {
+                searches.insert(search.id.clone(), search);
+            }
+            Operation::DeleteSearch { search_id } => {
+                searches.remove(&search_id);
+            }
+        }
+    }
+
+    let mut compacted = Vec::new();
+    for (bucket_id, documents) in buckets {
+        compacted.push(Operation::CreateBucket { bucket_id });
+        let indexed = documents
+            .into_values()
+            .map(|document| Operation::Index { document });
+        compacted.extend(indexed);
+    }
+
+    let saved = searches
+        .into_values()
+        .map(|search| Operation::SaveSearch { search });
+    compacted.extend(saved);
+    compacted
+}
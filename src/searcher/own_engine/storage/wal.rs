@@ -0,0 +1,110 @@
+use crate::searcher::own_engine::storage::{Operation, StorageResult};
+
+use serde_derive::{Deserialize, Serialize};
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize)]
+struct WalEntry {
+    seq: u64,
+    operation: Operation,
+}
+
+/// Append-only log of operations which are not flushed to segments yet.
+/// Every entry is written as a json line and synced to disk before the
+/// operation is applied to the in-memory index.
+pub struct Wal {
+    path: PathBuf,
+    file: File,
+    next_seq: u64,
+    pending: Vec<Operation>,
+}
+
+impl Wal {
+    /// Opens log file and returns operations with sequence number greater
+    /// than `flushed_seq`. A partially written tail entry left after crash
+    /// is truncated.
+    pub fn open(path: &Path, flushed_seq: u64) -> StorageResult<Wal> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        let mut valid_len = 0_u64;
+        let mut next_seq = flushed_seq + 1;
+        let mut pending = Vec::new();
+        let mut reader = BufReader::new(&file);
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let Ok(entry) = serde_json::from_str::<WalEntry>(line.trim_end()) else {
+                break;
+            };
+
+            if !line.ends_with('\n') {
+                break;
+            }
+
+            valid_len += line.len() as u64;
+            line.clear();
+            if entry.seq > flushed_seq {
+                next_seq = entry.seq + 1;
+                pending.push(entry.operation);
+            }
+        }
+
+        if valid_len < file.metadata()?.len() {
+            log::warn!("Truncating broken tail of write-ahead log: {:?}", path);
+            file.set_len(valid_len)?;
+            file.sync_all()?;
+        }
+
+        file.seek(SeekFrom::End(0))?;
+        Ok(Wal {
+            path: path.to_path_buf(),
+            file,
+            next_seq,
+            pending,
+        })
+    }
+
+    pub fn append(&mut self, operations: &[Operation]) -> StorageResult<()> {
+        let mut buffer = Vec::new();
+        for (index, operation) in operations.iter().enumerate() {
+            let entry = WalEntry {
+                seq: self.next_seq + index as u64,
+                operation: operation.clone(),
+            };
+
+            serde_json::to_writer(&mut buffer, &entry)?;
+            buffer.push(b'\n');
+        }
+
+        self.file.write_all(&buffer)?;
+        self.file.sync_data()?;
+        self.next_seq += operations.len() as u64;
+        self.pending.extend_from_slice(operations);
+        Ok(())
+    }
+
+    pub fn pending(&self) -> &[Operation] {
+        self.pending.as_slice()
+    }
+
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// Drops all entries after they have been flushed to a segment.
+    pub fn truncate(&mut self) -> StorageResult<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.sync_all()?;
+        self.pending.clear();
+        log::debug!("Write-ahead log has been truncated: {:?}", self.path);
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub term: String,
+    pub position: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut token_start: Option<usize> = None;
+    for (index, symbol) in text.char_indices() {
+        match (symbol.is_alphanumeric(), token_start) {
+            (true, None) => token_start = Some(index),
+            (false, Some(start)) => {
+                push_token(&mut tokens, text, start, index);
+                token_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = token_start {
+        push_token(&mut tokens, text, start, text.len());
+    }
+
+    tokens
+}
+
+pub fn tokenize_terms(text: &str) -> Vec<String> {
+    tokenize(text).into_iter().map(|token| token.term).collect()
+}
+
+fn push_token(tokens: &mut Vec<Token>, text: &str, start: usize, end: usize) {
+    let token = Token {
+        term: text[start..end].to_lowercase(),
+        position: tokens.len() as u32,
+        start,
+        end,
+    };
+
+    tokens.push(token);
+}
+
+#[cfg(test)]
+mod tokenizer_tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_mixed_text() {
+        let tokens = tokenize("Using skip_serializing, Ростов!");
+        let terms = tokens.iter().map(|t| t.term.as_str()).collect::<Vec<_>>();
+        assert_eq!(terms, vec!["using", "skip", "serializing", "ростов"]);
+        assert_eq!(tokens[3].position, 3);
+        assert_eq!(
+            &"Using skip_serializing, Ростов!"[tokens[3].start..tokens[3].end],
+            "Ростов"
+        );
+    }
+}
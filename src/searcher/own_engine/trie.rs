@@ -0,0 +1,188 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Prefix tree of lowercase keys which counts documents containing every
+/// key to complete prefixes typed by user.
+#[derive(Default)]
+pub struct PrefixTrie {
+    root: TrieNode,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    children: BTreeMap<char, TrieNode>,
+    count: usize,
+    /// Original text of key which is returned as completion.
+    text: String,
+}
+
+impl PrefixTrie {
+    pub fn insert(&mut self, text: &str) {
+        let mut node = &mut self.root;
+        for symbol in text.to_lowercase().chars() {
+            node = node.children.entry(symbol).or_default();
+        }
+
+        node.count += 1;
+        node.text = text.to_string();
+    }
+
+    pub fn remove(&mut self, text: &str) {
+        let key = text.to_lowercase().chars().collect::<Vec<_>>();
+        self.root.remove(&key);
+    }
+
+    /// Returns the most frequent keys starting with passed lowercase prefix
+    /// and counts of its documents. Keys with equal counts are ordered
+    /// ignoring case.
+    pub fn complete(&self, prefix: &str, size: usize) -> Vec<(&str, usize)> {
+        let mut node = &self.root;
+        for symbol in prefix.chars() {
+            match node.children.get(&symbol) {
+                Some(child) => node = child,
+                None => return Vec::default(),
+            }
+        }
+
+        let mut completions = Vec::new();
+        node.collect(&mut completions);
+        completions.sort_by_cached_key(|(text, count)| (Reverse(*count), text.to_lowercase()));
+        completions.truncate(size);
+        completions
+    }
+
+    /// Returns count of documents containing lowercase key.
+    pub fn count(&self, key: &str) -> usize {
+        let mut node = &self.root;
+        for symbol in key.chars() {
+            match node.children.get(&symbol) {
+                Some(child) => node = child,
+                None => return 0,
+            }
+        }
+
+        node.count
+    }
+
+    /// Returns keys within passed edit distance of lowercase word and counts
+    /// of its documents. Subtree is skipped as soon as distance of its prefix
+    /// to every prefix of word exceeds limit.
+    pub fn similar(&self, word: &str, max_edits: usize) -> Vec<(&str, usize)> {
+        let word = word.chars().collect::<Vec<_>>();
+        let distances = (0..=word.len()).collect::<Vec<_>>();
+        let mut similar = Vec::new();
+        for (symbol, child) in self.root.children.iter() {
+            child.collect_similar(*symbol, &word, &distances, max_edits, &mut similar);
+        }
+
+        similar
+    }
+}
+
+impl TrieNode {
+    /// Removes key from subtree and returns true if node became empty and
+    /// may be removed by its parent.
+    fn remove(&mut self, key: &[char]) -> bool {
+        match key.split_first() {
+            None => self.count = self.count.saturating_sub(1),
+            Some((symbol, rest)) => {
+                let is_empty = match self.children.get_mut(symbol) {
+                    Some(child) => child.remove(rest),
+                    None => false,
+                };
+
+                if is_empty {
+                    self.children.remove(symbol);
+                }
+            }
+        }
+
+        self.count == 0 && self.children.is_empty()
+    }
+
+    /// Extends distances of parent prefix to every prefix of word by
+    /// symbol of node like a row of Levenshtein matrix.
+    fn collect_similar<'a>(
+        &'a self,
+        symbol: char,
+        word: &[char],
+        parent_distances: &[usize],
+        max_edits: usize,
+        similar: &mut Vec<(&'a str, usize)>,
+    ) {
+        let mut distances = Vec::with_capacity(parent_distances.len());
+        distances.push(parent_distances[0] + 1);
+        for (index, word_symbol) in word.iter().enumerate() {
+            let cost = usize::from(*word_symbol != symbol);
+            let distance = (distances[index] + 1)
+                .min(parent_distances[index + 1] + 1)
+                .min(parent_distances[index] + cost);
+            distances.push(distance);
+        }
+
+        if self.count > 0 && distances[word.len()] <= max_edits {
+            similar.push((self.text.as_str(), self.count));
+        }
+
+        if distances.iter().any(|distance| *distance <= max_edits) {
+            for (symbol, child) in self.children.iter() {
+                child.collect_similar(*symbol, word, &distances, max_edits, similar);
+            }
+        }
+    }
+
+    fn collect<'a>(&'a self, completions: &mut Vec<(&'a str, usize)>) {
+        if self.count > 0 {
+            completions.push((self.text.as_str(), self.count));
+        }
+
+        for child in self.children.values() {
+            child.collect(completions);
+        }
+    }
+}
+
+#[cfg(test)]
+mod trie_tests {
+    use super::*;
+
+    #[test]
+    fn complete_prefix_test() {
+        let mut trie = PrefixTrie::default();
+        trie.insert("report");
+        trie.insert("report");
+        trie.insert("reply");
+        trie.insert("Result.txt");
+        trie.insert("tenant");
+
+        let completions = trie.complete("re", 2);
+        assert_eq!(completions, vec![("report", 2), ("reply", 1)]);
+        assert_eq!(trie.complete("result", 5), vec![("Result.txt", 1)]);
+        assert!(trie.complete("x", 5).is_empty());
+
+        trie.remove("reply");
+        trie.remove("report");
+        assert_eq!(trie.complete("rep", 5), vec![("report", 1)]);
+
+        trie.remove("report");
+        assert!(trie.complete("rep", 5).is_empty());
+        let node = &trie.root.children[&'r'].children[&'e'];
+        assert!(!node.children.contains_key(&'p'));
+    }
+
+    #[test]
+    fn similar_keys_test() {
+        let mut trie = PrefixTrie::default();
+        ["commander", "commander", "commanded", "command", "fleet"]
+            .into_iter()
+            .for_each(|key| trie.insert(key));
+
+        let mut similar = trie.similar("commandr", 1);
+        similar.sort();
+        assert_eq!(similar, vec![("command", 1), ("commander", 2)]);
+        assert_eq!(trie.similar("commandr", 2).len(), 3);
+        assert!(trie.similar("flt", 1).is_empty());
+        assert_eq!(trie.count("commander"), 2);
+        assert_eq!(trie.count("comm"), 0);
+    }
+}
@@ -1,15 +1,94 @@
-use crate::errors::WebResponse;
+use crate::errors::{WebError, WebResponse};
 use crate::wrappers::bucket::{Bucket, BucketForm};
 use crate::wrappers::cluster::Cluster;
 use crate::wrappers::document::Document;
+use crate::wrappers::saved_search::{SavedSearch, SavedSearchForm};
 use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::{SuggestForm, Suggestion};
 
 use actix_web::{web, HttpResponse};
+use futures::stream::BoxStream;
 
 pub type JsonResponse<T> = WebResponse<web::Json<T>>;
+pub type DocumentStream = BoxStream<'static, Result<Vec<Document>, WebError>>;
+
+/// Size of batches to export all found documents by embedded engines.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+/// Returns error of cursor pagination for backends which do not support it.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn cursor_not_supported() -> WebError {
+    let msg = "Cursor pagination is supported by elastic backend only";
+    WebError::SearchFailed(msg.to_string())
+}
+
+/// Rejects cursor pagination by backends which do not support it.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn check_offset_pagination(s_params: &SearchParams) -> Result<(), WebError> {
+    match s_params.is_cursor_pagination() {
+        false => Ok(()),
+        true => Err(cursor_not_supported()),
+    }
+}
+
+/// Returns stream of all documents matched by passed parameters which are
+/// loaded by batches with increasing offset. It is used by embedded engines
+/// which have no point-in-time, so documents indexed while stream is read
+/// may shift batches.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn export_by_offset<F, Fut>(s_params: &SearchParams, search: F) -> DocumentStream
+where
+    F: Fn(SearchParams) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Result<SearchResponse, WebError>> + Send + 'static,
+{
+    let s_params = SearchParams {
+        result_offset: 0,
+        result_size: EXPORT_BATCH_SIZE,
+        aggregations: Vec::default(),
+        keep_alive: None,
+        cursor: None,
+        ..s_params.clone()
+    };
+
+    let documents = futures::stream::try_unfold(Some(s_params), move |s_params| {
+        let batch = s_params.as_ref().map(|params| search(params.clone()));
+        async move {
+            let (Some(mut s_params), Some(batch)) = (s_params, batch) else {
+                return Ok(None);
+            };
+
+            let search_response = batch.await?;
+            let documents = search_response.hits.into_iter().map(|hit| hit.document);
+            let documents = documents.collect::<Vec<Document>>();
+            let has_more = documents.len() as i64 == EXPORT_BATCH_SIZE;
+            s_params.result_offset += EXPORT_BATCH_SIZE;
+            Ok(Some((documents, has_more.then_some(s_params))))
+        }
+    });
+
+    Box::pin(documents)
+}
 
 #[async_trait::async_trait]
-pub trait ServiceClient {
+pub trait ServiceClient: Send + Sync {
     async fn get_all_clusters(&self) -> JsonResponse<Vec<Cluster>>;
     async fn get_cluster(&self, cluster_id: &str) -> JsonResponse<Cluster>;
     async fn create_cluster(&self, cluster_id: &str) -> HttpResponse;
@@ -20,6 +99,7 @@ pub trait ServiceClient {
     async fn delete_bucket(&self, bucket_id: &str) -> HttpResponse;
     async fn create_bucket(&self, bucket_form: &BucketForm) -> HttpResponse;
 
+    #[cfg(feature = "elastic-search")]
     async fn check_duplication(&self, bucket_id: &str, document_id: &str) -> bool;
 
     async fn get_document(&self, bucket_id: &str, doc_id: &str) -> JsonResponse<Document>;
@@ -30,17 +110,40 @@ pub trait ServiceClient {
     async fn load_file_to_bucket(&self, bucket_id: &str, file_path: &str) -> HttpResponse;
     // async fn upload_file_to_bucket(&self, bucket_id: &str, part: Multipart) -> HttpResponse;
 
-    async fn search_all(&self, s_params: &SearchParams) -> JsonResponse<Vec<Document>>;
+    async fn search_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse>;
     async fn search_bucket(
         &self,
         bucket_id: &str,
         s_params: &SearchParams,
-    ) -> JsonResponse<Vec<Document>>;
+    ) -> JsonResponse<SearchResponse>;
+    async fn close_cursor(&self, cursor: &str) -> HttpResponse;
+    async fn suggest(
+        &self,
+        buckets_ids: Option<&str>,
+        form: &SuggestForm,
+    ) -> JsonResponse<Vec<Suggestion>>;
+    async fn export_documents(
+        &self,
+        buckets_ids: Option<&str>,
+        s_params: &SearchParams,
+    ) -> WebResponse<DocumentStream>;
 
-    async fn similar_all(&self, s_params: &SearchParams) -> JsonResponse<Vec<Document>>;
+    async fn similar_all(&self, s_params: &SearchParams) -> JsonResponse<SearchResponse>;
     async fn similar_bucket(
         &self,
         bucket_id: &str,
         s_params: &SearchParams,
-    ) -> JsonResponse<Vec<Document>>;
+    ) -> JsonResponse<SearchResponse>;
+
+    async fn get_all_saved_searches(&self) -> JsonResponse<Vec<SavedSearch>>;
+    async fn get_saved_search(&self, search_id: &str) -> JsonResponse<SavedSearch>;
+    async fn create_saved_search(&self, form: &SavedSearchForm) -> JsonResponse<SavedSearch>;
+    async fn update_saved_search(
+        &self,
+        search_id: &str,
+        form: &SavedSearchForm,
+    ) -> JsonResponse<SavedSearch>;
+    async fn delete_saved_search(&self, search_id: &str) -> HttpResponse;
+    /// Records run time of saved search found by id or name and returns it.
+    async fn mark_saved_search_run(&self, search_id: &str) -> JsonResponse<SavedSearch>;
 }
@@ -0,0 +1,20 @@
+use crate::searcher::sqlite_engine::engine::SqliteEngine;
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+#[derive(Clone)]
+pub struct SqliteContext {
+    context: Arc<Mutex<SqliteEngine>>,
+}
+
+impl SqliteContext {
+    pub fn _new(engine: SqliteEngine) -> Self {
+        let context = Arc::new(Mutex::new(engine));
+        SqliteContext { context }
+    }
+
+    pub fn get_cxt(&self) -> &Arc<Mutex<SqliteEngine>> {
+        &self.context
+    }
+}
@@ -0,0 +1,718 @@
+use crate::errors::WebError;
+use crate::searcher::sqlite_engine::helper::*;
+use crate::wrappers::aggregation::{aggregate_documents, Aggregations};
+use crate::wrappers::bucket::Bucket;
+use crate::wrappers::document::Document;
+use crate::wrappers::highlight::highlight_document;
+use crate::wrappers::pattern::TextFilter;
+use crate::wrappers::saved_search::*;
+use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::*;
+
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+const MAX_QUERY_TERMS: usize = 25;
+
+pub type EngineResult<T> = Result<T, WebError>;
+
+/// Count of all matched documents and the selected ones with scores.
+type Founded = (u64, Vec<(f64, Document)>);
+
+/// Search engine which keeps each bucket in a separate table of a single
+/// SQLite database with FTS5 index over `entity_data` and `document_path`.
+pub struct SqliteEngine {
+    connection: Connection,
+    saved_searches: SavedSearches,
+}
+
+impl SqliteEngine {
+    pub fn open_in_memory() -> EngineResult<Self> {
+        SqliteEngine::init(Connection::open_in_memory()?)
+    }
+
+    pub fn open(db_path: &Path) -> EngineResult<Self> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| WebError::StorageFailed(err.to_string()))?;
+        }
+
+        let connection = Connection::open(db_path)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        SqliteEngine::init(connection)
+    }
+
+    fn init(connection: Connection) -> EngineResult<Self> {
+        register_regexp_function(&connection)?;
+        connection.execute_batch(CREATE_BUCKETS_TABLE)?;
+        connection.execute_batch(CREATE_SAVED_SEARCHES_TABLE)?;
+        let saved_searches = load_saved_searches(&connection)?;
+        Ok(SqliteEngine {
+            connection,
+            saved_searches,
+        })
+    }
+
+    pub fn buckets(&self) -> EngineResult<Vec<Bucket>> {
+        let mut statement = self.connection.prepare("SELECT id, name FROM buckets")?;
+        let buckets = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        buckets
+            .into_iter()
+            .map(|(id, name)| self.bucket_stats(id, &name))
+            .collect()
+    }
+
+    pub fn bucket(&self, bucket_id: &str) -> EngineResult<Bucket> {
+        match self.find_bucket(bucket_id)? {
+            Some(id) => self.bucket_stats(id, bucket_id),
+            None => Err(WebError::GetBucket(format!(
+                "no such bucket: {}",
+                bucket_id
+            ))),
+        }
+    }
+
+    pub fn create_bucket(&mut self, bucket_id: &str) -> EngineResult<()> {
+        if self.find_bucket(bucket_id)?.is_some() {
+            let msg = format!("bucket {} already exists", bucket_id);
+            return Err(WebError::CreateBucket(msg));
+        }
+
+        let transaction = self.connection.transaction()?;
+        open_bucket(&transaction, bucket_id)?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    pub fn delete_bucket(&mut self, bucket_id: &str) -> EngineResult<()> {
+        let Some(id) = self.find_bucket(bucket_id)? else {
+            let msg = format!("no such bucket: {}", bucket_id);
+            return Err(WebError::DeleteBucket(msg));
+        };
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute_batch(&drop_bucket_scheme(&bucket_table(id)))?;
+        transaction.execute("DELETE FROM buckets WHERE id = ?", params![id])?;
+        transaction.commit()?;
+        Ok(())
+    }
+
+    pub fn saved_searches(&self) -> &SavedSearches {
+        &self.saved_searches
+    }
+
+    /// Stores new or changed saved search which replaces the one with the
+    /// same id.
+    pub fn save_search(&mut self, search: SavedSearch) -> EngineResult<SavedSearch> {
+        let source = serde_json::to_string(&search)
+            .map_err(|err| WebError::StorageFailed(err.to_string()))?;
+        self.connection.execute(
+            "INSERT OR REPLACE INTO saved_searches (id, name, source) VALUES (?, ?, ?)",
+            params![search.id, search.name, source],
+        )?;
+
+        self.saved_searches.insert(search.clone());
+        Ok(search)
+    }
+
+    pub fn delete_saved_search(&mut self, id_or_name: &str) -> EngineResult<()> {
+        let search_id = self.saved_searches.get(id_or_name)?.id.clone();
+        self.connection.execute(
+            "DELETE FROM saved_searches WHERE id = ?",
+            params![search_id],
+        )?;
+        self.saved_searches.remove(&search_id);
+        Ok(())
+    }
+
+    pub fn get_document(&self, bucket_id: &str, doc_id: &str) -> EngineResult<Document> {
+        let source = match self.find_bucket(bucket_id)? {
+            None => None,
+            Some(id) => {
+                let query = format!(
+                    "SELECT source FROM {} WHERE document_md5_hash = ?",
+                    bucket_table(id)
+                );
+                self.connection
+                    .query_row(&query, params![doc_id], |row| row.get::<_, String>(0))
+                    .optional()?
+            }
+        };
+
+        match source {
+            Some(source) => parse_document(&source),
+            None => Err(WebError::GetDocument(format!(
+                "no such document: {}",
+                doc_id
+            ))),
+        }
+    }
+
+    pub fn contains_document(&self, bucket_id: &str, doc_id: &str) -> bool {
+        self.get_document(bucket_id, doc_id).is_ok()
+    }
+
+    /// Stores passed documents to the buckets with names from `bucket_uuid`
+    /// field and replaces existing ones with the same id. The bucket is
+    /// created if it does not exist.
+    pub fn index_documents(&mut self, documents: &[Document]) -> EngineResult<()> {
+        let transaction = self.connection.transaction()?;
+        let mut tables = HashMap::new();
+        for document in documents {
+            let bucket_id = document.bucket_uuid.as_str();
+            if !tables.contains_key(bucket_id) {
+                let table = bucket_table(open_bucket(&transaction, bucket_id)?);
+                tables.insert(bucket_id, table);
+            }
+
+            let table = &tables[bucket_id];
+            let delete_query = format!("DELETE FROM {} WHERE document_md5_hash = ?", table);
+            transaction.execute(&delete_query, params![document.document_md5_hash])?;
+
+            let insert_query = format!(
+                "INSERT INTO {} (document_md5_hash, document_name, document_path, \
+                 document_size, document_type, document_extension, document_ssdeep_hash, \
+                 entity_data, document_created, document_modified, source) \
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                table
+            );
+            transaction.execute(&insert_query, params_from_iter(document_values(document)?))?;
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    pub fn delete_document(&mut self, bucket_id: &str, doc_id: &str) -> EngineResult<()> {
+        let deleted = match self.find_bucket(bucket_id)? {
+            None => 0,
+            Some(id) => {
+                let query = format!(
+                    "DELETE FROM {} WHERE document_md5_hash = ?",
+                    bucket_table(id)
+                );
+                self.connection.execute(&query, params![doc_id])?
+            }
+        };
+
+        if deleted == 0 {
+            let msg = format!("no such document: {}", doc_id);
+            return Err(WebError::DeleteDocument(msg));
+        }
+
+        Ok(())
+    }
+
+    pub fn search(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> EngineResult<SearchResponse> {
+        let started = Instant::now();
+        let limit = result_limit(parameters);
+        let text_filter = TextFilter::new(parameters);
+        let (total, scored) = self.search_buckets(buckets_ids, parameters, &text_filter, limit)?;
+        let mut search_response = paginate(total, scored, parameters);
+        search_response.timed_out = text_filter.is_timed_out();
+
+        let query = parameters.query.as_str();
+        let terms = match is_match_all(query) {
+            true => HashSet::default(),
+            false => tokenize_terms(query).into_iter().collect(),
+        };
+
+        for hit in search_response.hits.iter_mut() {
+            let highlight = highlight_document(&hit.document, &terms, &parameters.highlight);
+            hit.document.append_highlight(highlight);
+        }
+
+        if is_low_result(search_response.total, query) {
+            search_response.did_you_mean = self.suggest_corrections(buckets_ids, query)?;
+        }
+
+        search_response.took = started.elapsed().as_millis() as u64;
+        Ok(search_response)
+    }
+
+    /// Counts all documents matched by passed parameters by its facets.
+    pub fn aggregate(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> EngineResult<Aggregations> {
+        if parameters.aggregations.is_empty() {
+            return Ok(Aggregations::default());
+        }
+
+        let text_filter = TextFilter::new(parameters);
+        let (_, scored) = self.search_buckets(buckets_ids, parameters, &text_filter, -1)?;
+        let documents = scored.iter().map(|(_, document)| document);
+        let (facets, interval) = (&parameters.aggregations, parameters.date_interval);
+        Ok(aggregate_documents(facets, interval, documents))
+    }
+
+    /// Searches documents like the passed text as elastic `more_like_this`
+    /// query does: the most significant terms of text are selected and
+    /// documents containing any of them or having the same ssdeep hash are
+    /// returned.
+    pub fn similar(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> EngineResult<SearchResponse> {
+        let started = Instant::now();
+        let like = parameters.query.as_str();
+        let mut terms = tokenize_terms(like);
+        terms.sort();
+        terms.dedup();
+
+        let limit = result_limit(parameters);
+        let (mut total, mut scored) = (0, Vec::new());
+        for (id, _) in self.select_buckets(buckets_ids)? {
+            let table = bucket_table(id);
+            let same_hash = (
+                "doc.document_ssdeep_hash = ?",
+                Value::Text(like.to_string()),
+            );
+            let (same_total, mut founded) =
+                self.filter_documents(&table, Some(same_hash), parameters, limit)?;
+            for (score, _) in founded.iter_mut() {
+                *score = f64::INFINITY;
+            }
+
+            total += same_total;
+            let significant = self.significant_terms(&table, &terms)?;
+            if !significant.is_empty() {
+                let (matched_total, matched) =
+                    self.match_documents(&table, &significant, parameters, limit)?;
+                let selected = matched.len();
+                let matched = matched
+                    .into_iter()
+                    .filter(|(_, doc)| {
+                        !founded
+                            .iter()
+                            .any(|(_, same)| same.document_md5_hash == doc.document_md5_hash)
+                    })
+                    .collect::<Vec<_>>();
+
+                // Documents matched by both queries are counted once as far
+                // as they are selected by limit.
+                total += matched_total - (selected - matched.len()) as u64;
+                founded.extend(matched);
+            }
+
+            scored.extend(founded);
+        }
+
+        let mut search_response = paginate(total, scored, parameters);
+        search_response
+            .hits
+            .iter_mut()
+            .for_each(|hit| hit.document.append_highlight(None));
+        search_response.took = started.elapsed().as_millis() as u64;
+        Ok(search_response)
+    }
+
+    /// Completes prefix by the most frequent names of documents and terms
+    /// of documents text of every bucket.
+    pub fn suggest(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        form: &SuggestForm,
+    ) -> EngineResult<Vec<Suggestion>> {
+        let size = form.size as i64;
+        let name_pattern = format!("{}%", escape_like(&form.name_prefix()));
+        let term_prefix = form.term_prefix();
+
+        let mut suggestions = Vec::new();
+        for (id, _) in self.select_buckets(buckets_ids)? {
+            let table = bucket_table(id);
+            let query = format!(
+                "SELECT document_name, COUNT(*) AS count FROM {} \
+                WHERE document_name LIKE ? ESCAPE '\\' \
+                GROUP BY document_name ORDER BY count DESC, document_name LIMIT ?",
+                table
+            );
+            let mut statement = self.connection.prepare(&query)?;
+            let names = statement
+                .query_map(params![name_pattern, size], |row| {
+                    let name: String = row.get(0)?;
+                    let count: i64 = row.get(1)?;
+                    Ok(Suggestion::new(
+                        &name,
+                        SuggestField::DocumentName,
+                        count as u64,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            suggestions.extend(names);
+
+            let Some(term_prefix) = term_prefix.as_ref() else {
+                continue;
+            };
+
+            self.connection.execute_batch(&create_terms_vocab(&table))?;
+            let query = format!(
+                "SELECT term, doc FROM temp.{}_terms \
+                WHERE col = 'entity_data' AND term >= ? AND term < ? \
+                ORDER BY doc DESC, term LIMIT ?",
+                table
+            );
+            let upper_bound = format!("{}{}", term_prefix, char::MAX);
+            let mut statement = self.connection.prepare(&query)?;
+            let terms = statement
+                .query_map(params![term_prefix, upper_bound, size], |row| {
+                    let term: String = row.get(0)?;
+                    let count: i64 = row.get(1)?;
+                    Ok(Suggestion::new(
+                        &term,
+                        SuggestField::EntityData,
+                        count as u64,
+                    ))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            suggestions.extend(terms);
+        }
+
+        Ok(merge_suggestions(suggestions, form.size))
+    }
+
+    /// Suggests query where words of documents text which are missing or
+    /// rare are replaced by close frequent terms of vocabulary. Candidates
+    /// are selected by length and then filtered by edit distance.
+    fn suggest_corrections(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        query: &str,
+    ) -> EngineResult<Vec<QuerySuggestion>> {
+        let mut words = tokenize_terms(query);
+        words.sort();
+        words.dedup();
+
+        let mut candidates: HashMap<String, (u64, Vec<(String, u64)>)> = HashMap::new();
+        for (id, _) in self.select_buckets(buckets_ids)? {
+            let table = bucket_table(id);
+            self.connection.execute_batch(&create_terms_vocab(&table))?;
+            let query = format!(
+                "SELECT term, doc FROM temp.{}_terms \
+                WHERE col = 'entity_data' AND length(term) BETWEEN ? AND ?",
+                table
+            );
+            let mut statement = self.connection.prepare(&query)?;
+            for word in words.iter() {
+                let length = word.chars().count();
+                let (min_length, max_length) = (
+                    length.saturating_sub(max_edits(word)),
+                    length + max_edits(word),
+                );
+                let terms = statement
+                    .query_map(params![min_length, max_length], |row| {
+                        let term: String = row.get(0)?;
+                        let count: i64 = row.get(1)?;
+                        Ok((term, count as u64))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let (word_count, similar) = candidates.entry(word.clone()).or_default();
+                for (term, count) in terms {
+                    match term == *word {
+                        true => *word_count += count,
+                        false => similar.push((term, count)),
+                    }
+                }
+            }
+        }
+
+        let suggestion = correct_query(query, |word| {
+            let (word_count, similar) = candidates.get(word)?;
+            let similar = similar.iter().map(|(term, count)| (term.as_str(), *count));
+            best_correction(word, *word_count, similar)
+        });
+
+        Ok(suggestion.into_iter().collect())
+    }
+
+    fn find_bucket(&self, bucket_id: &str) -> EngineResult<Option<i64>> {
+        let id = self
+            .connection
+            .query_row(
+                "SELECT id FROM buckets WHERE name = ?",
+                params![bucket_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(id)
+    }
+
+    fn select_buckets(&self, buckets_ids: Option<&[&str]>) -> EngineResult<Vec<(i64, String)>> {
+        let mut statement = self.connection.prepare("SELECT id, name FROM buckets")?;
+        let buckets = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(i64, String)>, _>>()?;
+
+        Ok(match buckets_ids {
+            None => buckets,
+            Some(ids) => buckets
+                .into_iter()
+                .filter(|(_, name)| ids.contains(&name.as_str()))
+                .collect(),
+        })
+    }
+
+    fn bucket_stats(&self, id: i64, bucket_name: &str) -> EngineResult<Bucket> {
+        let query = format!(
+            "SELECT COUNT(*), COALESCE(SUM(LENGTH(entity_data)), 0) FROM {}",
+            bucket_table(id)
+        );
+        let (docs_count, store_size) = self
+            .connection
+            .query_row(&query, [], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(extract_bucket_stats(bucket_name, docs_count, store_size))
+    }
+
+    /// Returns up to `limit` documents of every bucket matched by passed
+    /// parameters, negative limit selects all matched documents. Text filter
+    /// is checked over all matched documents.
+    fn search_buckets(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+        text_filter: &TextFilter,
+        limit: i64,
+    ) -> EngineResult<Founded> {
+        let query = parameters.query.as_str();
+        let terms = match is_match_all(query) {
+            true => Vec::default(),
+            false => tokenize_terms(query),
+        };
+
+        let limit = match text_filter.is_empty() {
+            true => limit,
+            false => -1,
+        };
+
+        let (mut total, mut scored) = (0, Vec::new());
+        for (id, _) in self.select_buckets(buckets_ids)? {
+            let table = bucket_table(id);
+            let (mut founded_total, mut founded) = match terms.is_empty() {
+                true => self.filter_documents(&table, None, parameters, limit)?,
+                false => self.match_documents(&table, &terms, parameters, limit)?,
+            };
+
+            if !text_filter.is_empty() {
+                founded.retain(|(_, doc)| text_filter.matches(doc));
+                founded_total = founded.len() as u64;
+            }
+
+            total += founded_total;
+            scored.extend(founded);
+        }
+
+        Ok((total, scored))
+    }
+
+    fn filter_documents(
+        &self,
+        table: &str,
+        predicate: Option<(&str, Value)>,
+        parameters: &SearchParams,
+        limit: i64,
+    ) -> EngineResult<Founded> {
+        let (mut filters, mut values) = build_filter_predicates(parameters);
+        if let Some((predicate, value)) = predicate {
+            filters = format!("{} AND {}", predicate, filters);
+            values.insert(0, value);
+        }
+
+        values.push(Value::Integer(limit));
+        let query = format!(
+            "SELECT doc.source, COUNT(*) OVER () FROM {} AS doc WHERE {} \
+             ORDER BY doc.document_md5_hash LIMIT ?",
+            table, filters
+        );
+
+        let mut statement = self.connection.prepare(&query)?;
+        let rows = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total = rows.first().map_or(0, |(_, total)| *total as u64);
+        let documents = rows
+            .iter()
+            .map(|(source, _)| parse_document(source).map(|doc| (1.0, doc)))
+            .collect::<EngineResult<Vec<_>>>()?;
+
+        Ok((total, documents))
+    }
+
+    /// Returns documents matched by any of passed terms ranked by FTS5 `bm25`
+    /// function.
+    fn match_documents(
+        &self,
+        table: &str,
+        terms: &[String],
+        parameters: &SearchParams,
+        limit: i64,
+    ) -> EngineResult<Founded> {
+        let (filters, filter_values) = build_filter_predicates(parameters);
+        let mut values = vec![Value::Text(build_match_query(terms))];
+        values.extend(filter_values);
+
+        // FTS5 ranking functions can not be used with window functions, so
+        // matched documents are counted by a separate query.
+        let from_query = format!(
+            "FROM {table}_fts JOIN {table} AS doc ON doc.id = {table}_fts.rowid \
+             WHERE {table}_fts MATCH ? AND {filters}"
+        );
+        let total: i64 = self.connection.query_row(
+            &format!("SELECT COUNT(*) {}", from_query),
+            params_from_iter(values.iter()),
+            |row| row.get(0),
+        )?;
+
+        values.push(Value::Integer(limit));
+        let query = format!(
+            "SELECT doc.source, -bm25({table}_fts) AS score \
+             {from_query} ORDER BY score DESC LIMIT ?"
+        );
+
+        let mut statement = self.connection.prepare(&query)?;
+        let rows = statement
+            .query_map(params_from_iter(values), |row| {
+                let source: String = row.get(0)?;
+                let score: f64 = row.get(1)?;
+                Ok((source, score))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let documents = rows
+            .iter()
+            .map(|(source, score)| parse_document(source).map(|doc| (*score, doc)))
+            .collect::<EngineResult<Vec<_>>>()?;
+
+        Ok((total as u64, documents))
+    }
+
+    fn significant_terms(&self, table: &str, terms: &[String]) -> EngineResult<Vec<String>> {
+        let docs_count: i64 =
+            self.connection
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                    row.get(0)
+                })?;
+
+        let query = format!("SELECT doc FROM {}_vocab WHERE term = ?", table);
+        let mut statement = self.connection.prepare(&query)?;
+        let mut weighted = Vec::new();
+        for term in terms {
+            let doc_freq: Option<i64> = statement
+                .query_row(params![term], |row| row.get(0))
+                .optional()?;
+            if let Some(doc_freq) = doc_freq.filter(|freq| *freq > 0) {
+                let weight = (docs_count as f64 / doc_freq as f64).ln() + 1.0;
+                weighted.push((weight, term.clone()));
+            }
+        }
+
+        weighted.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+        Ok(weighted
+            .into_iter()
+            .take(MAX_QUERY_TERMS)
+            .map(|(_, term)| term)
+            .collect())
+    }
+}
+
+/// Returns id of bucket with passed name creating its tables if the bucket
+/// does not exist.
+fn open_bucket(connection: &Connection, bucket_id: &str) -> EngineResult<i64> {
+    let id = connection
+        .query_row(
+            "SELECT id FROM buckets WHERE name = ?",
+            params![bucket_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    if let Some(id) = id {
+        return Ok(id);
+    }
+
+    connection.execute("INSERT INTO buckets (name) VALUES (?)", params![bucket_id])?;
+    let id = connection.last_insert_rowid();
+    connection.execute_batch(&create_bucket_scheme(&bucket_table(id)))?;
+    Ok(id)
+}
+
+/// Returns limit of selected rows, negative limit selects all matched rows
+/// which are required to sort documents by fields.
+fn result_limit(parameters: &SearchParams) -> i64 {
+    match parameters.is_sorted_by_fields() {
+        true => -1,
+        false => parameters.result_offset.max(0) + parameters.result_size.max(0),
+    }
+}
+
+fn paginate(
+    total: u64,
+    mut scored: Vec<(f64, Document)>,
+    parameters: &SearchParams,
+) -> SearchResponse {
+    scored.sort_by(|a, b| parameters.compare_documents((a.0, &a.1), (b.0, &b.1)));
+    SearchResponse::paginate(total, scored, parameters)
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use crate::searcher::backend_tests::{build_documents, search_params};
+
+    #[test]
+    fn restore_persisted_engine() {
+        let db_path = std::env::temp_dir().join("searcher_sqlite_reopen.sqlite");
+        let _ = std::fs::remove_file(&db_path);
+
+        let mut engine = SqliteEngine::open(&db_path).unwrap();
+        engine.index_documents(&build_documents()).unwrap();
+        engine.create_bucket("empty_bucket").unwrap();
+        drop(engine);
+
+        let engine = SqliteEngine::open(&db_path).unwrap();
+        assert_eq!(engine.buckets().unwrap().len(), 2);
+        let founded = engine.search(None, &search_params("denisov")).unwrap().hits;
+        assert_eq!(founded.len(), 1);
+        assert_eq!(founded[0].document_md5_hash, "third");
+    }
+
+    #[test]
+    fn search_similar() {
+        let mut engine = SqliteEngine::open_in_memory().unwrap();
+        engine.index_documents(&build_documents()).unwrap();
+
+        let founded = engine
+            .similar(None, &search_params("3a:third"))
+            .unwrap()
+            .hits;
+        assert_eq!(founded.len(), 1);
+        assert_eq!(founded[0].document_md5_hash, "third");
+
+        let params = search_params("Deposit shall be refunded");
+        let founded = engine
+            .similar(Some(&["test_bucket"]), &params)
+            .unwrap()
+            .hits;
+        assert_eq!(founded[0].document_md5_hash, "second");
+    }
+}
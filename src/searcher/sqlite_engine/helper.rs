@@ -0,0 +1,324 @@
+use crate::errors::WebError;
+use crate::wrappers::bucket::{Bucket, BucketBuilder};
+use crate::wrappers::document::Document;
+use crate::wrappers::saved_search::{SavedSearch, SavedSearches};
+use crate::wrappers::search_params::SearchParams;
+
+use datetime::{parse_datetime, parse_datetime_end};
+use hasher::{gen_hash, HashType};
+use regex::Regex;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::Value;
+use rusqlite::Connection;
+
+use std::path::Path;
+
+impl From<rusqlite::Error> for WebError {
+    fn from(value: rusqlite::Error) -> Self {
+        WebError::StorageFailed(value.to_string())
+    }
+}
+
+/// Registers `regexp` function which is called by `X REGEXP Y` operator.
+/// Compiled expression is cached by SQLite for the whole statement, and
+/// invalid expression does not match anything.
+pub fn register_regexp_function(connection: &Connection) -> rusqlite::Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+    connection.create_scalar_function("regexp", 2, flags, |ctx| {
+        let regex = ctx.get_or_create_aux(0, |value| {
+            Ok::<_, rusqlite::Error>(Regex::new(value.as_str()?).ok())
+        })?;
+
+        let text = ctx.get::<String>(1)?;
+        Ok(regex
+            .as_ref()
+            .as_ref()
+            .map(|it| it.is_match(&text))
+            .unwrap_or(false))
+    })
+}
+
+pub const CREATE_BUCKETS_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS buckets (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        name TEXT NOT NULL UNIQUE
+    )";
+
+/// Saved searches are stored as json with name column which keeps names
+/// unique.
+pub const CREATE_SAVED_SEARCHES_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS saved_searches (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        source TEXT NOT NULL
+    )";
+
+/// Returns statements which create documents table of bucket and its FTS5
+/// index with triggers keeping the index in sync with the table.
+pub fn create_bucket_scheme(table: &str) -> String {
+    format!(
+        "
+        CREATE TABLE {table} (
+            id INTEGER PRIMARY KEY,
+            document_md5_hash TEXT NOT NULL UNIQUE,
+            document_name TEXT NOT NULL,
+            document_path TEXT NOT NULL,
+            document_size INTEGER NOT NULL,
+            document_type TEXT NOT NULL,
+            document_extension TEXT NOT NULL,
+            document_ssdeep_hash TEXT NOT NULL,
+            entity_data TEXT NOT NULL,
+            document_created INTEGER,
+            document_modified INTEGER,
+            source TEXT NOT NULL
+        );
+        CREATE VIRTUAL TABLE {table}_fts USING fts5(
+            entity_data,
+            document_path,
+            content = '{table}',
+            content_rowid = 'id'
+        );
+        CREATE VIRTUAL TABLE {table}_vocab USING fts5vocab({table}_fts, 'row');
+        CREATE TRIGGER {table}_ai AFTER INSERT ON {table} BEGIN
+            INSERT INTO {table}_fts (rowid, entity_data, document_path)
+            VALUES (new.id, new.entity_data, new.document_path);
+        END;
+        CREATE TRIGGER {table}_ad AFTER DELETE ON {table} BEGIN
+            INSERT INTO {table}_fts ({table}_fts, rowid, entity_data, document_path)
+            VALUES ('delete', old.id, old.entity_data, old.document_path);
+        END;
+        "
+    )
+}
+
+pub fn drop_bucket_scheme(table: &str) -> String {
+    format!(
+        "
+        DROP TABLE IF EXISTS temp.{table}_terms;
+        DROP TABLE IF EXISTS {table}_vocab;
+        DROP TABLE IF EXISTS {table}_fts;
+        DROP TABLE IF EXISTS {table};
+        "
+    )
+}
+
+/// Returns statement which creates vocabulary of documents text terms with
+/// counts of documents containing them. The vocabulary is virtual table of
+/// connection, so it is created on demand instead of within bucket scheme.
+pub fn create_terms_vocab(table: &str) -> String {
+    format!("CREATE VIRTUAL TABLE IF NOT EXISTS temp.{table}_terms USING fts5vocab(main, {table}_fts, 'col')")
+}
+
+/// Escapes wildcards of LIKE pattern by backslash.
+pub fn escape_like(pattern: &str) -> String {
+    pattern
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+pub fn bucket_table(bucket_id: i64) -> String {
+    format!("bucket_{}", bucket_id)
+}
+
+pub fn is_match_all(query: &str) -> bool {
+    let query = query.trim();
+    query.is_empty() || query == "*"
+}
+
+pub fn tokenize_terms(text: &str) -> Vec<String> {
+    text.split(|symbol: char| !symbol.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Builds FTS5 match expression which matches any of passed terms like
+/// `multi_match` query with `or` operator does. Terms are quoted to avoid
+/// interpreting user input as FTS5 query syntax.
+pub fn build_match_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"", term))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// Translates `CommonFilter` of search parameters to SQL predicates over
+/// documents table with alias `doc` and returns them with bound values.
+pub fn build_filter_predicates(parameters: &SearchParams) -> (String, Vec<Value>) {
+    let mut predicates = vec!["doc.document_size >= ?".to_string()];
+    let mut values = vec![Value::Integer(parameters.document_size_from)];
+
+    if parameters.document_size_to > 0 {
+        predicates.push("doc.document_size <= ?".to_string());
+        values.push(Value::Integer(parameters.document_size_to));
+    }
+
+    let date_filters = [
+        (
+            "document_created",
+            parameters.created_date_from.as_str(),
+            parameters.created_date_to.as_str(),
+        ),
+        (
+            "document_modified",
+            parameters.modified_date_from.as_str(),
+            parameters.modified_date_to.as_str(),
+        ),
+    ];
+    for (column, gte, lte) in date_filters {
+        if gte.is_empty() && lte.is_empty() {
+            continue;
+        }
+
+        predicates.push(format!("doc.{} IS NOT NULL", column));
+        if let Some(gte) = parse_datetime(gte) {
+            predicates.push(format!("doc.{} >= ?", column));
+            values.push(Value::Integer(gte.timestamp()));
+        }
+
+        if let Some(lte) = parse_datetime_end(lte) {
+            predicates.push(format!("doc.{} <= ?", column));
+            values.push(Value::Integer(lte.timestamp()));
+        }
+    }
+
+    // Extensions and types are compared ignoring case like other engines do.
+    let term_filters = [
+        ("document_extension", parameters.document_extension()),
+        ("document_type", parameters.document_type.clone()),
+    ];
+    for (column, value) in term_filters {
+        if !value.is_empty() {
+            predicates.push(format!("doc.{} = ? COLLATE NOCASE", column));
+            values.push(Value::Text(value));
+        }
+    }
+
+    let list_filters = [
+        ("document_extension", "IN", parameters.any_extensions()),
+        ("document_type", "IN", parameters.any_types.clone()),
+        (
+            "document_extension",
+            "NOT IN",
+            parameters.exclude_extensions(),
+        ),
+        ("document_type", "NOT IN", parameters.exclude_types.clone()),
+    ];
+    for (column, operator, list) in list_filters {
+        if !list.is_empty() {
+            let holders = vec!["?"; list.len()].join(", ");
+            predicates.push(format!(
+                "doc.{} COLLATE NOCASE {} ({})",
+                column, operator, holders
+            ));
+            values.extend(list.into_iter().map(Value::Text));
+        }
+    }
+
+    let prefixes = parameters
+        .path_prefixes
+        .iter()
+        .filter(|it| !it.is_empty())
+        .collect::<Vec<_>>();
+    if !prefixes.is_empty() {
+        let conditions = vec!["instr(doc.document_path, ?) = 1"; prefixes.len()];
+        predicates.push(format!("({})", conditions.join(" OR ")));
+        values.extend(prefixes.into_iter().cloned().map(Value::Text));
+    }
+
+    // Bucket path is not stored as column, so it is read from document source.
+    let path_globs = [
+        ("doc.document_path", parameters.document_path_regex()),
+        (
+            "json_extract(doc.source, '$.bucket_path')",
+            parameters.bucket_path_regex(),
+        ),
+    ];
+    for (column, regex) in path_globs {
+        if let Some(regex) = regex {
+            predicates.push(format!("{} REGEXP ?", column));
+            values.push(Value::Text(format!("^(?:{})$", regex)));
+        }
+    }
+
+    for prefix in parameters.exclude_paths.iter().filter(|it| !it.is_empty()) {
+        predicates.push("instr(doc.document_path, ?) <> 1".to_string());
+        values.push(Value::Text(prefix.clone()));
+    }
+
+    (predicates.join(" AND "), values)
+}
+
+pub fn document_values(document: &Document) -> Result<Vec<Value>, WebError> {
+    let mut source = document.clone();
+    source.highlight = None;
+    let source = serde_json::to_string(&source)
+        .map_err(|err| WebError::DocumentSerializing(err.to_string()))?;
+
+    let timestamp = |value: Option<i64>| value.map(Value::Integer).unwrap_or(Value::Null);
+    Ok(vec![
+        Value::Text(document.document_md5_hash.clone()),
+        Value::Text(document.document_name.clone()),
+        Value::Text(document.document_path.clone()),
+        Value::Integer(document.document_size as i64),
+        Value::Text(document.document_type.clone()),
+        Value::Text(document.document_extension.clone()),
+        Value::Text(document.document_ssdeep_hash.clone()),
+        Value::Text(document.entity_data.clone()),
+        timestamp(document.document_created.map(|dt| dt.timestamp())),
+        timestamp(document.document_modified.map(|dt| dt.timestamp())),
+        Value::Text(source),
+    ])
+}
+
+pub fn parse_document(source: &str) -> Result<Document, WebError> {
+    serde_json::from_str(source).map_err(|err| WebError::DocumentSerializing(err.to_string()))
+}
+
+pub fn load_saved_searches(connection: &Connection) -> Result<SavedSearches, WebError> {
+    let mut statement = connection.prepare("SELECT source FROM saved_searches")?;
+    let sources = statement
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    sources
+        .iter()
+        .map(|source| {
+            serde_json::from_str::<SavedSearch>(source)
+                .map_err(|err| WebError::StorageFailed(err.to_string()))
+        })
+        .collect()
+}
+
+pub fn extract_bucket_stats(bucket_name: &str, docs_count: i64, store_size: i64) -> Bucket {
+    let uuid = gen_hash(HashType::MD5, bucket_name.as_bytes())
+        .map(|hashed| hashed.get_hash_data().to_string())
+        .unwrap_or_default();
+
+    BucketBuilder::default()
+        .health("green".to_string())
+        .status("open".to_string())
+        .index(bucket_name.to_string())
+        .uuid(uuid)
+        .docs_count(docs_count.to_string())
+        .docs_deleted("0".to_string())
+        .store_size(store_size.to_string())
+        .pri_store_size(store_size.to_string())
+        .pri(None)
+        .rep(None)
+        .build()
+        .unwrap()
+}
+
+pub fn load_directory_entity(directory: &Path, bucket_id: &str) -> Vec<Document> {
+    file_loader::load_directory_entity(directory)
+        .into_iter()
+        .map(Document::from)
+        .map(|mut document| {
+            document.bucket_uuid = bucket_id.to_string();
+            document
+        })
+        .collect()
+}
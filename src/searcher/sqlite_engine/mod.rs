@@ -0,0 +1,25 @@
+pub mod client;
+pub mod context;
+pub mod engine;
+pub mod helper;
+
+use crate::searcher::sqlite_engine::context::SqliteContext;
+use crate::searcher::sqlite_engine::engine::SqliteEngine;
+use std::error::Error;
+use std::path::Path;
+
+const DATABASE_FILE_NAME: &str = "searcher.sqlite";
+
+pub type ClientBuildResult = Result<SqliteContext, Box<dyn Error>>;
+
+/// Builds SQLite engine client which keeps all buckets in a single database
+/// file of passed directory or in memory only if data directory is not
+/// specified.
+pub fn build_sqlite_client(data_dir: Option<&str>) -> ClientBuildResult {
+    let engine = match data_dir {
+        None => SqliteEngine::open_in_memory()?,
+        Some(data_dir) => SqliteEngine::open(&Path::new(data_dir).join(DATABASE_FILE_NAME))?,
+    };
+
+    Ok(SqliteContext::_new(engine))
+}
@@ -0,0 +1,20 @@
+use crate::searcher::tantivy_engine::engine::TantivyEngine;
+
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+#[derive(Default, Clone)]
+pub struct TantivyContext {
+    context: Arc<RwLock<TantivyEngine>>,
+}
+
+impl TantivyContext {
+    pub fn _new(engine: TantivyEngine) -> Self {
+        let context = Arc::new(RwLock::new(engine));
+        TantivyContext { context }
+    }
+
+    pub fn get_cxt(&self) -> &Arc<RwLock<TantivyEngine>> {
+        &self.context
+    }
+}
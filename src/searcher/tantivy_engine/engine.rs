@@ -0,0 +1,711 @@
+use crate::errors::WebError;
+use crate::searcher::tantivy_engine::helper::*;
+use crate::searcher::tantivy_engine::schema::{build_schema, DocumentFields};
+use crate::wrappers::aggregation::{aggregate_documents, Aggregations};
+use crate::wrappers::bucket::Bucket;
+use crate::wrappers::document::Document;
+use crate::wrappers::highlight::{highlight_document, HighlightField, HighlightOptions};
+use crate::wrappers::pattern::TextFilter;
+use crate::wrappers::saved_search::*;
+use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::search_response::SearchResponse;
+use crate::wrappers::suggest::*;
+
+use tantivy::collector::{Count, TopDocs};
+use tantivy::query::{Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy};
+use tantivy::{TantivyDocument, TantivyError, Term};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const WRITER_HEAP_SIZE: usize = 20_000_000;
+
+/// File of data directory where saved searches are stored, it is skipped
+/// while bucket indexes are opened.
+const SAVED_SEARCHES_FILE_NAME: &str = "saved_searches.json";
+
+/// Count of the most frequent terms of dictionary which documents are
+/// counted for each completion.
+const SUGGEST_CANDIDATES_FACTOR: usize = 4;
+
+pub type EngineResult<T> = Result<T, WebError>;
+
+/// Counts of documents containing query words and terms similar to them.
+type SimilarTerms = HashMap<String, (u64, Vec<(String, u64)>)>;
+
+/// Tantivy index of a single bucket with its own reader and writer.
+pub struct BucketIndex {
+    name: String,
+    index: Index,
+    fields: DocumentFields,
+    reader: IndexReader,
+    writer: IndexWriter,
+}
+
+impl BucketIndex {
+    fn new(name: &str, index: Index) -> EngineResult<Self> {
+        let fields = DocumentFields::from_schema(&index.schema())?;
+        let writer = index.writer_with_num_threads(1, WRITER_HEAP_SIZE)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+
+        Ok(BucketIndex {
+            name: name.to_string(),
+            index,
+            fields,
+            reader,
+            writer,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Returns count of documents to collect all of them for field sorting.
+    fn docs_count(&self) -> usize {
+        self.reader.searcher().num_docs().max(1) as usize
+    }
+
+    pub fn stats(&self) -> EngineResult<Bucket> {
+        extract_bucket_stats(self.name(), &self.reader.searcher())
+    }
+
+    fn find(&self, doc_id: &str) -> EngineResult<Option<Document>> {
+        let term = Term::from_field_text(self.fields.document_md5_hash, doc_id);
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let searcher = self.reader.searcher();
+        let founded = searcher.search(&query, &TopDocs::with_limit(1))?;
+        match founded.first() {
+            None => Ok(None),
+            Some((_, address)) => {
+                let doc = searcher.doc::<TantivyDocument>(*address)?;
+                Ok(self.fields.parse_document(&doc))
+            }
+        }
+    }
+
+    fn insert(&mut self, documents: &[&Document]) -> EngineResult<()> {
+        for document in documents {
+            let doc = self
+                .fields
+                .build_document(document)
+                .map_err(|err| WebError::DocumentSerializing(err.to_string()))?;
+
+            let id_term =
+                Term::from_field_text(self.fields.document_md5_hash, &document.document_md5_hash);
+            self.writer.delete_term(id_term);
+            self.writer.add_document(doc)?;
+        }
+
+        self.commit()
+    }
+
+    fn remove(&mut self, doc_id: &str) -> EngineResult<()> {
+        let id_term = Term::from_field_text(self.fields.document_md5_hash, doc_id);
+        self.writer.delete_term(id_term);
+        self.commit()
+    }
+
+    fn commit(&mut self) -> EngineResult<()> {
+        self.writer.commit()?;
+        self.reader.reload()?;
+        Ok(())
+    }
+
+    /// Returns count of all documents matched by passed query and the scored
+    /// ones. Limit is applied per bucket to merge results of several buckets
+    /// before pagination.
+    fn search(
+        &self,
+        query: &dyn Query,
+        limit: usize,
+        highlight: Option<&HighlightOptions>,
+    ) -> EngineResult<(usize, Vec<(f32, Document)>)> {
+        let searcher = self.reader.searcher();
+        let (total, founded) = searcher.search(query, &(Count, TopDocs::with_limit(limit)))?;
+        let highlight_terms = highlight
+            .map(|options| self.highlight_terms(query, options))
+            .unwrap_or_default();
+
+        let mut documents = Vec::with_capacity(founded.len());
+        for (score, address) in founded {
+            let doc = searcher.doc::<TantivyDocument>(address)?;
+            let Some(mut document) = self.fields.parse_document(&doc) else {
+                continue;
+            };
+
+            if let Some(options) = highlight {
+                let highlight = highlight_document(&document, &highlight_terms, options);
+                document.append_highlight(highlight);
+            }
+
+            documents.push((score, document));
+        }
+
+        Ok((total, documents))
+    }
+
+    /// Returns names of documents starting with passed lowercase prefix and
+    /// counts of documents. Names are taken from untokenized paths as far as
+    /// they are names of loaded files.
+    fn complete_names(&self, prefix: &str) -> EngineResult<Vec<(String, u64)>> {
+        let field = self.fields.document_path_raw;
+        let paths = self.dictionary_terms(field, "")?;
+        let mut names: HashMap<String, u64> = HashMap::new();
+        for path in paths.keys() {
+            let name = path.rsplit('/').next().unwrap_or(path);
+            if !name.to_lowercase().starts_with(prefix) {
+                continue;
+            }
+
+            let count = self.count_term(field, path)?;
+            if count > 0 {
+                *names.entry(name.to_string()).or_default() += count;
+            }
+        }
+
+        Ok(names.into_iter().collect())
+    }
+
+    /// Returns terms of documents text starting with passed prefix and
+    /// counts of documents containing them. Dictionary keeps terms of
+    /// deleted documents until segments are merged, so the most frequent
+    /// terms are counted by query again.
+    fn complete_terms(&self, prefix: &str, size: usize) -> EngineResult<Vec<(String, u64)>> {
+        let field = self.fields.entity_data;
+        let mut candidates = self
+            .dictionary_terms(field, prefix)?
+            .into_iter()
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(size * SUGGEST_CANDIDATES_FACTOR);
+
+        let mut terms = Vec::with_capacity(candidates.len());
+        for (term, _) in candidates {
+            let count = self.count_term(field, &term)?;
+            if count > 0 {
+                terms.push((term, count));
+            }
+        }
+
+        Ok(terms)
+    }
+
+    /// Returns terms of field dictionaries of all segments starting with
+    /// passed prefix and its documents frequencies.
+    fn dictionary_terms(&self, field: Field, prefix: &str) -> EngineResult<HashMap<String, u64>> {
+        // Byte 0xFF never occurs in UTF-8, so it follows any term with prefix.
+        let upper_bound = [prefix.as_bytes(), &[u8::MAX]].concat();
+        let mut terms: HashMap<String, u64> = HashMap::new();
+        for segment_reader in self.reader.searcher().segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let mut stream = inverted_index
+                .terms()
+                .range()
+                .ge(prefix.as_bytes())
+                .lt(&upper_bound)
+                .into_stream()
+                .map_err(TantivyError::from)?;
+
+            while stream.advance() {
+                if let Ok(term) = std::str::from_utf8(stream.key()) {
+                    let doc_freq = stream.value().doc_freq as u64;
+                    *terms.entry(term.to_string()).or_default() += doc_freq;
+                }
+            }
+        }
+
+        Ok(terms)
+    }
+
+    /// Returns count of documents containing word of documents text and
+    /// terms within edit distance of it with counts of its documents.
+    fn similar_terms(&self, words: &[String]) -> EngineResult<SimilarTerms> {
+        let field = self.fields.entity_data;
+        let terms = self.dictionary_terms(field, "")?;
+        let mut similar_terms = HashMap::with_capacity(words.len());
+        for word in words {
+            let word_count = self.count_term(field, word)?;
+            let mut similar = Vec::new();
+            for term in terms.keys() {
+                if term != word && edit_distance(word, term) <= max_edits(word) {
+                    let count = self.count_term(field, term)?;
+                    similar.push((term.to_string(), count));
+                }
+            }
+
+            similar_terms.insert(word.to_string(), (word_count, similar));
+        }
+
+        Ok(similar_terms)
+    }
+
+    fn count_term(&self, field: Field, text: &str) -> EngineResult<u64> {
+        let term = Term::from_field_text(field, text);
+        let query = TermQuery::new(term, IndexRecordOption::Basic);
+        let count = self.reader.searcher().search(&query, &Count)?;
+        Ok(count as u64)
+    }
+
+    /// Returns terms of query which are searched in highlighted fields.
+    fn highlight_terms(&self, query: &dyn Query, options: &HighlightOptions) -> HashSet<String> {
+        let fields = options
+            .fields
+            .iter()
+            .map(|field| match field {
+                HighlightField::EntityData => self.fields.entity_data,
+                HighlightField::DocumentName => self.fields.document_name,
+                HighlightField::DocumentPath => self.fields.document_path,
+            })
+            .collect::<Vec<_>>();
+
+        let mut terms = HashSet::new();
+        query.query_terms(&mut |term, _| {
+            if fields.contains(&term.field()) {
+                if let Some(text) = term.value().as_str() {
+                    terms.insert(text.to_string());
+                }
+            }
+        });
+
+        terms
+    }
+}
+
+/// Search engine which keeps each bucket in a separate tantivy index. Indexes
+/// are stored to subdirectories of data directory or in memory only.
+#[derive(Default)]
+pub struct TantivyEngine {
+    data_dir: Option<PathBuf>,
+    buckets: HashMap<String, BucketIndex>,
+    saved_searches: SavedSearches,
+}
+
+impl TantivyEngine {
+    pub fn new() -> Self {
+        TantivyEngine::default()
+    }
+
+    /// Opens all bucket indexes stored in passed directory.
+    pub fn open(data_dir: &Path) -> EngineResult<Self> {
+        fs::create_dir_all(data_dir).map_err(|err| WebError::StorageFailed(err.to_string()))?;
+        let mut engine = TantivyEngine {
+            data_dir: Some(data_dir.to_path_buf()),
+            ..Default::default()
+        };
+
+        let entries =
+            fs::read_dir(data_dir).map_err(|err| WebError::StorageFailed(err.to_string()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(bucket_id) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            if !path.join("meta.json").exists() {
+                continue;
+            }
+
+            let bucket = BucketIndex::new(bucket_id, Index::open_in_dir(&path)?)?;
+            engine.buckets.insert(bucket_id.to_string(), bucket);
+        }
+
+        let searches_path = data_dir.join(SAVED_SEARCHES_FILE_NAME);
+        if searches_path.exists() {
+            let data =
+                fs::read(searches_path).map_err(|err| WebError::StorageFailed(err.to_string()))?;
+            let searches: Vec<SavedSearch> = serde_json::from_slice(data.as_slice())
+                .map_err(|err| WebError::StorageFailed(err.to_string()))?;
+            engine.saved_searches = searches.into_iter().collect();
+        }
+
+        log::info!(
+            "Opened {} bucket indexes from {:?}",
+            engine.buckets.len(),
+            data_dir
+        );
+        Ok(engine)
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = &BucketIndex> {
+        self.buckets.values()
+    }
+
+    pub fn bucket(&self, bucket_id: &str) -> EngineResult<&BucketIndex> {
+        match self.buckets.get(bucket_id) {
+            Some(bucket) => Ok(bucket),
+            None => Err(WebError::GetBucket(format!(
+                "no such bucket: {}",
+                bucket_id
+            ))),
+        }
+    }
+
+    pub fn create_bucket(&mut self, bucket_id: &str) -> EngineResult<()> {
+        if self.buckets.contains_key(bucket_id) {
+            let msg = format!("bucket {} already exists", bucket_id);
+            return Err(WebError::CreateBucket(msg));
+        }
+
+        self.open_bucket(bucket_id).map(|_| ())
+    }
+
+    pub fn delete_bucket(&mut self, bucket_id: &str) -> EngineResult<()> {
+        let Some(bucket) = self.buckets.remove(bucket_id) else {
+            let msg = format!("no such bucket: {}", bucket_id);
+            return Err(WebError::DeleteBucket(msg));
+        };
+
+        drop(bucket);
+        if let Some(data_dir) = self.data_dir.as_ref() {
+            fs::remove_dir_all(data_dir.join(bucket_id))
+                .map_err(|err| WebError::DeleteBucket(err.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn saved_searches(&self) -> &SavedSearches {
+        &self.saved_searches
+    }
+
+    /// Stores new or changed saved search which replaces the one with the
+    /// same id.
+    pub fn save_search(&mut self, search: SavedSearch) -> EngineResult<SavedSearch> {
+        self.saved_searches.insert(search.clone());
+        self.store_saved_searches()?;
+        Ok(search)
+    }
+
+    pub fn delete_saved_search(&mut self, id_or_name: &str) -> EngineResult<()> {
+        let search_id = self.saved_searches.get(id_or_name)?.id.clone();
+        self.saved_searches.remove(&search_id);
+        self.store_saved_searches()
+    }
+
+    /// Rewrites file of saved searches, the file is replaced atomically so
+    /// it is not broken by crash while writing.
+    fn store_saved_searches(&self) -> EngineResult<()> {
+        let Some(data_dir) = self.data_dir.as_ref() else {
+            return Ok(());
+        };
+
+        let storage_failed = |err: std::io::Error| WebError::StorageFailed(err.to_string());
+        let path = data_dir.join(SAVED_SEARCHES_FILE_NAME);
+        let tmp_path = path.with_extension("tmp");
+        let data = serde_json::to_vec(&self.saved_searches.all())
+            .map_err(|err| WebError::StorageFailed(err.to_string()))?;
+        fs::write(&tmp_path, data).map_err(storage_failed)?;
+        fs::rename(&tmp_path, &path).map_err(storage_failed)
+    }
+
+    pub fn get_document(&self, bucket_id: &str, doc_id: &str) -> EngineResult<Document> {
+        let document = match self.buckets.get(bucket_id) {
+            Some(bucket) => bucket.find(doc_id)?,
+            None => None,
+        };
+
+        match document {
+            Some(document) => Ok(document),
+            None => Err(WebError::GetDocument(format!(
+                "no such document: {}",
+                doc_id
+            ))),
+        }
+    }
+
+    pub fn contains_document(&self, bucket_id: &str, doc_id: &str) -> bool {
+        self.get_document(bucket_id, doc_id).is_ok()
+    }
+
+    /// Stores passed documents to the buckets with names from `bucket_uuid`
+    /// field and replaces existing ones with the same id. The bucket is
+    /// created if it does not exist.
+    pub fn index_documents(&mut self, documents: &[Document]) -> EngineResult<()> {
+        let mut grouped: HashMap<&str, Vec<&Document>> = HashMap::new();
+        for document in documents {
+            grouped
+                .entry(document.bucket_uuid.as_str())
+                .or_default()
+                .push(document);
+        }
+
+        for (bucket_id, documents) in grouped {
+            self.open_bucket(bucket_id)?.insert(&documents)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn delete_document(&mut self, bucket_id: &str, doc_id: &str) -> EngineResult<()> {
+        if !self.contains_document(bucket_id, doc_id) {
+            let msg = format!("no such document: {}", doc_id);
+            return Err(WebError::DeleteDocument(msg));
+        }
+
+        match self.buckets.get_mut(bucket_id) {
+            Some(bucket) => bucket.remove(doc_id),
+            None => Ok(()),
+        }
+    }
+
+    pub fn search(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> EngineResult<SearchResponse> {
+        let text_filter = TextFilter::new(parameters);
+        let mut search_response =
+            self.collect_documents(buckets_ids, parameters, true, &text_filter, |bucket| {
+                build_search_query(bucket, parameters)
+            })?;
+
+        let query = parameters.query.as_str();
+        if is_low_result(search_response.total, query) {
+            search_response.did_you_mean = self.suggest_corrections(buckets_ids, query)?;
+        }
+
+        Ok(search_response)
+    }
+
+    /// Suggests query where words of documents text which are missing or
+    /// rare are replaced by close frequent terms of dictionaries.
+    fn suggest_corrections(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        query: &str,
+    ) -> EngineResult<Vec<QuerySuggestion>> {
+        let mut words = tokenize_terms(query);
+        words.sort();
+        words.dedup();
+
+        let mut candidates = SimilarTerms::new();
+        for bucket in self.select_buckets(buckets_ids) {
+            for (word, (count, similar)) in bucket.similar_terms(&words)? {
+                let (word_count, merged) = candidates.entry(word).or_default();
+                *word_count += count;
+                merged.extend(similar);
+            }
+        }
+
+        let suggestion = correct_query(query, |word| {
+            let (word_count, similar) = candidates.get(word)?;
+            let similar = similar.iter().map(|(term, count)| (term.as_str(), *count));
+            best_correction(word, *word_count, similar)
+        });
+
+        Ok(suggestion.into_iter().collect())
+    }
+
+    /// Counts all documents matched by passed parameters by its facets.
+    pub fn aggregate(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> EngineResult<Aggregations> {
+        if parameters.aggregations.is_empty() {
+            return Ok(Aggregations::default());
+        }
+
+        let mut documents = Vec::new();
+        let text_filter = TextFilter::new(parameters);
+        for bucket in self.select_buckets(buckets_ids) {
+            let query = build_search_query(bucket, parameters);
+            let (_, founded) = bucket.search(query.as_ref(), bucket.docs_count(), None)?;
+            let founded = founded.into_iter().map(|(_, document)| document);
+            documents.extend(founded.filter(|doc| text_filter.matches(doc)));
+        }
+
+        let (facets, interval) = (&parameters.aggregations, parameters.date_interval);
+        Ok(aggregate_documents(facets, interval, documents.iter()))
+    }
+
+    pub fn similar(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+    ) -> EngineResult<SearchResponse> {
+        let text_filter = TextFilter::default();
+        self.collect_documents(buckets_ids, parameters, false, &text_filter, |bucket| {
+            let query = build_similar_query(&bucket.fields, &parameters.query);
+            build_query(query, build_filter_queries(&bucket.fields, parameters))
+        })
+    }
+
+    /// Completes prefix by the most frequent names of documents and terms
+    /// of documents text of every bucket.
+    pub fn suggest(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        form: &SuggestForm,
+    ) -> EngineResult<Vec<Suggestion>> {
+        let name_prefix = form.name_prefix();
+        let term_prefix = form.term_prefix();
+        let mut suggestions = Vec::new();
+        for bucket in self.select_buckets(buckets_ids) {
+            let names = bucket.complete_names(&name_prefix)?;
+            suggestions.extend(
+                names
+                    .into_iter()
+                    .map(|(text, count)| Suggestion::new(&text, SuggestField::DocumentName, count)),
+            );
+
+            if let Some(term_prefix) = term_prefix.as_deref() {
+                let terms = bucket.complete_terms(term_prefix, form.size)?;
+                suggestions.extend(
+                    terms.into_iter().map(|(text, count)| {
+                        Suggestion::new(&text, SuggestField::EntityData, count)
+                    }),
+                );
+            }
+        }
+
+        Ok(merge_suggestions(suggestions, form.size))
+    }
+
+    fn open_bucket(&mut self, bucket_id: &str) -> EngineResult<&mut BucketIndex> {
+        if !self.buckets.contains_key(bucket_id) {
+            let index = self.create_index(bucket_id)?;
+            let bucket = BucketIndex::new(bucket_id, index)?;
+            self.buckets.insert(bucket_id.to_string(), bucket);
+        }
+
+        Ok(self.buckets.get_mut(bucket_id).unwrap())
+    }
+
+    fn create_index(&self, bucket_id: &str) -> EngineResult<Index> {
+        let Some(data_dir) = self.data_dir.as_ref() else {
+            return Ok(Index::create_in_ram(build_schema()));
+        };
+
+        let is_valid_name = !bucket_id.is_empty()
+            && !bucket_id.starts_with('.')
+            && !bucket_id.contains(['/', '\\']);
+        if !is_valid_name {
+            let msg = format!("invalid bucket name: {}", bucket_id);
+            return Err(WebError::CreateBucket(msg));
+        }
+
+        let index_dir = data_dir.join(bucket_id);
+        fs::create_dir_all(&index_dir).map_err(|err| WebError::StorageFailed(err.to_string()))?;
+        Ok(Index::create_in_dir(index_dir, build_schema())?)
+    }
+
+    fn select_buckets(&self, buckets_ids: Option<&[&str]>) -> Vec<&BucketIndex> {
+        match buckets_ids {
+            None => self.buckets.values().collect(),
+            Some(ids) => ids.iter().filter_map(|id| self.buckets.get(*id)).collect(),
+        }
+    }
+
+    fn collect_documents<F>(
+        &self,
+        buckets_ids: Option<&[&str]>,
+        parameters: &SearchParams,
+        with_highlight: bool,
+        text_filter: &TextFilter,
+        build_query: F,
+    ) -> EngineResult<SearchResponse>
+    where
+        F: Fn(&BucketIndex) -> Box<dyn Query>,
+    {
+        let started = Instant::now();
+        let offset = parameters.result_offset.max(0) as usize;
+        let size = parameters.result_size.max(0) as usize;
+        if size == 0 {
+            return Ok(SearchResponse::default());
+        }
+
+        let (mut total, mut scored) = (0, Vec::new());
+        for bucket in self.select_buckets(buckets_ids) {
+            let query = build_query(bucket);
+            // Text filter is checked over all matched documents.
+            let limit = match parameters.is_sorted_by_fields() || !text_filter.is_empty() {
+                true => bucket.docs_count(),
+                false => offset + size,
+            };
+            let highlight = with_highlight.then_some(&parameters.highlight);
+            let (mut founded_total, mut founded) =
+                bucket.search(query.as_ref(), limit, highlight)?;
+            if !text_filter.is_empty() {
+                founded.retain(|(_, doc)| text_filter.matches(doc));
+                founded_total = founded.len();
+            }
+
+            total += founded_total as u64;
+            scored.extend(founded.into_iter().map(|(score, doc)| (score as f64, doc)));
+        }
+
+        scored.sort_by(|a, b| parameters.compare_documents((a.0, &a.1), (b.0, &b.1)));
+
+        let mut search_response = SearchResponse::paginate(total, scored, parameters);
+        search_response.timed_out = text_filter.is_timed_out();
+        search_response.took = started.elapsed().as_millis() as u64;
+        Ok(search_response)
+    }
+}
+
+fn build_search_query(bucket: &BucketIndex, parameters: &SearchParams) -> Box<dyn Query> {
+    let query = build_text_query(&bucket.index, &bucket.fields, &parameters.query);
+    build_query(query, build_filter_queries(&bucket.fields, parameters))
+}
+
+#[cfg(test)]
+mod engine_tests {
+    use super::*;
+    use crate::searcher::backend_tests::{build_documents, search_params};
+
+    #[test]
+    fn restore_persisted_engine() {
+        let data_dir = std::env::temp_dir().join("searcher_tantivy_reopen");
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let mut engine = TantivyEngine::open(&data_dir).unwrap();
+        engine.index_documents(&build_documents()).unwrap();
+        engine.create_bucket("empty_bucket").unwrap();
+        drop(engine);
+
+        let engine = TantivyEngine::open(&data_dir).unwrap();
+        assert_eq!(engine.buckets().count(), 2);
+        let founded = engine.search(None, &search_params("denisov")).unwrap().hits;
+        assert_eq!(founded.len(), 1);
+        assert_eq!(founded[0].document_md5_hash, "third");
+    }
+
+    #[test]
+    fn restore_saved_searches() {
+        let data_dir = std::env::temp_dir().join("searcher_tantivy_saved_searches");
+        let _ = fs::remove_dir_all(&data_dir);
+
+        let mut engine = TantivyEngine::open(&data_dir).unwrap();
+        let form = SavedSearchForm {
+            name: "denisov".to_string(),
+            owner: "analyst".to_string(),
+            buckets: None,
+            params: search_params("denisov"),
+        };
+        let search = engine.saved_searches().created(&form).unwrap();
+        let search = engine.save_search(search).unwrap();
+        let ran = engine.saved_searches().ran("denisov").unwrap();
+        engine.save_search(ran).unwrap();
+        drop(engine);
+
+        let mut engine = TantivyEngine::open(&data_dir).unwrap();
+        assert_eq!(engine.buckets().count(), 0);
+        let restored = engine.saved_searches().get(&search.id).unwrap();
+        assert_eq!(restored.params.query, "denisov");
+        assert!(restored.last_run.is_some());
+
+        engine.delete_saved_search("denisov").unwrap();
+        drop(engine);
+        let engine = TantivyEngine::open(&data_dir).unwrap();
+        assert!(engine.saved_searches().all().is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+use crate::errors::WebError;
+use crate::searcher::tantivy_engine::schema::{to_tantivy_date, DocumentFields};
+use crate::wrappers::bucket::{Bucket, BucketBuilder};
+use crate::wrappers::document::Document;
+use crate::wrappers::search_params::SearchParams;
+
+use chrono::{DateTime, Utc};
+use datetime::{parse_datetime, parse_datetime_end};
+use hasher::{gen_hash, HashType};
+use tantivy::query::*;
+use tantivy::schema::{Field, IndexRecordOption, OwnedValue};
+use tantivy::{Index, Searcher, TantivyError, Term};
+
+use std::ops::Bound;
+use std::path::Path;
+
+const MAX_QUERY_TERMS: usize = 25;
+
+impl From<TantivyError> for WebError {
+    fn from(value: TantivyError) -> Self {
+        WebError::ResponseError(value.to_string())
+    }
+}
+
+pub fn is_match_all(query: &str) -> bool {
+    let query = query.trim();
+    query.is_empty() || query == "*"
+}
+
+/// Splits text to lowercase words like the default tokenizer does.
+pub fn tokenize_terms(text: &str) -> Vec<String> {
+    text.split(|symbol: char| !symbol.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Translates `multi_match` query of `best_fields` type: the score of
+/// document is the best score of query parsed for each field separately.
+pub fn build_text_query(index: &Index, fields: &DocumentFields, query: &str) -> Box<dyn Query> {
+    if is_match_all(query) {
+        return Box::new(AllQuery);
+    }
+
+    let disjuncts = [fields.entity_data, fields.document_path]
+        .into_iter()
+        .map(|field| {
+            let query_parser = QueryParser::for_index(index, vec![field]);
+            let (parsed, _errors) = query_parser.parse_query_lenient(query);
+            parsed
+        })
+        .collect();
+
+    Box::new(DisjunctionMaxQuery::new(disjuncts))
+}
+
+/// Translates `more_like_this` query over `entity_data` field with the
+/// same options as elastic query has, extended by exact match of ssdeep hash.
+pub fn build_similar_query(fields: &DocumentFields, like: &str) -> Box<dyn Query> {
+    let more_like_this = MoreLikeThisQuery::builder()
+        .with_min_doc_frequency(1)
+        .with_min_term_frequency(1)
+        .with_max_query_terms(MAX_QUERY_TERMS)
+        .with_document_fields(vec![(
+            fields.entity_data,
+            vec![OwnedValue::Str(like.to_string())],
+        )]);
+
+    let ssdeep_term = Term::from_field_text(fields.document_ssdeep_hash, like);
+    let ssdeep_query = TermQuery::new(ssdeep_term, IndexRecordOption::Basic);
+    Box::new(BooleanQuery::new(vec![
+        (Occur::Should, Box::new(more_like_this)),
+        (Occur::Should, Box::new(ssdeep_query)),
+    ]))
+}
+
+/// Translates `CommonFilter` of search parameters to the filter clauses
+/// which do not affect score of found documents.
+pub fn build_filter_queries(
+    fields: &DocumentFields,
+    parameters: &SearchParams,
+) -> Vec<Box<dyn Query>> {
+    let mut filters: Vec<Box<dyn Query>> = Vec::new();
+
+    let doc_size_to = match parameters.document_size_to {
+        0 => Bound::Unbounded,
+        value => Bound::Included(value),
+    };
+    let doc_size_from = Bound::Included(parameters.document_size_from);
+    filters.push(Box::new(RangeQuery::new_i64_bounds(
+        "document_size".to_string(),
+        doc_size_from,
+        doc_size_to,
+    )));
+
+    let date_filters = [
+        (
+            "document_created",
+            parameters.created_date_from.as_str(),
+            parameters.created_date_to.as_str(),
+        ),
+        (
+            "document_modified",
+            parameters.modified_date_from.as_str(),
+            parameters.modified_date_to.as_str(),
+        ),
+    ];
+    for (field, gte, lte) in date_filters {
+        if gte.is_empty() && lte.is_empty() {
+            continue;
+        }
+
+        filters.push(Box::new(RangeQuery::new_date_bounds(
+            field.to_string(),
+            date_bound(parse_datetime(gte)),
+            date_bound(parse_datetime_end(lte)),
+        )));
+    }
+
+    let term_filters = [
+        (fields.document_extension, parameters.document_extension()),
+        (fields.document_type, parameters.document_type.clone()),
+    ];
+    for (field, value) in term_filters {
+        if value.is_empty() {
+            continue;
+        }
+
+        filters.push(build_term_query(field, &value));
+    }
+
+    let any_filters = [
+        (fields.document_extension, parameters.any_extensions()),
+        (fields.document_type, parameters.any_types.clone()),
+    ];
+    for (field, values) in any_filters {
+        if !values.is_empty() {
+            filters.push(build_terms_query(field, &values));
+        }
+    }
+
+    let prefixes = parameters.path_prefixes.iter().filter(|it| !it.is_empty());
+    let prefix_clauses = prefixes
+        .map(|prefix| {
+            let query = build_prefix_query(fields.document_path_raw, prefix);
+            (Occur::Should, query)
+        })
+        .collect::<Vec<_>>();
+    if !prefix_clauses.is_empty() {
+        filters.push(Box::new(BooleanQuery::new(prefix_clauses)));
+    }
+
+    let path_globs = [
+        (fields.document_path_raw, parameters.document_path_regex()),
+        (fields.bucket_path, parameters.bucket_path_regex()),
+    ];
+    for (field, regex) in path_globs {
+        let Some(regex) = regex else {
+            continue;
+        };
+
+        match RegexQuery::from_pattern(&regex, field) {
+            Ok(query) => filters.push(Box::new(query)),
+            Err(_) => filters.push(Box::new(EmptyQuery)),
+        }
+    }
+
+    let mut exclusions = Vec::new();
+    let exclude_filters = [
+        (fields.document_extension, parameters.exclude_extensions()),
+        (fields.document_type, parameters.exclude_types.clone()),
+    ];
+    for (field, values) in exclude_filters {
+        if !values.is_empty() {
+            exclusions.push(build_terms_query(field, &values));
+        }
+    }
+
+    for prefix in parameters.exclude_paths.iter().filter(|it| !it.is_empty()) {
+        exclusions.push(build_prefix_query(fields.document_path_raw, prefix));
+    }
+
+    if !exclusions.is_empty() {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, Box::new(AllQuery))];
+        clauses.extend(exclusions.into_iter().map(|query| (Occur::MustNot, query)));
+        filters.push(Box::new(BooleanQuery::new(clauses)));
+    }
+
+    filters
+        .into_iter()
+        .map(|filter| Box::new(ConstScoreQuery::new(filter, 0.0)) as Box<dyn Query>)
+        .collect()
+}
+
+fn date_bound(value: Option<DateTime<Utc>>) -> Bound<tantivy::DateTime> {
+    value
+        .map(|value| Bound::Included(to_tantivy_date(&value)))
+        .unwrap_or(Bound::Unbounded)
+}
+
+/// Builds query over keyword field which is indexed in lower case.
+fn build_term_query(field: Field, value: &str) -> Box<dyn Query> {
+    let term = Term::from_field_text(field, &value.to_lowercase());
+    Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+}
+
+fn build_terms_query(field: Field, values: &[String]) -> Box<dyn Query> {
+    let terms = values
+        .iter()
+        .map(|value| Term::from_field_text(field, &value.to_lowercase()))
+        .collect::<Vec<_>>();
+    Box::new(TermSetQuery::new(terms))
+}
+
+fn build_prefix_query(field: Field, prefix: &str) -> Box<dyn Query> {
+    let pattern = format!("{}.*", escape_regex(prefix));
+    match RegexQuery::from_pattern(&pattern, field) {
+        Ok(query) => Box::new(query),
+        Err(_) => Box::new(EmptyQuery),
+    }
+}
+
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for symbol in value.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(symbol) {
+            escaped.push('\\');
+        }
+        escaped.push(symbol);
+    }
+    escaped
+}
+
+pub fn build_query(query: Box<dyn Query>, filters: Vec<Box<dyn Query>>) -> Box<dyn Query> {
+    let mut clauses = vec![(Occur::Must, query)];
+    clauses.extend(filters.into_iter().map(|filter| (Occur::Must, filter)));
+    Box::new(BooleanQuery::new(clauses))
+}
+
+pub fn extract_bucket_stats(bucket_name: &str, searcher: &Searcher) -> Result<Bucket, WebError> {
+    let store_size = searcher
+        .space_usage()
+        .map_err(|err| WebError::GetBucket(err.to_string()))?
+        .total()
+        .get_bytes();
+    let docs_count = searcher.num_docs();
+    let docs_deleted: u64 = searcher
+        .segment_readers()
+        .iter()
+        .map(|segment| segment.num_deleted_docs() as u64)
+        .sum();
+
+    let uuid = gen_hash(HashType::MD5, bucket_name.as_bytes())
+        .map(|hashed| hashed.get_hash_data().to_string())
+        .unwrap_or_default();
+
+    Ok(BucketBuilder::default()
+        .health("green".to_string())
+        .status("open".to_string())
+        .index(bucket_name.to_string())
+        .uuid(uuid)
+        .docs_count(docs_count.to_string())
+        .docs_deleted(docs_deleted.to_string())
+        .store_size(store_size.to_string())
+        .pri_store_size(store_size.to_string())
+        .pri(None)
+        .rep(None)
+        .build()
+        .unwrap())
+}
+
+pub fn load_directory_entity(directory: &Path, bucket_id: &str) -> Vec<Document> {
+    file_loader::load_directory_entity(directory)
+        .into_iter()
+        .map(Document::from)
+        .map(|mut document| {
+            document.bucket_uuid = bucket_id.to_string();
+            document
+        })
+        .collect()
+}
@@ -0,0 +1,23 @@
+pub mod client;
+pub mod context;
+pub mod engine;
+pub mod helper;
+mod schema;
+
+use crate::searcher::tantivy_engine::context::TantivyContext;
+use crate::searcher::tantivy_engine::engine::TantivyEngine;
+use std::error::Error;
+use std::path::Path;
+
+pub type ClientBuildResult = Result<TantivyContext, Box<dyn Error>>;
+
+/// Builds tantivy engine client which keeps bucket indexes in passed
+/// directory or in memory only if data directory is not specified.
+pub fn build_tantivy_client(data_dir: Option<&str>) -> ClientBuildResult {
+    let engine = match data_dir {
+        None => TantivyEngine::new(),
+        Some(data_dir) => TantivyEngine::open(Path::new(data_dir))?,
+    };
+
+    Ok(TantivyContext::_new(engine))
+}
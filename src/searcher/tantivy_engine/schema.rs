@@ -0,0 +1,116 @@
+use crate::wrappers::document::Document;
+
+use chrono::{DateTime, Utc};
+use tantivy::schema::{Field, Schema, Value, FAST, INDEXED, STORED, STRING, TEXT};
+use tantivy::TantivyDocument;
+
+/// Fields of bucket index schema which mirrors `Document` fields. The whole
+/// document is also stored as json to `_source` field like elastic does.
+#[derive(Clone)]
+pub struct DocumentFields {
+    pub bucket_uuid: Field,
+    pub bucket_path: Field,
+    pub document_name: Field,
+    pub document_path: Field,
+    pub document_path_raw: Field,
+    pub document_size: Field,
+    pub document_type: Field,
+    pub document_extension: Field,
+    pub document_md5_hash: Field,
+    pub document_ssdeep_hash: Field,
+    pub entity_data: Field,
+    pub entity_keywords: Field,
+    pub document_created: Field,
+    pub document_modified: Field,
+    pub source: Field,
+}
+
+impl DocumentFields {
+    pub fn from_schema(schema: &Schema) -> tantivy::Result<Self> {
+        Ok(DocumentFields {
+            bucket_uuid: schema.get_field("bucket_uuid")?,
+            bucket_path: schema.get_field("bucket_path")?,
+            document_name: schema.get_field("document_name")?,
+            document_path: schema.get_field("document_path")?,
+            document_path_raw: schema.get_field("document_path_raw")?,
+            document_size: schema.get_field("document_size")?,
+            document_type: schema.get_field("document_type")?,
+            document_extension: schema.get_field("document_extension")?,
+            document_md5_hash: schema.get_field("document_md5_hash")?,
+            document_ssdeep_hash: schema.get_field("document_ssdeep_hash")?,
+            entity_data: schema.get_field("entity_data")?,
+            entity_keywords: schema.get_field("entity_keywords")?,
+            document_created: schema.get_field("document_created")?,
+            document_modified: schema.get_field("document_modified")?,
+            source: schema.get_field("_source")?,
+        })
+    }
+
+    pub fn build_document(
+        &self,
+        document: &Document,
+    ) -> Result<TantivyDocument, serde_json::Error> {
+        let mut doc = TantivyDocument::default();
+        doc.add_text(self.bucket_uuid, &document.bucket_uuid);
+        doc.add_text(self.bucket_path, &document.bucket_path);
+        doc.add_text(self.document_name, &document.document_name);
+        doc.add_text(self.document_path, &document.document_path);
+        doc.add_text(self.document_path_raw, &document.document_path);
+        doc.add_i64(self.document_size, document.document_size as i64);
+        // Types and extensions are filtered ignoring case like other engines do.
+        doc.add_text(self.document_type, document.document_type.to_lowercase());
+        doc.add_text(
+            self.document_extension,
+            document.document_extension.to_lowercase(),
+        );
+        doc.add_text(self.document_md5_hash, &document.document_md5_hash);
+        doc.add_text(self.document_ssdeep_hash, &document.document_ssdeep_hash);
+        doc.add_text(self.entity_data, &document.entity_data);
+        for keyword in document.entity_keywords.iter() {
+            doc.add_text(self.entity_keywords, keyword);
+        }
+
+        if let Some(created) = document.document_created.as_ref() {
+            doc.add_date(self.document_created, to_tantivy_date(created));
+        }
+
+        if let Some(modified) = document.document_modified.as_ref() {
+            doc.add_date(self.document_modified, to_tantivy_date(modified));
+        }
+
+        let mut source = document.clone();
+        source.highlight = None;
+        doc.add_text(self.source, serde_json::to_string(&source)?);
+        Ok(doc)
+    }
+
+    pub fn parse_document(&self, doc: &TantivyDocument) -> Option<Document> {
+        let source = doc.get_first(self.source)?.as_str()?;
+        serde_json::from_str(source).ok()
+    }
+}
+
+pub fn build_schema() -> Schema {
+    let mut schema_builder = Schema::builder();
+    schema_builder.add_text_field("bucket_uuid", STRING);
+    schema_builder.add_text_field("bucket_path", STRING);
+    schema_builder.add_text_field("document_name", TEXT);
+    schema_builder.add_text_field("document_path", TEXT);
+    // Untokenized copy of path which is used by prefix filters.
+    schema_builder.add_text_field("document_path_raw", STRING);
+    schema_builder.add_i64_field("document_size", INDEXED | FAST);
+    schema_builder.add_text_field("document_type", STRING);
+    schema_builder.add_text_field("document_extension", STRING);
+    schema_builder.add_text_field("document_md5_hash", STRING);
+    schema_builder.add_text_field("document_ssdeep_hash", STRING);
+    schema_builder.add_text_field("entity_data", TEXT);
+    schema_builder.add_text_field("entity_keywords", STRING);
+    schema_builder.add_date_field("document_created", INDEXED | FAST);
+    schema_builder.add_date_field("document_modified", INDEXED | FAST);
+    schema_builder.add_text_field("_source", STORED);
+    schema_builder.build()
+}
+
+pub fn to_tantivy_date(value: &DateTime<Utc>) -> tantivy::DateTime {
+    tantivy::DateTime::from_timestamp_secs(value.timestamp())
+}
@@ -5,7 +5,13 @@ use crate::endpoints::clusters::{all_clusters, delete_cluster, get_cluster, new_
 use crate::endpoints::documents::{delete_document, get_document, new_document, update_document};
 use crate::endpoints::hello::hello;
 use crate::endpoints::loader::{load_file, upload_file};
-use crate::endpoints::searcher::{search_all, search_target};
+use crate::endpoints::saved_searches::{
+    all_saved_searches, delete_saved_search, get_saved_search, new_saved_search, run_saved_search,
+    update_saved_search,
+};
+use crate::endpoints::searcher::{
+    close_cursor, export_documents, search_all, search_target, suggest,
+};
 use crate::endpoints::similarities::{search_similar_docs, search_similar_docs_target};
 
 use actix_cors::Cors;
@@ -14,32 +20,185 @@ use actix_web::{web, Scope};
 use dotenv::dotenv;
 
 use derive_builder::Builder;
+#[cfg(feature = "elastic-search")]
+use serde_derive::Deserialize;
 use std::env::var;
+use std::fmt::Display;
 use std::str::FromStr;
+#[cfg(feature = "elastic-search")]
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SearchBackend {
+    #[default]
+    Elastic,
+    Embedded,
+    Tantivy,
+    Sqlite,
+    Federated,
+}
+
+impl FromStr for SearchBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "elastic" | "elasticsearch" => Ok(SearchBackend::Elastic),
+            "embedded" | "own" => Ok(SearchBackend::Embedded),
+            "tantivy" => Ok(SearchBackend::Tantivy),
+            "sqlite" => Ok(SearchBackend::Sqlite),
+            "federated" => Ok(SearchBackend::Federated),
+            _ => Err(anyhow::anyhow!("Unknown search backend: {}", value)),
+        }
+    }
+}
+
+impl Display for SearchBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SearchBackend::Elastic => "elastic",
+            SearchBackend::Embedded => "embedded",
+            SearchBackend::Tantivy => "tantivy",
+            SearchBackend::Sqlite => "sqlite",
+            SearchBackend::Federated => "federated",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Authentication method of elastic requests. Basic authentication uses
+/// `ELASTIC_USER` and `ELASTIC_PASSWORD` values.
+#[cfg(feature = "elastic-search")]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ElasticAuth {
+    #[default]
+    Basic,
+    Bearer {
+        token: String,
+    },
+    ApiKey {
+        id: String,
+        key: String,
+    },
+    None,
+}
+
+/// Validation mode of certificate provided by elastic node. The `full` and
+/// `certificate` modes require CA certificate, which the server certificate
+/// must be signed by, and `certificate` mode skips hostname verification.
+#[cfg(feature = "elastic-search")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertValidation {
+    #[default]
+    Default,
+    Full,
+    Certificate,
+    None,
+}
+
+#[cfg(feature = "elastic-search")]
+impl FromStr for CertValidation {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "default" => Ok(CertValidation::Default),
+            "full" => Ok(CertValidation::Full),
+            "certificate" => Ok(CertValidation::Certificate),
+            "none" => Ok(CertValidation::None),
+            _ => Err(anyhow::anyhow!("Unknown certificate validation: {}", value)),
+        }
+    }
+}
+
+/// TLS settings of elastic transport. Client certificate is either PKCS#12
+/// archive or PEM certificate with PEM private key passed by `client_key`.
+#[cfg(feature = "elastic-search")]
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ElasticTls {
+    #[serde(default)]
+    pub cert_validation: CertValidation,
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    #[serde(default)]
+    pub client_cert: Option<String>,
+    #[serde(default)]
+    pub client_key: Option<String>,
+    #[serde(default)]
+    pub client_cert_passwd: Option<String>,
+}
 
 #[derive(Builder)]
 pub struct ServiceParameters {
+    search_backend: SearchBackend,
+    #[cfg(feature = "elastic-search")]
     es_host: String,
+    #[cfg(feature = "elastic-search")]
     es_user: String,
+    #[cfg(feature = "elastic-search")]
     es_passwd: String,
+    #[cfg(feature = "elastic-search")]
+    es_auth: ElasticAuth,
+    #[cfg(feature = "elastic-search")]
+    es_tls: ElasticTls,
+    #[cfg(feature = "elastic-search")]
+    es_sniff_interval: Option<Duration>,
+    #[cfg(feature = "elastic-search")]
+    es_match_config: Option<String>,
     service_addr: String,
     service_port: u16,
     cors_origin: String,
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    data_dir: Option<String>,
+    federated_config: Option<String>,
 }
 
 impl ServiceParameters {
+    pub fn search_backend(&self) -> SearchBackend {
+        self.search_backend
+    }
+
+    #[cfg(feature = "elastic-search")]
     pub fn es_host(&self) -> &str {
         self.es_host.as_str()
     }
 
+    #[cfg(feature = "elastic-search")]
     pub fn es_user(&self) -> &str {
         self.es_user.as_str()
     }
 
+    #[cfg(feature = "elastic-search")]
     pub fn es_passwd(&self) -> &str {
         self.es_passwd.as_str()
     }
 
+    #[cfg(feature = "elastic-search")]
+    pub fn es_auth(&self) -> &ElasticAuth {
+        &self.es_auth
+    }
+
+    #[cfg(feature = "elastic-search")]
+    pub fn es_tls(&self) -> &ElasticTls {
+        &self.es_tls
+    }
+
+    #[cfg(feature = "elastic-search")]
+    pub fn es_sniff_interval(&self) -> Option<Duration> {
+        self.es_sniff_interval
+    }
+
+    #[cfg(feature = "elastic-search")]
+    pub fn es_match_config(&self) -> Option<&str> {
+        self.es_match_config.as_deref()
+    }
+
     pub fn service_address(&self) -> &str {
         self.service_addr.as_str()
     }
@@ -51,33 +210,112 @@ impl ServiceParameters {
     pub fn cors_origin(&self) -> String {
         self.cors_origin.clone()
     }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn data_dir(&self) -> Option<&str> {
+        self.data_dir.as_deref()
+    }
+
+    pub fn federated_config(&self) -> Option<&str> {
+        self.federated_config.as_deref()
+    }
 }
 
 pub fn init_service_parameters() -> Result<ServiceParameters, anyhow::Error> {
     dotenv().ok();
     build_env_logger();
 
-    let es_host = var("ELASTIC_HOST").expect("There is not ELASTIC_HOST env variable!");
-    let es_user = var("ELASTIC_USER").expect("There is no ELASTIC_USER env variable!");
-    let es_passwd = var("ELASTIC_PASSWORD").expect("There is not ELASTIC_PASSWORD env variable!");
+    let search_backend = match var("SEARCH_BACKEND") {
+        Ok(backend) => SearchBackend::from_str(backend.as_str())?,
+        Err(_) => SearchBackend::default(),
+    };
+
     let client_addr = var("SEARCHER_ADDRESS").expect("There is not SEARCHER_ADDRESS env variable!");
     let client_port = var("SEARCHER_PORT").expect("There is not SEARCHER_PORT env variable!");
     let cors_origins: String = var("CORS_ORIGIN").expect("There is not CORS_ORIGIN env variable!");
+    let federated_config = var("FEDERATED_CONFIG").ok();
     let client_port =
         u16::from_str(client_port.as_str()).expect("Failed while parsing port number.");
 
-    let service = ServiceParametersBuilder::default()
-        .es_host(es_host)
-        .es_user(es_user)
-        .es_passwd(es_passwd)
+    let mut builder = ServiceParametersBuilder::default();
+    builder
+        .search_backend(search_backend)
         .service_addr(client_addr)
         .service_port(client_port)
         .cors_origin(cors_origins)
-        .build();
+        .federated_config(federated_config);
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    builder.data_dir(var("SEARCHER_DATA_DIR").ok());
+
+    #[cfg(feature = "elastic-search")]
+    load_elastic_parameters(&mut builder)?;
 
+    let service = builder.build();
     Ok(service.unwrap())
 }
 
+/// Loads connection parameters of elastic which are used by elastic backend
+/// only, including the backends of federated search.
+#[cfg(feature = "elastic-search")]
+fn load_elastic_parameters(builder: &mut ServiceParametersBuilder) -> Result<(), anyhow::Error> {
+    let es_host = var("ELASTIC_HOST").unwrap_or_default();
+    let es_user = var("ELASTIC_USER").unwrap_or_default();
+    let es_passwd = var("ELASTIC_PASSWORD").unwrap_or_default();
+    let es_auth = match var("ELASTIC_AUTH")
+        .unwrap_or_default()
+        .to_lowercase()
+        .as_str()
+    {
+        "" | "basic" => ElasticAuth::Basic,
+        "bearer" => ElasticAuth::Bearer {
+            token: var("ELASTIC_BEARER_TOKEN")?,
+        },
+        "api_key" => ElasticAuth::ApiKey {
+            id: var("ELASTIC_API_KEY_ID")?,
+            key: var("ELASTIC_API_KEY")?,
+        },
+        "none" => ElasticAuth::None,
+        other => return Err(anyhow::anyhow!("Unknown elastic auth method: {}", other)),
+    };
+
+    let es_tls = ElasticTls {
+        cert_validation: match var("ELASTIC_CERT_VALIDATION") {
+            Ok(validation) => CertValidation::from_str(validation.as_str())?,
+            Err(_) => CertValidation::default(),
+        },
+        ca_cert: var("ELASTIC_CA_CERT").ok(),
+        client_cert: var("ELASTIC_CLIENT_CERT").ok(),
+        client_key: var("ELASTIC_CLIENT_KEY").ok(),
+        client_cert_passwd: var("ELASTIC_CLIENT_CERT_PASSWORD").ok(),
+    };
+
+    let es_sniff_interval = match var("ELASTIC_SNIFF_INTERVAL") {
+        Ok(interval) => Some(Duration::from_secs(u64::from_str(interval.as_str())?)),
+        Err(_) => None,
+    };
+    let es_match_config = var("BUCKET_MATCH_CONFIG").ok();
+
+    builder
+        .es_host(es_host)
+        .es_user(es_user)
+        .es_passwd(es_passwd)
+        .es_auth(es_auth)
+        .es_tls(es_tls)
+        .es_sniff_interval(es_sniff_interval)
+        .es_match_config(es_match_config);
+
+    Ok(())
+}
+
 pub fn build_cors_config(_origin: &str) -> Cors {
     let available_methods = vec!["GET", "POST", "PUT", "DELETE", "OPTIONS"];
     let available_headers = vec![header::AUTHORIZATION, header::ACCEPT];
@@ -112,10 +350,19 @@ pub fn build_service() -> Scope {
         .service(delete_document)
         .service(update_document)
         .service(get_document)
+        .service(close_cursor)
+        .service(export_documents)
         .service(search_target)
         .service(search_all)
+        .service(suggest)
         .service(search_similar_docs)
         .service(search_similar_docs_target)
+        .service(all_saved_searches)
+        .service(new_saved_search)
+        .service(run_saved_search)
+        .service(get_saved_search)
+        .service(update_saved_search)
+        .service(delete_saved_search)
         .service(load_file)
         .service(upload_file)
 }
@@ -0,0 +1,362 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Count of the most frequent values returned by terms facets.
+pub const TERMS_FACET_SIZE: usize = 20;
+
+/// Key, lower and upper (exclusive) bounds of `document_size` facet ranges.
+pub const SIZE_RANGES: [(&str, Option<i64>, Option<i64>); 5] = [
+    ("0-10KB", None, Some(10 << 10)),
+    ("10KB-1MB", Some(10 << 10), Some(1 << 20)),
+    ("1MB-10MB", Some(1 << 20), Some(10 << 20)),
+    ("10MB-100MB", Some(10 << 20), Some(100 << 20)),
+    ("100MB+", Some(100 << 20), None),
+];
+
+pub type Aggregations = BTreeMap<Facet, Vec<FacetBucket>>;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Facet {
+    #[serde(rename = "document_extension")]
+    Extension,
+    #[serde(rename = "document_type")]
+    Type,
+    #[serde(rename = "bucket")]
+    Bucket,
+    #[serde(rename = "document_created")]
+    Created,
+    #[serde(rename = "document_size")]
+    Size,
+}
+
+/// Calendar interval of `document_created` histogram.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DateInterval {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct FacetBucket {
+    pub key: String,
+    pub count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<i64>,
+}
+
+impl FacetBucket {
+    pub fn new(key: &str, count: u64) -> Self {
+        FacetBucket {
+            key: key.to_string(),
+            count,
+            from: None,
+            to: None,
+        }
+    }
+}
+
+impl Facet {
+    #[cfg(feature = "elastic-search")]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Facet::Bucket => "bucket",
+            other => other.field(),
+        }
+    }
+
+    /// Returns indexed field which values are aggregated by facet.
+    #[cfg(feature = "elastic-search")]
+    pub fn field(&self) -> &'static str {
+        match self {
+            Facet::Extension => "document_extension",
+            Facet::Type => "document_type",
+            Facet::Bucket => "bucket_uuid",
+            Facet::Created => "document_created",
+            Facet::Size => "document_size",
+        }
+    }
+}
+
+impl DateInterval {
+    #[cfg(feature = "elastic-search")]
+    pub fn name(&self) -> &'static str {
+        match self {
+            DateInterval::Day => "day",
+            DateInterval::Week => "week",
+            DateInterval::Month => "month",
+            DateInterval::Quarter => "quarter",
+            DateInterval::Year => "year",
+        }
+    }
+
+    /// Returns start of interval which contains passed date. Weeks start
+    /// on Monday like in elastic date histogram.
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn truncate(&self, date: DateTime<Utc>) -> DateTime<Utc> {
+        use chrono::{Datelike, TimeZone};
+
+        let date = date.date_naive();
+        let start = match self {
+            DateInterval::Day => date,
+            DateInterval::Week => {
+                date - chrono::Days::new(date.weekday().num_days_from_monday() as u64)
+            }
+            DateInterval::Month => date.with_day(1).unwrap_or(date),
+            DateInterval::Quarter => {
+                let month = (date.month0() / 3) * 3 + 1;
+                date.with_day(1)
+                    .and_then(|d| d.with_month(month))
+                    .unwrap_or(date)
+            }
+            DateInterval::Year => date.with_ordinal(1).unwrap_or(date),
+        };
+
+        Utc.from_utc_datetime(&start.and_hms_opt(0, 0, 0).unwrap_or_default())
+    }
+}
+
+/// Returns key of date histogram bucket which starts at passed date.
+pub fn date_key(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Counts passed documents by facets like elastic aggregations do, for
+/// backends which aggregate matched documents in memory.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn aggregate_documents<'a, I>(
+    facets: &[Facet],
+    interval: DateInterval,
+    documents: I,
+) -> Aggregations
+where
+    I: Iterator<Item = &'a crate::wrappers::document::Document> + Clone,
+{
+    facets
+        .iter()
+        .map(|facet| {
+            let buckets = match facet {
+                Facet::Extension => {
+                    count_terms(documents.clone().map(|doc| doc.document_extension.as_str()))
+                }
+                Facet::Type => count_terms(documents.clone().map(|doc| doc.document_type.as_str())),
+                Facet::Bucket => count_terms(documents.clone().map(|doc| doc.bucket_uuid.as_str())),
+                Facet::Created => {
+                    let mut counts = BTreeMap::new();
+                    let dates = documents.clone().filter_map(|doc| doc.document_created);
+                    dates.for_each(|date| *counts.entry(interval.truncate(date)).or_insert(0) += 1);
+                    counts
+                        .into_iter()
+                        .map(|(date, count)| FacetBucket::new(&date_key(date), count))
+                        .collect()
+                }
+                Facet::Size => {
+                    let sizes = documents.clone().map(|doc| doc.document_size as i64);
+                    count_ranges(sizes.collect())
+                }
+            };
+
+            (*facet, buckets)
+        })
+        .collect()
+}
+
+/// Merges aggregations of several backends by summing counts of buckets
+/// with the same key.
+pub fn merge_aggregations(aggregations: Vec<Aggregations>) -> Aggregations {
+    let mut merged = Aggregations::new();
+    for (facet, buckets) in aggregations.into_iter().flatten() {
+        let merged_buckets = merged.entry(facet).or_default();
+        for bucket in buckets {
+            match merged_buckets
+                .iter_mut()
+                .find(|merged| merged.key == bucket.key)
+            {
+                Some(merged) => merged.count += bucket.count,
+                None => merged_buckets.push(bucket),
+            }
+        }
+    }
+
+    for (facet, buckets) in merged.iter_mut() {
+        match facet {
+            Facet::Created => buckets.sort_by(|a, b| a.key.cmp(&b.key)),
+            Facet::Size => buckets.sort_by_key(|bucket| bucket.from),
+            _ => sort_terms(buckets),
+        }
+    }
+
+    merged
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn count_ranges(sizes: Vec<i64>) -> Vec<FacetBucket> {
+    SIZE_RANGES
+        .iter()
+        .map(|(key, from, to)| {
+            let in_range = |size: &&i64| {
+                from.is_none_or(|from| **size >= from) && to.is_none_or(|to| **size < to)
+            };
+
+            let count = sizes.iter().filter(in_range).count() as u64;
+            FacetBucket {
+                from: *from,
+                to: *to,
+                ..FacetBucket::new(key, count)
+            }
+        })
+        .collect()
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn count_terms<'a>(values: impl Iterator<Item = &'a str>) -> Vec<FacetBucket> {
+    let mut counts = std::collections::HashMap::new();
+    values
+        .filter(|value| !value.is_empty())
+        .for_each(|value| *counts.entry(value).or_insert(0) += 1);
+
+    let mut buckets = counts
+        .into_iter()
+        .map(|(key, count)| FacetBucket::new(key, count))
+        .collect::<Vec<_>>();
+
+    sort_terms(&mut buckets);
+    buckets
+}
+
+/// Orders terms buckets by count and then key as elastic does and keeps
+/// the most frequent ones.
+fn sort_terms(buckets: &mut Vec<FacetBucket>) {
+    buckets.sort_by(|a, b| (Reverse(a.count), &a.key).cmp(&(Reverse(b.count), &b.key)));
+    buckets.truncate(TERMS_FACET_SIZE);
+}
+
+#[cfg(test)]
+mod aggregation_tests {
+    use super::*;
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    use crate::wrappers::document::Document;
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    use datetime::parse_datetime;
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    fn build_document(extension: &str, size: i32, created: &str) -> Document {
+        Document {
+            bucket_uuid: "test_bucket".to_string(),
+            document_extension: extension.to_string(),
+            document_size: size,
+            document_created: parse_datetime(created),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[test]
+    fn aggregate_documents_test() {
+        let documents = [
+            build_document("pdf", 1024, "2023-09-15"),
+            build_document("txt", 20480, "2023-09-30"),
+            build_document("pdf", 4 << 20, "2023-11-02"),
+        ];
+
+        let facets = [Facet::Extension, Facet::Created, Facet::Size];
+        let aggregations = aggregate_documents(&facets, DateInterval::Month, documents.iter());
+        assert_eq!(
+            aggregations[&Facet::Extension],
+            vec![FacetBucket::new("pdf", 2), FacetBucket::new("txt", 1)]
+        );
+
+        let created = &aggregations[&Facet::Created];
+        assert_eq!(created[0], FacetBucket::new("2023-09-01T00:00:00Z", 2));
+        assert_eq!(created[1], FacetBucket::new("2023-11-01T00:00:00Z", 1));
+
+        let sizes = &aggregations[&Facet::Size];
+        let counts = sizes.iter().map(|bucket| bucket.count).collect::<Vec<_>>();
+        assert_eq!(counts, [1, 1, 1, 0, 0]);
+        assert!(!aggregations.contains_key(&Facet::Bucket));
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[test]
+    fn truncate_dates_test() {
+        let date = parse_datetime("2023-08-17").unwrap();
+        assert_eq!(
+            date_key(DateInterval::Week.truncate(date)),
+            "2023-08-14T00:00:00Z"
+        );
+        assert_eq!(
+            date_key(DateInterval::Quarter.truncate(date)),
+            "2023-07-01T00:00:00Z"
+        );
+        assert_eq!(
+            date_key(DateInterval::Year.truncate(date)),
+            "2023-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn merge_aggregations_test() {
+        let first = Aggregations::from([(Facet::Type, vec![FacetBucket::new("document", 2)])]);
+        let second = Aggregations::from([(
+            Facet::Type,
+            vec![
+                FacetBucket::new("archive", 3),
+                FacetBucket::new("document", 2),
+            ],
+        )]);
+
+        let merged = merge_aggregations(vec![first, second]);
+        assert_eq!(
+            merged[&Facet::Type],
+            vec![
+                FacetBucket::new("document", 4),
+                FacetBucket::new("archive", 3)
+            ]
+        );
+    }
+}
@@ -4,7 +4,7 @@ use derive_builder::Builder;
 use file_loader::FileData;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Builder, Default)]
+#[derive(Clone, Deserialize, Serialize, Builder, Default)]
 pub struct Document {
     pub bucket_uuid: String,
     pub bucket_path: String,
@@ -20,12 +20,14 @@ pub struct Document {
     pub entity_keywords: Vec<String>,
     pub highlight: Option<HighlightEntity>,
     #[serde(
+        default,
         serialize_with = "serialize_dt",
         deserialize_with = "deserialize_dt",
         skip_serializing_if = "Option::is_none"
     )]
     pub document_created: Option<DateTime<Utc>>,
     #[serde(
+        default,
         serialize_with = "serialize_dt",
         deserialize_with = "deserialize_dt",
         skip_serializing_if = "Option::is_none"
@@ -39,9 +41,16 @@ impl Document {
     }
 }
 
+/// Highlighted fragments of document fields, fragments of fields which are
+/// not highlighted are empty.
 #[derive(Serialize, Deserialize, Clone, Default)]
 pub struct HighlightEntity {
+    #[serde(default)]
     pub entity_data: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub document_name: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub document_path: Vec<String>,
 }
 
 impl From<FileData> for Document {
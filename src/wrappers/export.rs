@@ -0,0 +1,200 @@
+use crate::errors::WebError;
+use crate::wrappers::document::Document;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Fields of exported documents in order of CSV columns.
+pub const EXPORT_FIELDS: [&str; 14] = [
+    "bucket_uuid",
+    "bucket_path",
+    "document_name",
+    "document_path",
+    "document_size",
+    "document_type",
+    "document_extension",
+    "document_permissions",
+    "document_created",
+    "document_modified",
+    "document_md5_hash",
+    "document_ssdeep_hash",
+    "entity_keywords",
+    "entity_data",
+];
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Ndjson,
+    Csv,
+}
+
+#[derive(Deserialize)]
+pub struct ExportForm {
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Comma-separated list of exported fields, all fields are exported by
+    /// default.
+    pub fields: Option<String>,
+    /// Comma-separated list of buckets, all buckets are exported by default.
+    pub buckets: Option<String>,
+}
+
+impl ExportForm {
+    pub fn fields(&self) -> Result<Vec<String>, WebError> {
+        let Some(fields) = self.fields.as_ref() else {
+            return Ok(EXPORT_FIELDS
+                .iter()
+                .map(|field| field.to_string())
+                .collect());
+        };
+
+        let fields = fields
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(String::from)
+            .collect::<Vec<String>>();
+
+        if fields.is_empty() {
+            let msg = "There are no exported fields".to_string();
+            return Err(WebError::SearchFailed(msg));
+        }
+
+        match fields
+            .iter()
+            .find(|field| !EXPORT_FIELDS.contains(&field.as_str()))
+        {
+            None => Ok(fields),
+            Some(field) => {
+                let msg = format!("Unknown exported field: {}", field);
+                Err(WebError::SearchFailed(msg))
+            }
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    /// Returns header which is written before exported documents.
+    pub fn header(&self, fields: &[String]) -> Option<String> {
+        match self {
+            ExportFormat::Ndjson => None,
+            ExportFormat::Csv => Some(csv_line(fields)),
+        }
+    }
+
+    /// Writes batch of documents as lines which contain passed fields only.
+    pub fn write_batch(&self, documents: &[Document], fields: &[String]) -> String {
+        let mut batch = String::new();
+        for document in documents {
+            let value = serde_json::to_value(document).unwrap_or_default();
+            let line = match self {
+                ExportFormat::Ndjson => {
+                    let object = fields
+                        .iter()
+                        .map(|field| (field.to_owned(), value[field].to_owned()))
+                        .collect::<serde_json::Map<String, Value>>();
+                    format!("{}\n", Value::Object(object))
+                }
+                ExportFormat::Csv => {
+                    let cells = fields.iter().map(|field| csv_cell(&value[field]));
+                    csv_line(&cells.collect::<Vec<String>>())
+                }
+            };
+
+            batch.push_str(&line);
+        }
+
+        batch
+    }
+}
+
+/// Returns value of CSV cell, lists and objects are written as JSON.
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::default(),
+        Value::String(value) => value.to_owned(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_line(cells: &[String]) -> String {
+    let cells = cells
+        .iter()
+        .map(|cell| match cell.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", cell.replace('"', "\"\"")),
+            false => cell.to_string(),
+        })
+        .collect::<Vec<String>>();
+
+    format!("{}\r\n", cells.join(","))
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+
+    fn build_form(fields: Option<&str>) -> ExportForm {
+        ExportForm {
+            format: ExportFormat::Csv,
+            fields: fields.map(String::from),
+            buckets: None,
+        }
+    }
+
+    #[test]
+    fn select_fields_test() {
+        let fields = build_form(None).fields().unwrap();
+        assert!(fields.contains(&"entity_data".to_string()));
+        assert_eq!(fields.len(), EXPORT_FIELDS.len());
+
+        let fields = build_form(Some("document_name, document_size"))
+            .fields()
+            .unwrap();
+        assert_eq!(fields, ["document_name", "document_size"]);
+
+        assert!(build_form(Some("document_name,highlight"))
+            .fields()
+            .is_err());
+        assert!(build_form(Some(" , ")).fields().is_err());
+    }
+
+    #[test]
+    fn write_batch_test() {
+        let document = Document {
+            document_name: "report, \"final\".docx".to_string(),
+            document_size: 1024,
+            entity_keywords: vec!["report".to_string()],
+            ..Default::default()
+        };
+
+        let fields = build_form(Some("document_name,document_size,entity_keywords"))
+            .fields()
+            .unwrap();
+
+        let csv = ExportFormat::Csv;
+        assert_eq!(
+            csv.header(&fields).unwrap(),
+            "document_name,document_size,entity_keywords\r\n"
+        );
+        assert_eq!(
+            csv.write_batch(std::slice::from_ref(&document), &fields),
+            "\"report, \"\"final\"\".docx\",1024,\"[\"\"report\"\"]\"\r\n"
+        );
+
+        let ndjson = ExportFormat::Ndjson;
+        assert!(ndjson.header(&fields).is_none());
+        assert_eq!(
+            ndjson.write_batch(&[document], &fields[..2]),
+            "{\"document_name\":\"report, \\\"final\\\".docx\",\"document_size\":1024}\n"
+        );
+    }
+}
@@ -0,0 +1,371 @@
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use crate::wrappers::document::{Document, HighlightEntity};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use std::{collections::HashSet, ops::Range};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HighlightField {
+    EntityData,
+    DocumentName,
+    DocumentPath,
+}
+
+/// Options of highlighted fragments which have the same meaning as options
+/// of elastic highlighter.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub struct HighlightOptions {
+    pub pre_tag: String,
+    pub post_tag: String,
+    /// Approximate size of fragment in characters.
+    pub fragment_size: usize,
+    /// Maximum count of fragments of each field, the whole field value is
+    /// highlighted if it is zero.
+    pub number_of_fragments: usize,
+    /// Size of the field beginning which is returned if there are no
+    /// matches in field, nothing is returned if it is zero.
+    pub no_match_size: usize,
+    pub fields: Vec<HighlightField>,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions {
+            pre_tag: "<em>".to_string(),
+            post_tag: "</em>".to_string(),
+            fragment_size: 100,
+            number_of_fragments: 5,
+            no_match_size: 0,
+            fields: vec![HighlightField::EntityData],
+        }
+    }
+}
+
+impl HighlightField {
+    #[cfg(feature = "elastic-search")]
+    pub fn name(&self) -> &'static str {
+        match self {
+            HighlightField::EntityData => "entity_data",
+            HighlightField::DocumentName => "document_name",
+            HighlightField::DocumentPath => "document_path",
+        }
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn value<'a>(&self, document: &'a Document) -> &'a str {
+        match self {
+            HighlightField::EntityData => document.entity_data.as_str(),
+            HighlightField::DocumentName => document.document_name.as_str(),
+            HighlightField::DocumentPath => document.document_path.as_str(),
+        }
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    fn fragments_mut<'a>(&self, highlight: &'a mut HighlightEntity) -> &'a mut Vec<String> {
+        match self {
+            HighlightField::EntityData => &mut highlight.entity_data,
+            HighlightField::DocumentName => &mut highlight.document_name,
+            HighlightField::DocumentPath => &mut highlight.document_path,
+        }
+    }
+}
+
+/// Builds highlight of document fields by passed lowercase terms for
+/// backends which highlight documents in memory.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn highlight_document(
+    document: &Document,
+    terms: &HashSet<String>,
+    options: &HighlightOptions,
+) -> Option<HighlightEntity> {
+    let mut highlight = HighlightEntity::default();
+    let mut is_empty = true;
+    for field in options.fields.iter() {
+        let text = field.value(document);
+        let fragments = build_fragments(text, &match_terms(text, terms), options);
+        is_empty &= fragments.is_empty();
+        *field.fragments_mut(&mut highlight) = fragments;
+    }
+
+    match is_empty {
+        true => None,
+        false => Some(highlight),
+    }
+}
+
+/// Returns ranges of words of text which are contained by passed terms.
+/// Text is split by non-alphanumeric characters and lowercased like it is
+/// done by tokenizers of embedded engines.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn match_terms(text: &str, terms: &HashSet<String>) -> Vec<Range<usize>> {
+    let mut matches = Vec::new();
+    let mut word_start: Option<usize> = None;
+    let boundaries = text.char_indices().chain([(text.len(), ' ')]);
+    for (index, symbol) in boundaries {
+        match (symbol.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(index),
+            (false, Some(start)) => {
+                if terms.contains(&text[start..index].to_lowercase()) {
+                    matches.push(start..index);
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    matches
+}
+
+/// Builds fragments of text around passed sorted matches which are wrapped
+/// by tags. Fragments are ordered by matches count like elastic does with
+/// `"order": "score"` option.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn build_fragments(
+    text: &str,
+    matches: &[Range<usize>],
+    options: &HighlightOptions,
+) -> Vec<String> {
+    if matches.is_empty() {
+        let end = text
+            .char_indices()
+            .nth(options.no_match_size)
+            .map_or(text.len(), |(index, _)| index);
+        let fragment = text[..end].trim();
+        return match fragment.is_empty() {
+            true => Vec::default(),
+            false => vec![fragment.to_string()],
+        };
+    }
+
+    if options.number_of_fragments == 0 {
+        return vec![tag_matches(text, 0..text.len(), matches, options)];
+    }
+
+    let mut fragments: Vec<(Range<usize>, usize)> = Vec::new();
+    for matched in matches {
+        if let Some((fragment, count)) = fragments.last_mut() {
+            if matched.end <= fragment.end {
+                *count += 1;
+                continue;
+            }
+        }
+
+        let padding = options.fragment_size.saturating_sub(matched.len()) / 2;
+        let start = word_start(text, matched.start.saturating_sub(padding));
+        let end = word_end(text, matched.end + padding);
+        fragments.push((start..end, 1));
+    }
+
+    fragments.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.start.cmp(&b.0.start)));
+    fragments
+        .into_iter()
+        .take(options.number_of_fragments)
+        .map(|(fragment, _)| tag_matches(text, fragment, matches, options))
+        .collect()
+}
+
+/// Returns trimmed fragment of text where matches within it are tagged.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn tag_matches(
+    text: &str,
+    fragment: Range<usize>,
+    matches: &[Range<usize>],
+    options: &HighlightOptions,
+) -> String {
+    let slice = &text[fragment.clone()];
+    let start = fragment.start + slice.len() - slice.trim_start().len();
+    let end = fragment.end - (slice.len() - slice.trim_end().len());
+
+    let mut tagged = String::with_capacity(end.saturating_sub(start));
+    let mut position = start;
+    for matched in matches {
+        if matched.start < position || matched.end > end {
+            continue;
+        }
+
+        tagged.push_str(&text[position..matched.start]);
+        tagged.push_str(&options.pre_tag);
+        tagged.push_str(&text[matched.clone()]);
+        tagged.push_str(&options.post_tag);
+        position = matched.end;
+    }
+
+    tagged.push_str(&text[position..end.max(position)]);
+    tagged
+}
+
+/// Moves index back to the start of word which contains it, so fragments
+/// do not begin in the middle of word.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn word_start(text: &str, index: usize) -> usize {
+    let index = floor_char_boundary(text, index.min(text.len()));
+    if !text[index..].starts_with(char::is_alphanumeric) {
+        return index;
+    }
+
+    text[..index]
+        .char_indices()
+        .rev()
+        .take_while(|(_, symbol)| symbol.is_alphanumeric())
+        .last()
+        .map_or(index, |(start, _)| start)
+}
+
+/// Moves index forward to the end of word which contains it.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn word_end(text: &str, index: usize) -> usize {
+    let index = ceil_char_boundary(text, index);
+    text[index..]
+        .char_indices()
+        .find(|(_, symbol)| !symbol.is_alphanumeric())
+        .map_or(text.len(), |(offset, _)| index + offset)
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    )
+))]
+mod highlight_tests {
+    use super::*;
+
+    fn build_terms(terms: &[&str]) -> HashSet<String> {
+        terms.iter().map(|term| term.to_string()).collect()
+    }
+
+    #[test]
+    fn build_fragments_test() {
+        let text = "The commander of the fleet. Nothing here at all, \
+                    only filler text. Commander and fleet again.";
+        let terms = build_terms(&["commander", "fleet"]);
+        let matches = match_terms(text, &terms);
+        assert_eq!(matches.len(), 4);
+
+        let options = HighlightOptions {
+            pre_tag: "[".to_string(),
+            post_tag: "]".to_string(),
+            fragment_size: 30,
+            number_of_fragments: 1,
+            ..Default::default()
+        };
+
+        let fragments = build_fragments(text, &matches, &options);
+        assert_eq!(fragments, ["The [commander] of the [fleet]"]);
+
+        let options = HighlightOptions {
+            number_of_fragments: 0,
+            ..options
+        };
+        let fragments = build_fragments(text, &matches, &options);
+        assert_eq!(fragments.len(), 1);
+        assert!(fragments[0].ends_with("[Commander] and [fleet] again."));
+    }
+
+    #[test]
+    fn highlight_document_test() {
+        let document = Document {
+            document_name: "Отчёт командира.txt".to_string(),
+            document_path: "/reports/2023/report.txt".to_string(),
+            entity_data: "Nothing matched".to_string(),
+            ..Default::default()
+        };
+
+        let options = HighlightOptions {
+            fields: vec![HighlightField::DocumentName, HighlightField::DocumentPath],
+            ..Default::default()
+        };
+
+        let terms = build_terms(&["командира"]);
+        let highlight = highlight_document(&document, &terms, &options).unwrap();
+        assert_eq!(highlight.document_name, ["Отчёт <em>командира</em>.txt"]);
+        assert!(highlight.document_path.is_empty());
+        assert!(highlight.entity_data.is_empty());
+
+        let options = HighlightOptions {
+            no_match_size: 4,
+            fields: vec![HighlightField::EntityData],
+            ..Default::default()
+        };
+        let highlight = highlight_document(&document, &terms, &options).unwrap();
+        assert_eq!(highlight.entity_data, ["Noth"]);
+
+        let options = HighlightOptions::default();
+        assert!(highlight_document(&document, &terms, &options).is_none());
+    }
+}
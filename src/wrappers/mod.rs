@@ -1,5 +1,13 @@
+pub mod aggregation;
 pub mod bucket;
 pub mod cluster;
 pub mod document;
+pub mod export;
 pub mod file_form;
+pub mod highlight;
+pub mod pattern;
+pub mod proximity;
+pub mod saved_search;
 pub mod search_params;
+pub mod search_response;
+pub mod suggest;
@@ -0,0 +1,371 @@
+use crate::errors::WebError;
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use crate::wrappers::{document::Document, proximity::ProximityQuery, search_params::SearchParams};
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use std::time::Duration;
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use std::{cell::Cell, time::Instant};
+
+/// Maximum length of pattern in characters.
+pub const MAX_PATTERN_LENGTH: usize = 512;
+/// Maximum count of `*` and `?` symbols of wildcard pattern.
+pub const MAX_WILDCARDS: usize = 16;
+/// Maximum count of automaton states of regular expression, it limits
+/// elastic `regexp` query by the same count.
+#[cfg(feature = "elastic-search")]
+pub const MAX_DETERMINIZED_STATES: usize = 10_000;
+/// Maximum size in bytes of compiled regular expression of embedded engines.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+pub const DEFAULT_PATTERN_TIMEOUT_MS: u64 = 10_000;
+pub const MAX_PATTERN_TIMEOUT_MS: u64 = 60_000;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternMode {
+    /// Regular expression which matches any part of field value.
+    Regex,
+    /// Wildcard pattern with `*` and `?` which matches whole field value.
+    Wildcard,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternField {
+    EntityData,
+    DocumentPath,
+}
+
+impl PatternField {
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn value<'a>(&self, document: &'a Document) -> &'a str {
+        match self {
+            PatternField::EntityData => document.entity_data.as_str(),
+            PatternField::DocumentPath => document.document_path.as_str(),
+        }
+    }
+}
+
+/// Pattern like `AKIA[0-9A-Z]{16}` or `*.bak` which documents must match
+/// besides full-text query. Regular expressions support the syntax which
+/// is common for elastic `regexp` query and `regex` crate.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PatternQuery {
+    pub mode: PatternMode,
+    pub value: String,
+    /// Matched fields, pattern matches any of them. Text and path are
+    /// matched if there are no fields.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<PatternField>,
+    /// Search is stopped after timeout and documents matched so far are
+    /// returned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl PatternQuery {
+    pub fn validate(&self) -> Result<(), WebError> {
+        let invalid_pattern = |msg: String| Err(WebError::SearchFailed(msg));
+
+        let length = self.value.chars().count();
+        if length == 0 || length > MAX_PATTERN_LENGTH {
+            let msg = format!("Pattern length must be from 1 to {}", MAX_PATTERN_LENGTH);
+            return invalid_pattern(msg);
+        }
+
+        if self
+            .timeout_ms
+            .is_some_and(|timeout| timeout > MAX_PATTERN_TIMEOUT_MS)
+        {
+            let msg = format!("Pattern timeout may be up to {} ms", MAX_PATTERN_TIMEOUT_MS);
+            return invalid_pattern(msg);
+        }
+
+        match self.mode {
+            PatternMode::Regex => validate_regex_syntax(&self.value)?,
+            PatternMode::Wildcard => {
+                let wildcards = self.value.chars().filter(|it| matches!(it, '*' | '?'));
+                if wildcards.count() > MAX_WILDCARDS {
+                    let msg = format!("Pattern may have up to {} wildcards", MAX_WILDCARDS);
+                    return invalid_pattern(msg);
+                }
+            }
+        }
+
+        match build_regex(&self.to_regex()) {
+            Ok(_) => Ok(()),
+            Err(_) => invalid_pattern(format!("Pattern is too complex: {}", self.value)),
+        }
+    }
+
+    pub fn fields(&self) -> Vec<PatternField> {
+        match self.fields.is_empty() {
+            true => vec![PatternField::EntityData, PatternField::DocumentPath],
+            false => self.fields.clone(),
+        }
+    }
+
+    pub fn timeout(&self) -> Duration {
+        let timeout = self.timeout_ms.unwrap_or(DEFAULT_PATTERN_TIMEOUT_MS);
+        Duration::from_millis(timeout.min(MAX_PATTERN_TIMEOUT_MS))
+    }
+
+    /// Returns pattern translated to regular expression of `regex` crate
+    /// where `.` matches line breaks like in elastic.
+    pub fn to_regex(&self) -> String {
+        match self.mode {
+            PatternMode::Regex => format!("(?s:{})", self.value),
+            PatternMode::Wildcard => format!("(?is)^{}$", wildcard_to_regex(&self.value)),
+        }
+    }
+}
+
+/// Allows the syntax which has the same meaning for elastic `regexp` query
+/// without optional operators and for `regex` crate: patterns match any
+/// part of value, so anchors are not allowed, and classes like `\d` are
+/// written as `[0-9]`.
+fn validate_regex_syntax(pattern: &str) -> Result<(), WebError> {
+    let unsupported = |construct: &str| {
+        let msg = format!("Unsupported regex syntax {} in: {}", construct, pattern);
+        Err(WebError::SearchFailed(msg))
+    };
+
+    let mut chars = pattern.chars().peekable();
+    let mut in_class = false;
+    while let Some(symbol) = chars.next() {
+        match symbol {
+            '\\' => match chars.next() {
+                Some(escaped) if escaped.is_alphanumeric() => {
+                    return unsupported(&format!("\\{}", escaped));
+                }
+                Some(_) => {}
+                None => return unsupported("\\"),
+            },
+            '[' if !in_class => {
+                in_class = true;
+                if chars.peek() == Some(&'^') {
+                    chars.next();
+                }
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                }
+            }
+            ']' if in_class => in_class = false,
+            '^' | '$' | '"' if !in_class => return unsupported(&symbol.to_string()),
+            '(' if !in_class && chars.peek() == Some(&'?') => return unsupported("(?"),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Escapes symbols of wildcard pattern except `*` and `?`, backslash escapes
+/// the following symbol like in elastic.
+fn wildcard_to_regex(pattern: &str) -> String {
+    let mut regex = String::with_capacity(pattern.len() * 2);
+    let mut chars = pattern.chars();
+    while let Some(symbol) = chars.next() {
+        match symbol {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    regex.push_str(&regex::escape(&escaped.to_string()));
+                }
+            }
+            _ => regex.push_str(&regex::escape(&symbol.to_string())),
+        }
+    }
+
+    regex
+}
+
+fn build_regex(regex: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(regex)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+}
+
+/// Matcher of pattern query which stops matching after its timeout, so
+/// documents checked later are not matched.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub struct PatternMatcher {
+    regex: Regex,
+    fields: Vec<PatternField>,
+    deadline: Instant,
+    timed_out: Cell<bool>,
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+impl PatternMatcher {
+    pub fn new(pattern: &PatternQuery) -> Self {
+        // Invalid pattern must not match anything instead of being ignored.
+        let regex =
+            build_regex(&pattern.to_regex()).unwrap_or_else(|_| Regex::new("[^\\s\\S]").unwrap());
+
+        PatternMatcher {
+            regex,
+            fields: pattern.fields(),
+            deadline: Instant::now() + pattern.timeout(),
+            timed_out: Cell::new(false),
+        }
+    }
+
+    pub fn matches(&self, document: &Document) -> bool {
+        if self.timed_out.get() || Instant::now() >= self.deadline {
+            self.timed_out.set(true);
+            return false;
+        }
+
+        self.fields
+            .iter()
+            .any(|field| self.regex.is_match(field.value(document)))
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.timed_out.get()
+    }
+}
+
+/// Filter by proximity queries and pattern which embedded engines check
+/// over text of loaded documents, so all matched documents are loaded.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+#[derive(Default)]
+pub struct TextFilter<'a> {
+    proximity: &'a [ProximityQuery],
+    pattern: Option<PatternMatcher>,
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+impl<'a> TextFilter<'a> {
+    pub fn new(parameters: &'a SearchParams) -> Self {
+        TextFilter {
+            proximity: parameters.proximity.as_slice(),
+            pattern: parameters.pattern.as_ref().map(PatternMatcher::new),
+        }
+    }
+
+    #[cfg(any(feature = "tantivy-search", feature = "sqlite-search"))]
+    pub fn is_empty(&self) -> bool {
+        self.proximity.is_empty() && self.pattern.is_none()
+    }
+
+    pub fn matches(&self, document: &Document) -> bool {
+        let is_close = |query: &ProximityQuery| query.matches(&document.entity_data);
+        self.proximity.iter().all(is_close)
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.matches(document))
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.pattern
+            .as_ref()
+            .is_some_and(PatternMatcher::is_timed_out)
+    }
+}
+
+#[cfg(test)]
+mod pattern_tests {
+    use super::*;
+
+    fn build_pattern(mode: PatternMode, value: &str) -> PatternQuery {
+        PatternQuery {
+            mode,
+            value: value.to_string(),
+            fields: Vec::default(),
+            timeout_ms: None,
+        }
+    }
+
+    #[test]
+    fn validate_pattern_test() {
+        let pattern = build_pattern(PatternMode::Regex, "AKIA[0-9A-Z]{16}");
+        assert!(pattern.validate().is_ok());
+        assert_eq!(pattern.to_regex(), "(?s:AKIA[0-9A-Z]{16})");
+
+        let unsupported = [
+            "^AKIA",
+            "key\\d+",
+            "(?i)akia",
+            "a\"b",
+            "([a-z]{1000}){1000}",
+        ];
+        for value in unsupported {
+            assert!(build_pattern(PatternMode::Regex, value).validate().is_err());
+        }
+        assert!(build_pattern(PatternMode::Regex, "[^$]\\.bak")
+            .validate()
+            .is_ok());
+
+        let pattern = build_pattern(PatternMode::Wildcard, "/home/*.bak");
+        assert!(pattern.validate().is_ok());
+        assert_eq!(pattern.to_regex(), "(?is)^/home/.*\\.bak$");
+        assert!(build_pattern(PatternMode::Wildcard, "").validate().is_err());
+        assert!(build_pattern(PatternMode::Wildcard, &"*a".repeat(17))
+            .validate()
+            .is_err());
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[test]
+    fn match_pattern_test() {
+        let document = Document {
+            document_path: "/home/user/notes.BAK".to_string(),
+            entity_data: "aws key:\nAKIAABCDEFGHIJKLMNOP".to_string(),
+            ..Default::default()
+        };
+
+        let pattern = build_pattern(PatternMode::Regex, "key:.AKIA[0-9A-Z]{16}");
+        assert!(PatternMatcher::new(&pattern).matches(&document));
+
+        let mut pattern = build_pattern(PatternMode::Wildcard, "*.bak");
+        assert!(PatternMatcher::new(&pattern).matches(&document));
+
+        pattern.fields = vec![PatternField::EntityData];
+        assert!(!PatternMatcher::new(&pattern).matches(&document));
+
+        pattern.timeout_ms = Some(0);
+        let matcher = PatternMatcher::new(&pattern);
+        assert!(!matcher.matches(&document));
+        assert!(matcher.is_timed_out());
+    }
+}
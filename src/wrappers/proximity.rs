@@ -0,0 +1,234 @@
+use crate::errors::WebError;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use std::ops::Range;
+
+/// Maximum count of terms of proximity query.
+pub const MAX_PROXIMITY_TERMS: usize = 10;
+
+/// Terms which must occur in documents text within passed count of other
+/// words of each other, like `password` within 5 words of `admin`. Terms
+/// of several words must occur as phrases.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct ProximityQuery {
+    pub terms: Vec<String>,
+    /// Maximum count of other words between terms.
+    pub within: u32,
+    /// Terms must occur in the passed order.
+    #[serde(default)]
+    pub ordered: bool,
+}
+
+impl ProximityQuery {
+    pub fn validate(&self) -> Result<(), WebError> {
+        let terms_count = self.term_words().len();
+        if terms_count < 2 || terms_count != self.terms.len() {
+            let msg = "Proximity query must have at least two non-empty terms".to_string();
+            return Err(WebError::SearchFailed(msg));
+        }
+
+        if terms_count > MAX_PROXIMITY_TERMS {
+            let msg = format!(
+                "Proximity query may have up to {} terms",
+                MAX_PROXIMITY_TERMS
+            );
+            return Err(WebError::SearchFailed(msg));
+        }
+
+        Ok(())
+    }
+
+    /// Returns lowercase words of every term which has any.
+    pub fn term_words(&self) -> Vec<Vec<String>> {
+        self.terms
+            .iter()
+            .map(|term| split_words(term))
+            .filter(|words| !words.is_empty())
+            .collect()
+    }
+
+    /// Checks whether text contains all terms within the distance. It is
+    /// used by backends which check documents text in memory.
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn matches(&self, text: &str) -> bool {
+        let words = split_words(text);
+        let occurrences = self
+            .term_words()
+            .iter()
+            .map(|term| find_phrase(&words, term))
+            .collect::<Vec<_>>();
+
+        min_gaps(&occurrences, self.ordered).is_some_and(|gaps| gaps <= self.within)
+    }
+}
+
+/// Returns the least count of other words between occurrences of all terms
+/// where one occurrence of every term is taken. Occurrences are ranges of
+/// words positions sorted by start, all occurrences of term have the same
+/// length. Returns none if any term does not occur.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn min_gaps(occurrences: &[Vec<Range<u32>>], ordered: bool) -> Option<u32> {
+    if occurrences.iter().any(Vec::is_empty) {
+        return None;
+    }
+
+    let terms_length = occurrences.iter().map(|term| term[0].len() as u32).sum();
+    match ordered {
+        true => min_ordered_span(occurrences),
+        false => min_unordered_span(occurrences),
+    }
+    .map(|span: u32| span.saturating_sub(terms_length))
+}
+
+/// Finds the shortest span where terms follow each other. The earliest
+/// following occurrences give the shortest span for every first one.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn min_ordered_span(occurrences: &[Vec<Range<u32>>]) -> Option<u32> {
+    occurrences[0]
+        .iter()
+        .filter_map(|first| {
+            let mut end = first.end;
+            for term in occurrences[1..].iter() {
+                let index = term.partition_point(|occurrence| occurrence.start < end);
+                end = term.get(index)?.end;
+            }
+
+            Some(end - first.start)
+        })
+        .min()
+}
+
+/// Finds the shortest window of occurrences sorted by start which contains
+/// every term.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn min_unordered_span(occurrences: &[Vec<Range<u32>>]) -> Option<u32> {
+    let mut sorted = occurrences
+        .iter()
+        .enumerate()
+        .flat_map(|(term, ranges)| ranges.iter().map(move |range| (range.clone(), term)))
+        .collect::<Vec<_>>();
+    sorted.sort_by_key(|(range, _)| range.start);
+
+    let mut counts = vec![0; occurrences.len()];
+    let mut covered = 0;
+    let mut window_start = 0;
+    let mut min_span = None;
+    for (index, (_, term)) in sorted.iter().enumerate() {
+        counts[*term] += 1;
+        if counts[*term] == 1 {
+            covered += 1;
+        }
+
+        while covered == occurrences.len() {
+            let window = &sorted[window_start..=index];
+            let end = window.iter().map(|(range, _)| range.end).max().unwrap_or(0);
+            let span = end - window[0].0.start;
+            min_span = Some(min_span.map_or(span, |min: u32| min.min(span)));
+
+            let first_term = sorted[window_start].1;
+            counts[first_term] -= 1;
+            if counts[first_term] == 0 {
+                covered -= 1;
+            }
+            window_start += 1;
+        }
+    }
+
+    min_span
+}
+
+/// Returns ranges of positions where phrase words follow each other.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+fn find_phrase(words: &[String], phrase: &[String]) -> Vec<Range<u32>> {
+    words
+        .windows(phrase.len())
+        .enumerate()
+        .filter(|(_, window)| *window == phrase)
+        .map(|(position, _)| position as u32..(position + phrase.len()) as u32)
+        .collect()
+}
+
+/// Splits text to lowercase words like tokenizers of embedded engines do.
+fn split_words(text: &str) -> Vec<String> {
+    text.split(|symbol: char| !symbol.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    )
+))]
+mod proximity_tests {
+    use super::*;
+
+    fn build_query(terms: &[&str], within: u32, ordered: bool) -> ProximityQuery {
+        ProximityQuery {
+            terms: terms.iter().map(|term| term.to_string()).collect(),
+            within,
+            ordered,
+        }
+    }
+
+    #[test]
+    fn min_gaps_test() {
+        let occurrences = vec![vec![0..1, 10..11], vec![4..6, 12..14]];
+        assert_eq!(min_gaps(&occurrences, true), Some(1));
+        assert_eq!(min_gaps(&occurrences, false), Some(1));
+
+        let occurrences = vec![vec![10..11, 20..21], vec![4..5, 8..9]];
+        assert_eq!(min_gaps(&occurrences, true), None);
+        assert_eq!(min_gaps(&occurrences, false), Some(1));
+        assert_eq!(min_gaps(&[vec![0..1, 2..3], vec![]], false), None);
+    }
+
+    #[test]
+    fn match_proximity_test() {
+        let text = "Admin account: the password of the admin is stored in plain text.";
+        assert!(build_query(&["password", "admin"], 2, true).matches(text));
+        assert!(!build_query(&["password", "admin"], 1, true).matches(text));
+        assert!(!build_query(&["account", "admin"], 3, true).matches(text));
+        assert!(build_query(&["account", "admin"], 0, false).matches(text));
+        assert!(build_query(&["admin", "plain text"], 3, true).matches(text));
+        assert!(!build_query(&["admin", "text plain"], 10, false).matches(text));
+
+        assert!(build_query(&["password", "admin"], 5, false)
+            .validate()
+            .is_ok());
+        assert!(build_query(&["password"], 5, false).validate().is_err());
+        assert!(build_query(&["password", "--"], 5, false)
+            .validate()
+            .is_err());
+    }
+}
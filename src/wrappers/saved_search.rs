@@ -0,0 +1,269 @@
+use crate::errors::WebError;
+use crate::wrappers::search_params::SearchParams;
+
+use chrono::{DateTime, Utc};
+use hasher::{gen_hash, HashType};
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use std::collections::BTreeMap;
+
+/// Maximum length of saved search name in characters.
+pub const MAX_SAVED_SEARCH_NAME_LENGTH: usize = 256;
+
+/// Named search parameters which are stored by backend and may be run by
+/// name or id instead of passing the same query again.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SavedSearch {
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    /// Comma-separated list of searched buckets, all buckets are searched
+    /// if it is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buckets: Option<String>,
+    pub params: SearchParams,
+    pub created: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct SavedSearchForm {
+    pub name: String,
+    pub owner: String,
+    pub buckets: Option<String>,
+    pub params: SearchParams,
+}
+
+impl SavedSearchForm {
+    pub fn validate(&self) -> Result<(), WebError> {
+        let length = self.name.trim().chars().count();
+        if length == 0 || length > MAX_SAVED_SEARCH_NAME_LENGTH {
+            let msg = format!(
+                "Saved search name length must be from 1 to {}",
+                MAX_SAVED_SEARCH_NAME_LENGTH
+            );
+            return Err(WebError::SavedSearch(msg));
+        }
+
+        if self.owner.trim().is_empty() {
+            let msg = "Saved search owner must not be empty".to_string();
+            return Err(WebError::SavedSearch(msg));
+        }
+
+        if self.params.cursor.is_some() {
+            let msg = "Saved search must not have cursor".to_string();
+            return Err(WebError::SavedSearch(msg));
+        }
+
+        self.params.validate_matching()
+    }
+}
+
+impl SavedSearch {
+    /// Creates saved search with id built from its name and creation time.
+    pub fn new(form: &SavedSearchForm) -> Self {
+        let name = form.name.trim().to_string();
+        let created = Utc::now();
+        let id_data = format!("{}:{}", name, created.timestamp_nanos_opt().unwrap_or(0));
+        let id = gen_hash(HashType::MD5, id_data.as_bytes())
+            .map(|hashed| hashed.get_hash_data().to_string())
+            .unwrap_or_else(|_| id_data);
+
+        SavedSearch {
+            id,
+            name,
+            owner: form.owner.clone(),
+            buckets: form.buckets.clone(),
+            params: form.params.clone(),
+            created,
+            last_run: None,
+        }
+    }
+
+    /// Returns search with parameters of form, id and timestamps are kept.
+    pub fn updated(&self, form: &SavedSearchForm) -> Self {
+        SavedSearch {
+            name: form.name.trim().to_string(),
+            owner: form.owner.clone(),
+            buckets: form.buckets.clone(),
+            params: form.params.clone(),
+            ..self.clone()
+        }
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn is_named(&self, id_or_name: &str) -> bool {
+        self.id == id_or_name || self.name == id_or_name.trim()
+    }
+}
+
+/// Saved searches of embedded engines which are kept in memory and stored
+/// by engine. Methods which change searches return changed search without
+/// applying it, so engine applies it after it is stored.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+#[derive(Default)]
+pub struct SavedSearches {
+    searches: BTreeMap<String, SavedSearch>,
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+impl SavedSearches {
+    pub fn all(&self) -> Vec<SavedSearch> {
+        let mut searches = self.searches.values().cloned().collect::<Vec<_>>();
+        searches.sort_by(|a, b| a.name.cmp(&b.name));
+        searches
+    }
+
+    /// Returns search by id or by name if there is no such id.
+    pub fn get(&self, id_or_name: &str) -> Result<&SavedSearch, WebError> {
+        self.searches
+            .get(id_or_name)
+            .or_else(|| self.searches.values().find(|it| it.is_named(id_or_name)))
+            .ok_or_else(|| WebError::SavedSearch(format!("no such saved search: {}", id_or_name)))
+    }
+
+    pub fn created(&self, form: &SavedSearchForm) -> Result<SavedSearch, WebError> {
+        self.check_unique_name(None, &form.name)?;
+        Ok(SavedSearch::new(form))
+    }
+
+    pub fn updated(
+        &self,
+        search_id: &str,
+        form: &SavedSearchForm,
+    ) -> Result<SavedSearch, WebError> {
+        let search = self.get(search_id)?;
+        self.check_unique_name(Some(&search.id), &form.name)?;
+        Ok(search.updated(form))
+    }
+
+    pub fn ran(&self, id_or_name: &str) -> Result<SavedSearch, WebError> {
+        let mut search = self.get(id_or_name)?.clone();
+        search.last_run = Some(Utc::now());
+        Ok(search)
+    }
+
+    pub fn insert(&mut self, search: SavedSearch) {
+        self.searches.insert(search.id.clone(), search);
+    }
+
+    pub fn remove(&mut self, search_id: &str) -> Option<SavedSearch> {
+        self.searches.remove(search_id)
+    }
+
+    fn check_unique_name(&self, search_id: Option<&str>, name: &str) -> Result<(), WebError> {
+        let name = name.trim();
+        let is_taken = self
+            .searches
+            .values()
+            .any(|it| it.name == name && Some(it.id.as_str()) != search_id);
+
+        match is_taken {
+            false => Ok(()),
+            true => Err(WebError::SavedSearch(format!(
+                "saved search {} already exists",
+                name
+            ))),
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+impl FromIterator<SavedSearch> for SavedSearches {
+    fn from_iter<T: IntoIterator<Item = SavedSearch>>(iter: T) -> Self {
+        let searches = iter
+            .into_iter()
+            .map(|search| (search.id.clone(), search))
+            .collect();
+
+        SavedSearches { searches }
+    }
+}
+
+#[cfg(test)]
+mod saved_search_tests {
+    use super::*;
+
+    fn build_form(name: &str) -> SavedSearchForm {
+        SavedSearchForm {
+            name: name.to_string(),
+            owner: "analyst".to_string(),
+            buckets: Some("reports".to_string()),
+            params: SearchParams {
+                query: "lease fee".to_string(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[test]
+    fn saved_searches_test() {
+        let mut searches = SavedSearches::default();
+        let search = searches.created(&build_form("leases")).unwrap();
+        assert!(search.last_run.is_none());
+        let search_id = search.id.clone();
+        searches.insert(search);
+
+        assert!(searches.created(&build_form(" leases ")).is_err());
+        assert_eq!(searches.get("leases").unwrap().id, search_id);
+        assert_eq!(searches.get(&search_id).unwrap().name, "leases");
+        assert!(searches.get("unknown").is_err());
+
+        let mut form = build_form("lease fees");
+        form.params.query = "fee".to_string();
+        let updated = searches.updated("leases", &form).unwrap();
+        assert_eq!(updated.id, search_id);
+        assert_eq!(updated.params.query, "fee");
+        searches.insert(updated);
+
+        let other = searches.created(&build_form("keys")).unwrap();
+        searches.insert(other);
+        assert!(searches.updated("keys", &form).is_err());
+
+        let ran = searches.ran("lease fees").unwrap();
+        assert!(ran.last_run.is_some());
+        searches.insert(ran);
+
+        let names = searches.all().into_iter().map(|it| it.name);
+        assert_eq!(names.collect::<Vec<_>>(), vec!["keys", "lease fees"]);
+        assert!(searches.remove(&search_id).is_some());
+        assert!(searches.get("lease fees").is_err());
+    }
+
+    #[test]
+    fn validate_saved_search_form() {
+        assert!(build_form("leases").validate().is_ok());
+        assert!(build_form("  ").validate().is_err());
+
+        let mut form = build_form("leases");
+        form.owner = String::default();
+        assert!(form.validate().is_err());
+    }
+}
@@ -1,7 +1,18 @@
+use crate::errors::WebError;
+use crate::wrappers::aggregation::{DateInterval, Facet};
+use crate::wrappers::document::Document;
+use crate::wrappers::highlight::HighlightOptions;
+use crate::wrappers::pattern::PatternQuery;
+use crate::wrappers::proximity::ProximityQuery;
+
+use chrono::{DateTime, Utc};
 use derive_builder::Builder;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
-#[derive(Deserialize, Builder)]
+#[derive(Clone, Deserialize, Serialize, Builder)]
 pub struct SearchParams {
     pub query: String,
     pub document_type: String,
@@ -10,8 +21,426 @@ pub struct SearchParams {
     pub document_size_from: i64,
     pub created_date_to: String,
     pub created_date_from: String,
+    #[serde(default)]
+    #[builder(default)]
+    pub modified_date_to: String,
+    #[serde(default)]
+    #[builder(default)]
+    pub modified_date_from: String,
     pub result_size: i64,
     pub result_offset: i64,
+    /// Documents must have any of these extensions. Names of extension
+    /// groups like `office` or `images` are expanded to its extensions.
+    #[serde(default)]
+    #[builder(default)]
+    pub any_extensions: Vec<String>,
+    /// Documents must have any of these types.
+    #[serde(default)]
+    #[builder(default)]
+    pub any_types: Vec<String>,
+    /// Excluded extensions which also may contain names of groups.
+    #[serde(default)]
+    #[builder(default)]
+    pub exclude_extensions: Vec<String>,
+    #[serde(default)]
+    #[builder(default)]
+    pub exclude_types: Vec<String>,
+    /// Prefixes of documents paths which are excluded from results.
+    #[serde(default)]
+    #[builder(default)]
+    pub exclude_paths: Vec<String>,
+    /// Documents path must start with any of these prefixes.
+    #[serde(default)]
+    #[builder(default)]
+    pub path_prefixes: Vec<String>,
+    /// Glob like `/evidence/*/Users/**.docx` which must match whole path:
+    /// `*` and `?` do not match `/`, `**` matches any characters.
+    #[serde(default)]
+    #[builder(default)]
+    pub document_path_glob: String,
+    #[serde(default)]
+    #[builder(default)]
+    pub bucket_path_glob: String,
+    #[serde(default)]
+    #[builder(default)]
+    pub match_options: MatchOptions,
+    /// Terms which must occur near each other in documents text.
+    #[serde(default)]
+    #[builder(default)]
+    pub proximity: Vec<ProximityQuery>,
+    /// Regex or wildcard pattern over documents text and paths.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub pattern: Option<PatternQuery>,
+    /// Sort keys of results, relevance order is used if there are no keys.
+    /// Documents are ordered by md5 hash if all keys are equal.
+    #[serde(default)]
+    #[builder(default)]
+    pub sort: Vec<SortKey>,
+    /// Facets which are counted over all matched documents.
+    #[serde(default)]
+    #[builder(default)]
+    pub aggregations: Vec<Facet>,
+    #[serde(default)]
+    #[builder(default)]
+    pub date_interval: DateInterval,
+    /// Keep-alive like `1m` of point-in-time which is opened to paginate
+    /// results by cursors instead of `result_offset`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub keep_alive: Option<String>,
+    /// Cursor returned with previous page to load next page of results.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    #[builder(default)]
+    pub highlight: HighlightOptions,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CursorForm {
+    pub cursor: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum SortField {
+    #[serde(rename = "document_created")]
+    Created,
+    #[serde(rename = "document_modified")]
+    Modified,
+    #[serde(rename = "document_size")]
+    Size,
+    #[serde(rename = "document_name")]
+    Name,
+    #[serde(rename = "_score")]
+    Score,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct SortKey {
+    pub field: SortField,
+    /// Relevance is sorted descending and other fields ascending by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+}
+
+impl SortKey {
+    pub fn order(&self) -> SortOrder {
+        match (self.order, self.field) {
+            (Some(order), _) => order,
+            (None, SortField::Score) => SortOrder::Desc,
+            (None, _) => SortOrder::Asc,
+        }
+    }
+
+    /// Compares scored documents by the key. Documents without sorted date
+    /// are placed last in any order like elastic does.
+    pub fn compare(&self, a: (f64, &Document), b: (f64, &Document)) -> Ordering {
+        let ordering = match self.field {
+            SortField::Score => a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal),
+            SortField::Size => a.1.document_size.cmp(&b.1.document_size),
+            SortField::Name => a.1.document_name.cmp(&b.1.document_name),
+            SortField::Created => {
+                return compare_dates(a.1.document_created, b.1.document_created, self.order())
+            }
+            SortField::Modified => {
+                return compare_dates(a.1.document_modified, b.1.document_modified, self.order())
+            }
+        };
+
+        match self.order() {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    }
+}
+
+fn compare_dates(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>, order: SortOrder) -> Ordering {
+    match (a, b, order) {
+        (Some(a), Some(b), SortOrder::Asc) => a.cmp(&b),
+        (Some(a), Some(b), SortOrder::Desc) => b.cmp(&a),
+        (a, b, _) => b.is_none().cmp(&a.is_none()).reverse(),
+    }
+}
+
+/// Options of full-text matching of query terms. Options which are not set
+/// are taken from defaults of searched bucket and then from service ones.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct MatchOptions {
+    /// Searched fields with optional boost like `document_name^3`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Operator of query terms: `or` or `and`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator: Option<String>,
+    /// Type of `multi_match` query like `best_fields` or `cross_fields`.
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub match_type: Option<String>,
+    /// Count like `2` or percentage like `75%` of terms which must match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_should_match: Option<String>,
+    /// Maximum edits of matched terms: `AUTO` or count from `0` to `2`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuzziness: Option<String>,
+    /// Count of other words which are allowed between words of phrases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slop: Option<u32>,
+}
+
+/// Allowed values of `fuzziness` option.
+pub const FUZZINESS_VALUES: [&str; 4] = ["AUTO", "0", "1", "2"];
+
+impl MatchOptions {
+    /// Returns options where unset values are filled by passed defaults.
+    #[cfg(feature = "elastic-search")]
+    pub fn or_defaults(&self, defaults: &MatchOptions) -> MatchOptions {
+        let fields = match self.fields.is_empty() {
+            true => defaults.fields.clone(),
+            false => self.fields.clone(),
+        };
+
+        MatchOptions {
+            fields,
+            operator: self.operator.clone().or(defaults.operator.clone()),
+            match_type: self.match_type.clone().or(defaults.match_type.clone()),
+            minimum_should_match: self
+                .minimum_should_match
+                .clone()
+                .or(defaults.minimum_should_match.clone()),
+            fuzziness: self.fuzziness.clone().or(defaults.fuzziness.clone()),
+            slop: self.slop.or(defaults.slop),
+        }
+    }
+
+    pub fn validate_fuzziness(&self) -> Result<(), WebError> {
+        match self.fuzziness.as_deref() {
+            Some(fuzziness) if !FUZZINESS_VALUES.contains(&fuzziness) => {
+                let msg = format!("Unknown fuzziness: {}", fuzziness);
+                Err(WebError::SearchFailed(msg))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns count of edits which are allowed for the word: `AUTO`
+    /// depends on word length like elastic does.
+    #[cfg(feature = "default-search")]
+    pub fn fuzzy_edits(&self, word: &str) -> usize {
+        match self.fuzziness.as_deref() {
+            Some("AUTO") => crate::wrappers::suggest::max_edits(word),
+            Some(edits) => edits.parse().unwrap_or_default(),
+            None => 0,
+        }
+    }
+}
+
+/// Named groups of documents extensions which are expanded on the server.
+const EXTENSION_GROUPS: [(&str, &[&str]); 4] = [
+    (
+        "office",
+        &[
+            "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods", "csv", "ppt", "pptx", "odp", "pdf",
+        ],
+    ),
+    (
+        "source-code",
+        &[
+            "rs", "c", "h", "cpp", "hpp", "cc", "cs", "go", "java", "kt", "py", "rb", "php", "js",
+            "jsx", "ts", "tsx", "swift", "scala", "sh", "sql", "lua", "pl",
+        ],
+    ),
+    (
+        "images",
+        &[
+            "jpg", "jpeg", "png", "gif", "bmp", "tif", "tiff", "webp", "svg", "ico", "heic",
+        ],
+    ),
+    (
+        "archives",
+        &[
+            "zip", "rar", "7z", "tar", "gz", "tgz", "bz2", "xz", "zst", "cab", "iso",
+        ],
+    ),
+];
+
+impl SearchParams {
+    /// Returns normalized `document_extension`.
+    pub fn document_extension(&self) -> String {
+        normalize_extension(&self.document_extension)
+    }
+
+    /// Returns `any_extensions` with expanded extension groups.
+    pub fn any_extensions(&self) -> Vec<String> {
+        expand_extension_groups(&self.any_extensions)
+    }
+
+    /// Returns `exclude_extensions` with expanded extension groups.
+    pub fn exclude_extensions(&self) -> Vec<String> {
+        expand_extension_groups(&self.exclude_extensions)
+    }
+
+    /// Checks whether results are paginated by cursor of point-in-time.
+    pub fn is_cursor_pagination(&self) -> bool {
+        self.keep_alive.is_some() || self.cursor.is_some()
+    }
+
+    /// Checks whether results are sorted by any field except relevance, so
+    /// all matched documents must be sorted instead of the most relevant.
+    #[cfg(any(feature = "tantivy-search", feature = "sqlite-search"))]
+    pub fn is_sorted_by_fields(&self) -> bool {
+        self.sort.iter().any(|key| key.field != SortField::Score)
+    }
+
+    /// Compares scored documents by sort keys, by relevance if there are
+    /// no keys, and then by md5 hash as stable tiebreaker.
+    pub fn compare_documents(&self, a: (f64, &Document), b: (f64, &Document)) -> Ordering {
+        let ordering = match self.sort.is_empty() {
+            true => b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal),
+            false => self
+                .sort
+                .iter()
+                .map(|key| key.compare(a, b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal),
+        };
+
+        ordering.then_with(|| a.1.document_md5_hash.cmp(&b.1.document_md5_hash))
+    }
+
+    /// Checks matching options which are common for all backends.
+    pub fn validate_matching(&self) -> Result<(), WebError> {
+        self.match_options.validate_fuzziness()?;
+        self.proximity
+            .iter()
+            .try_for_each(ProximityQuery::validate)?;
+        self.pattern.iter().try_for_each(PatternQuery::validate)
+    }
+
+    /// Returns `document_path_glob` translated to regular expression.
+    pub fn document_path_regex(&self) -> Option<String> {
+        glob_to_regex(&self.document_path_glob)
+    }
+
+    /// Returns `bucket_path_glob` translated to regular expression.
+    pub fn bucket_path_regex(&self) -> Option<String> {
+        glob_to_regex(&self.bucket_path_glob)
+    }
+}
+
+/// Translates glob to regular expression which must match whole value. It
+/// uses syntax subset which is common for elastic `regexp` query, tantivy
+/// and `regex` crate, so literal symbols are escaped or put to classes.
+fn glob_to_regex(glob: &str) -> Option<String> {
+    if glob.is_empty() {
+        return None;
+    }
+
+    let mut regex = String::with_capacity(glob.len() * 2);
+    let mut chars = glob.chars().peekable();
+    let mut braces_depth = 0;
+    while let Some(symbol) = chars.next() {
+        match symbol {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '{' => {
+                braces_depth += 1;
+                regex.push('(');
+            }
+            '}' if braces_depth > 0 => {
+                braces_depth -= 1;
+                regex.push(')');
+            }
+            ',' if braces_depth > 0 => regex.push('|'),
+            '[' => {
+                regex.push('[');
+                if matches!(chars.peek(), Some('!') | Some('^')) {
+                    chars.next();
+                    regex.push('^');
+                }
+
+                for class_symbol in chars.by_ref() {
+                    if class_symbol == ']' {
+                        break;
+                    }
+
+                    if matches!(class_symbol, '\\' | '[' | '^') {
+                        regex.push('\\');
+                    }
+                    regex.push(class_symbol);
+                }
+                regex.push(']');
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    push_literal(&mut regex, escaped);
+                }
+            }
+            _ => push_literal(&mut regex, symbol),
+        }
+    }
+
+    for _ in 0..braces_depth {
+        regex.push(')');
+    }
+
+    Some(regex)
+}
+
+fn push_literal(regex: &mut String, symbol: char) {
+    match symbol {
+        '<' | '>' => {
+            regex.push('[');
+            regex.push(symbol);
+            regex.push(']');
+        }
+        '.' | '?' | '+' | '*' | '|' | '{' | '}' | '[' | ']' | '(' | ')' | '"' | '\\' | '#'
+        | '@' | '&' | '~' | '^' | '$' => {
+            regex.push('\\');
+            regex.push(symbol);
+        }
+        _ => regex.push(symbol),
+    }
+}
+
+/// Normalizes extension to the form of loaded documents: without leading
+/// dot and in lower case, so `.TXT` and `txt` are the same extension.
+pub fn normalize_extension(extension: &str) -> String {
+    let extension = extension.trim();
+    extension
+        .strip_prefix('.')
+        .unwrap_or(extension)
+        .to_lowercase()
+}
+
+fn expand_extension_groups(values: &[String]) -> Vec<String> {
+    let mut extensions: Vec<String> = Vec::with_capacity(values.len());
+    for value in values.iter().map(|it| normalize_extension(it)) {
+        if value.is_empty() {
+            continue;
+        }
+
+        let group = EXTENSION_GROUPS.iter().find(|(name, _)| *name == value);
+        match group {
+            Some((_, group_exts)) => extensions.extend(group_exts.iter().map(|it| it.to_string())),
+            None => extensions.push(value),
+        }
+    }
+
+    let mut seen = HashSet::with_capacity(extensions.len());
+    extensions.retain(|ext| seen.insert(ext.clone()));
+    extensions
 }
 
 impl Default for SearchParams {
@@ -30,3 +459,71 @@ impl Default for SearchParams {
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod search_params_tests {
+    use super::*;
+
+    #[test]
+    fn expand_extension_groups_test() {
+        let params = SearchParams {
+            any_extensions: vec!["txt".to_string(), "Archives".to_string()],
+            ..Default::default()
+        };
+
+        let extensions = params.any_extensions();
+        assert_eq!(extensions[0], "txt");
+        assert!(extensions.contains(&"7z".to_string()));
+        assert!(!extensions.contains(&"pdf".to_string()));
+        assert!(params.exclude_extensions().is_empty());
+
+        let params = SearchParams {
+            any_extensions: ["zip", "txt", "archives", "txt"].map(String::from).to_vec(),
+            ..Default::default()
+        };
+
+        let extensions = params.any_extensions();
+        assert_eq!(extensions[..2], ["zip", "txt"]);
+        assert_eq!(extensions.iter().filter(|ext| *ext == "zip").count(), 1);
+        assert_eq!(extensions.iter().filter(|ext| *ext == "txt").count(), 1);
+
+        let params = SearchParams {
+            document_extension: " .DOCX".to_string(),
+            any_extensions: [".TXT", "txt", ".Archives", ""].map(String::from).to_vec(),
+            ..Default::default()
+        };
+
+        let extensions = params.any_extensions();
+        assert_eq!(params.document_extension(), "docx");
+        assert_eq!(extensions[0], "txt");
+        assert_eq!(extensions.iter().filter(|ext| *ext == "txt").count(), 1);
+        assert!(extensions.contains(&"zip".to_string()));
+        assert!(!extensions.contains(&String::default()));
+    }
+
+    #[test]
+    fn glob_to_regex_test() {
+        let regex = glob_to_regex("/evidence/host?2/**/{a,b}[!.]*.tar.gz").unwrap();
+        assert_eq!(regex, "/evidence/host[^/]2/.*/(a|b)[^.][^/]*\\.tar\\.gz");
+        assert!(glob_to_regex("").is_none());
+
+        let regex = glob_to_regex("/docs/<draft> (1)#.txt").unwrap();
+        assert_eq!(regex, "/docs/[<]draft[>] \\(1\\)\\#\\.txt");
+    }
+
+    #[test]
+    fn deserialize_sort_keys_test() {
+        let sort = r#"[{"field": "_score"}, {"field": "document_created", "order": "desc"}]"#;
+        let keys: Vec<SortKey> = serde_json::from_str(sort).unwrap();
+        assert_eq!(keys[0].order(), SortOrder::Desc);
+        assert_eq!(keys[1].field, SortField::Created);
+        assert_eq!(keys[1].order(), SortOrder::Desc);
+
+        let name_key = SortKey {
+            field: SortField::Name,
+            order: None,
+        };
+        assert_eq!(name_key.order(), SortOrder::Asc);
+        assert!(serde_json::from_str::<SortKey>(r#"{"field": "entity_data"}"#).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::wrappers::aggregation::Aggregations;
+use crate::wrappers::document::Document;
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use crate::wrappers::search_params::SearchParams;
+use crate::wrappers::suggest::QuerySuggestion;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+use std::borrow::Borrow;
+use std::ops::Deref;
+
+#[derive(Clone, Deserialize, Serialize, Default)]
+pub struct SearchResponse {
+    /// Count of all matched documents, not only returned ones.
+    #[serde(default)]
+    pub total: u64,
+    /// Duration of search in milliseconds.
+    #[serde(default)]
+    pub took: u64,
+    /// The best score of matched documents, elastic does not score them if
+    /// results are sorted by fields.
+    #[serde(default)]
+    pub max_score: Option<f64>,
+    pub hits: Vec<SearchHit>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_backends: Vec<FailedBackend>,
+    /// Cursor to load next page, there is no cursor after the last page.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(default, skip_serializing_if = "Aggregations::is_empty")]
+    pub aggregations: Aggregations,
+    /// Corrected queries which are suggested if few documents are matched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub did_you_mean: Vec<QuerySuggestion>,
+    /// Search is stopped by timeout of pattern query, so only documents
+    /// matched before it are returned.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+impl SearchResponse {
+    pub fn new(total: u64, hits: Vec<SearchHit>) -> Self {
+        SearchResponse {
+            total,
+            hits,
+            ..Default::default()
+        }
+    }
+
+    /// Builds response of the requested page of sorted scored documents.
+    /// Documents with infinite score are exact matches which have no score.
+    pub fn paginate<D>(total: u64, scored: Vec<(f64, D)>, parameters: &SearchParams) -> Self
+    where
+        D: Borrow<Document>,
+    {
+        let finite_score = |score: f64| Some(score).filter(|score| score.is_finite());
+        let max_score = scored
+            .iter()
+            .filter_map(|(score, _)| finite_score(*score))
+            .reduce(f64::max);
+
+        let offset = parameters.result_offset.max(0) as usize;
+        let size = parameters.result_size.max(0) as usize;
+        let hits = scored
+            .into_iter()
+            .skip(offset)
+            .take(size)
+            .map(|(score, document)| SearchHit::new(finite_score(score), document.borrow().clone()))
+            .collect();
+
+        SearchResponse {
+            max_score,
+            ..SearchResponse::new(total, hits)
+        }
+    }
+}
+
+/// Found document with its id and bucket to load it by `get_document`.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SearchHit {
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(rename = "_index")]
+    pub index: String,
+    #[serde(rename = "_score")]
+    pub score: Option<f64>,
+    #[serde(flatten)]
+    pub document: Document,
+}
+
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+impl SearchHit {
+    /// Builds hit of document which is stored by its md5 hash in bucket.
+    pub fn new(score: Option<f64>, document: Document) -> Self {
+        SearchHit {
+            id: document.document_md5_hash.to_owned(),
+            index: document.bucket_uuid.to_owned(),
+            score,
+            document,
+        }
+    }
+}
+
+impl Deref for SearchHit {
+    type Target = Document;
+
+    fn deref(&self) -> &Self::Target {
+        &self.document
+    }
+}
+
+/// Backend of federated search which has not returned results in time or
+/// has returned an error.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct FailedBackend {
+    pub backend: String,
+    pub error: String,
+}
@@ -0,0 +1,359 @@
+use crate::errors::WebError;
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Maximum count of completions of each field.
+pub const MAX_SUGGEST_SIZE: usize = 50;
+
+/// Searches with total of matched documents up to this value are returned
+/// with corrected queries.
+pub const DID_YOU_MEAN_MAX_TOTAL: u64 = 5;
+pub const DID_YOU_MEAN_SIZE: usize = 3;
+
+/// Existing word is corrected only if the correction is found in this many
+/// times more documents, so rare typos of noisy texts are corrected too.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+const CORRECTION_FREQUENCY_RATIO: u64 = 10;
+
+#[derive(Deserialize)]
+pub struct SuggestForm {
+    pub prefix: String,
+    #[serde(default = "default_suggest_size")]
+    pub size: usize,
+    /// Comma-separated list of buckets, all buckets are used by default.
+    pub buckets: Option<String>,
+}
+
+fn default_suggest_size() -> usize {
+    10
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestField {
+    DocumentName,
+    EntityData,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Suggestion {
+    pub text: String,
+    pub field: SuggestField,
+    /// Count of documents which contain completed text.
+    pub count: u64,
+}
+
+impl Suggestion {
+    pub fn new(text: &str, field: SuggestField, count: u64) -> Self {
+        Suggestion {
+            text: text.to_string(),
+            field,
+            count,
+        }
+    }
+}
+
+/// Corrected query where replaced words are wrapped by highlight tags.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct QuerySuggestion {
+    pub text: String,
+    pub highlighted: String,
+}
+
+impl SuggestForm {
+    pub fn validate(&self) -> Result<(), WebError> {
+        if self.prefix.trim().is_empty() {
+            let msg = "Prefix of suggestions is empty".to_string();
+            return Err(WebError::SearchFailed(msg));
+        }
+
+        if self.size == 0 || self.size > MAX_SUGGEST_SIZE {
+            let msg = format!("Size of suggestions must be 1..={}", MAX_SUGGEST_SIZE);
+            return Err(WebError::SearchFailed(msg));
+        }
+
+        Ok(())
+    }
+
+    /// Returns lowercase prefix of documents names.
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    pub fn name_prefix(&self) -> String {
+        self.prefix.trim_start().to_lowercase()
+    }
+
+    /// Returns lowercase last word of prefix which is completed by terms of
+    /// documents text. There is no word if prefix ends with separator.
+    pub fn term_prefix(&self) -> Option<String> {
+        let word = self
+            .prefix
+            .rsplit(|symbol: char| !symbol.is_alphanumeric())
+            .next()
+            .filter(|word| !word.is_empty())?;
+
+        Some(word.to_lowercase())
+    }
+}
+
+/// Returns true if query of search which matched passed count of documents
+/// should be corrected. Queries without words like `*` are not corrected.
+pub fn is_low_result(total: u64, query: &str) -> bool {
+    total <= DID_YOU_MEAN_MAX_TOTAL && query.contains(char::is_alphanumeric)
+}
+
+/// Returns maximum edit distance of corrections like elastic `AUTO`
+/// fuzziness does: short words are not corrected at all.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn max_edits(word: &str) -> usize {
+    match word.chars().count() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Returns Levenshtein distance between words in characters.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn edit_distance(first: &str, second: &str) -> usize {
+    let second = second.chars().collect::<Vec<_>>();
+    let mut row = (0..=second.len()).collect::<Vec<_>>();
+    for (i, first_symbol) in first.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, second_symbol) in second.iter().enumerate() {
+            let cost = usize::from(first_symbol != *second_symbol);
+            let current = (row[j] + 1).min(row[j + 1] + 1).min(previous + cost);
+            previous = row[j + 1];
+            row[j + 1] = current;
+        }
+    }
+
+    row[second.len()]
+}
+
+/// Selects correction of lowercase word among indexed terms with counts of
+/// documents containing them: the closest one and then the most frequent.
+/// Word found in `word_count` documents is kept unless correction is much
+/// more frequent.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn best_correction<'a, I>(word: &str, word_count: u64, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = (&'a str, u64)>,
+{
+    let mut counts: HashMap<&str, u64> = HashMap::new();
+    for (term, count) in candidates {
+        *counts.entry(term).or_default() += count;
+    }
+
+    let max_edits = max_edits(word);
+    counts
+        .into_iter()
+        .filter(|(term, count)| {
+            *term != word && *count > word_count.saturating_mul(CORRECTION_FREQUENCY_RATIO)
+        })
+        .map(|(term, count)| (edit_distance(word, term), Reverse(count), term))
+        .filter(|(distance, _, _)| *distance <= max_edits)
+        .min()
+        .map(|(_, _, term)| term.to_string())
+}
+
+/// Builds corrected query where words are replaced by passed function which
+/// is called with lowercase words. Separators of query are kept as is.
+#[cfg(any(
+    feature = "default-search",
+    feature = "tantivy-search",
+    feature = "sqlite-search"
+))]
+pub fn correct_query<F>(query: &str, mut correct: F) -> Option<QuerySuggestion>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut suggestion = QuerySuggestion {
+        text: String::with_capacity(query.len()),
+        highlighted: String::with_capacity(query.len()),
+    };
+
+    let mut is_corrected = false;
+    let mut word_start: Option<usize> = None;
+    let boundaries = query.char_indices().chain([(query.len(), ' ')]);
+    for (index, symbol) in boundaries {
+        match (symbol.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(index),
+            (false, Some(start)) => {
+                let word = &query[start..index];
+                match correct(&word.to_lowercase()) {
+                    Some(correction) => {
+                        is_corrected = true;
+                        suggestion.text.push_str(&correction);
+                        let tagged = format!("<em>{}</em>", correction);
+                        suggestion.highlighted.push_str(&tagged);
+                    }
+                    None => {
+                        suggestion.text.push_str(word);
+                        suggestion.highlighted.push_str(word);
+                    }
+                }
+                word_start = None;
+            }
+            _ => {}
+        }
+
+        if word_start.is_none() && index < query.len() && !symbol.is_alphanumeric() {
+            suggestion.text.push(symbol);
+            suggestion.highlighted.push(symbol);
+        }
+    }
+
+    is_corrected.then_some(suggestion)
+}
+
+/// Merges corrected queries of several backends keeping order of backends.
+pub fn merge_query_suggestions(suggestions: Vec<QuerySuggestion>) -> Vec<QuerySuggestion> {
+    let mut merged: Vec<QuerySuggestion> = Vec::new();
+    for suggestion in suggestions {
+        if !merged.iter().any(|merged| merged.text == suggestion.text) {
+            merged.push(suggestion);
+        }
+    }
+
+    merged.truncate(DID_YOU_MEAN_SIZE);
+    merged
+}
+
+/// Sums counts of the same completions of several buckets or backends and
+/// keeps the most frequent ones of each field. Names are followed by terms.
+pub fn merge_suggestions(suggestions: Vec<Suggestion>, size: usize) -> Vec<Suggestion> {
+    let mut counts: HashMap<(SuggestField, String), u64> = HashMap::new();
+    for suggestion in suggestions {
+        *counts
+            .entry((suggestion.field, suggestion.text))
+            .or_default() += suggestion.count;
+    }
+
+    let mut merged = counts
+        .into_iter()
+        .map(|((field, text), count)| Suggestion { text, field, count })
+        .collect::<Vec<_>>();
+
+    merged.sort_by(|a, b| {
+        let field_order = |field: SuggestField| field != SuggestField::DocumentName;
+        (field_order(a.field), Reverse(a.count), &a.text).cmp(&(
+            field_order(b.field),
+            Reverse(b.count),
+            &b.text,
+        ))
+    });
+
+    let mut field_counts: HashMap<SuggestField, usize> = HashMap::new();
+    merged.retain(|suggestion| {
+        let field_count = field_counts.entry(suggestion.field).or_default();
+        *field_count += 1;
+        *field_count <= size
+    });
+
+    merged
+}
+
+#[cfg(test)]
+mod suggest_tests {
+    use super::*;
+
+    fn build_form(prefix: &str) -> SuggestForm {
+        SuggestForm {
+            prefix: prefix.to_string(),
+            size: 10,
+            buckets: None,
+        }
+    }
+
+    #[test]
+    fn parse_prefix_test() {
+        let form = build_form("Quarterly Rep");
+        #[cfg(any(
+            feature = "default-search",
+            feature = "tantivy-search",
+            feature = "sqlite-search"
+        ))]
+        assert_eq!(form.name_prefix(), "quarterly rep");
+        assert_eq!(form.term_prefix().unwrap(), "rep");
+        assert!(build_form("report ").term_prefix().is_none());
+
+        assert!(build_form("  ").validate().is_err());
+        let form = SuggestForm {
+            size: MAX_SUGGEST_SIZE + 1,
+            ..build_form("rep")
+        };
+        assert!(form.validate().is_err());
+    }
+
+    #[cfg(any(
+        feature = "default-search",
+        feature = "tantivy-search",
+        feature = "sqlite-search"
+    ))]
+    #[test]
+    fn correct_query_test() {
+        assert_eq!(edit_distance("commandr", "commander"), 1);
+        assert_eq!(edit_distance("tenant", "tneant"), 2);
+        assert_eq!(max_edits("fee"), 1);
+
+        let candidates = [("commander", 4), ("commanded", 1), ("commend", 9)];
+        let correction = best_correction("commandr", 0, candidates);
+        assert_eq!(correction.as_deref(), Some("commander"));
+        assert!(best_correction("commandr", 1, candidates).is_none());
+        assert!(best_correction("of", 0, [("on", 5)]).is_none());
+
+        let suggestion = correct_query("The Commandr, fleet", |word| match word {
+            "commandr" => Some("commander".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(suggestion.text, "The commander, fleet");
+        assert_eq!(suggestion.highlighted, "The <em>commander</em>, fleet");
+        assert!(correct_query("fleet", |_| None).is_none());
+
+        assert!(is_low_result(0, "commandr"));
+        assert!(!is_low_result(0, "*"));
+    }
+
+    #[test]
+    fn merge_suggestions_test() {
+        let suggestions = vec![
+            Suggestion::new("report", SuggestField::EntityData, 2),
+            Suggestion::new("reply", SuggestField::EntityData, 3),
+            Suggestion::new("report", SuggestField::EntityData, 2),
+            Suggestion::new("Report.docx", SuggestField::DocumentName, 1),
+        ];
+
+        let merged = merge_suggestions(suggestions, 1);
+        assert_eq!(
+            merged,
+            vec![
+                Suggestion::new("Report.docx", SuggestField::DocumentName, 1),
+                Suggestion::new("report", SuggestField::EntityData, 4),
+            ]
+        );
+    }
+}